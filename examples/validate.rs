@@ -15,10 +15,18 @@ impl ValidatorCommand {
     fn run(&self) -> anyhow::Result<()> {
         match self {
             Self::Feed { file } => {
-                let json = std::fs::read_to_string(&file)?;
+                let json = std::fs::read_to_string(file)?;
                 let feed: Feed<'_> = serde_json::from_str(&json)?;
-                let output = serde_json::to_string_pretty(&feed)?;
-                println!("{output}");
+                let report = feed.validate();
+
+                for issue in report.issues.iter() {
+                    println!("{issue}");
+                }
+
+                if report.has_errors() {
+                    anyhow::bail!("{} does not follow the specification", file.display());
+                }
+
                 Ok(())
             }
         }
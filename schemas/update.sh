@@ -0,0 +1,85 @@
+#!/bin/sh
+# Replace the vendored schemas with the published ones.
+#
+# Usage: schemas/update.sh [OPDS_COMMIT] [READIUM_COMMIT]
+#
+# Each schema is downloaded from the given commit (the head of the default branch if not
+# given) of its source repository, and every schema that it refers to with "$ref" is then
+# downloaded in turn. The commits used are written to schemas/SOURCES, and the files are
+# written out unchanged.
+set -eu
+
+dir=$(cd "$(dirname "$0")" && pwd)
+
+opds_repo=https://github.com/opds-community/drafts
+readium_repo=https://github.com/readium/webpub-manifest
+
+resolve() {
+    if [ -n "$2" ]; then
+        echo "$2"
+    else
+        git ls-remote "$1" HEAD | cut -f1
+    fi
+}
+
+opds_commit=$(resolve "$opds_repo" "${1:-}")
+readium_commit=$(resolve "$readium_repo" "${2:-}")
+
+# Map a schema's $id to the raw file at the pinned commit and to its path in this directory.
+source_url() {
+    case "$1" in
+        https://drafts.opds.io/schema/*)
+            echo "https://raw.githubusercontent.com/opds-community/drafts/$opds_commit/schema/${1#https://drafts.opds.io/schema/}" ;;
+        https://readium.org/webpub-manifest/schema/*)
+            echo "https://raw.githubusercontent.com/readium/webpub-manifest/$readium_commit/schema/${1#https://readium.org/webpub-manifest/schema/}" ;;
+        *) return 1 ;;
+    esac
+}
+
+local_path() {
+    case "$1" in
+        https://drafts.opds.io/schema/*) echo "$dir/opds/${1#https://drafts.opds.io/schema/}" ;;
+        https://readium.org/webpub-manifest/schema/*) echo "$dir/readium/${1#https://readium.org/webpub-manifest/schema/}" ;;
+    esac
+}
+
+rm -rf "$dir/opds" "$dir/readium"
+
+queue="https://drafts.opds.io/schema/feed.schema.json"
+seen=""
+
+while [ -n "$queue" ]; do
+    id=${queue%% *}
+    queue=$(echo "${queue#"$id"}" | sed 's/^ *//')
+
+    case " $seen " in *" $id "*) continue ;; esac
+    seen="$seen $id"
+
+    url=$(source_url "$id") || { echo "no source for $id" >&2; exit 1; }
+    path=$(local_path "$id")
+    mkdir -p "$(dirname "$path")"
+    curl -fsSL "$url" -o "$path"
+    echo "$id"
+
+    base=${id%/*}
+    for ref in $(grep -o '"\$ref": *"[^"#]*' "$path" | sed 's/.*"//' | sort -u); do
+        case "$ref" in
+            https://*) next=$ref ;;
+            *) next=$base/$ref ;;
+        esac
+        # Resolve "../" segments left by relative references.
+        while echo "$next" | grep -q '/[^/]*/\.\./'; do
+            next=$(echo "$next" | sed 's|/[^/]*/\.\./|/|')
+        done
+        queue="$queue $next"
+    done
+done
+
+cat > "$dir/SOURCES" <<EOF
+$opds_repo/tree/$opds_commit/schema
+$readium_repo/tree/$readium_commit/schema
+EOF
+
+echo
+echo "Update SCHEMAS in src/v2_0/validate/schema.rs to include:"
+(cd "$dir" && find opds readium -name '*.json' | sort | sed 's|.*|    include_str!("../../../schemas/&"),|')
@@ -88,6 +88,24 @@ where
     }
 }
 
+/// The name of the newtype that a single value is wrapped in when [serialize_flattened_vec]
+/// writes it without the surrounding array.
+///
+/// Serializers for formats like JSON write newtypes out as the value they wrap, but this lets
+/// a serializer that wants the full form of every field write the array back out.
+pub(crate) const SINGLETON_KEY: &str = "$opds::private::Singleton";
+
+struct Singleton<'t, T>(&'t T);
+
+impl<T: Serialize> Serialize for Singleton<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(SINGLETON_KEY, self.0)
+    }
+}
+
 pub(crate) fn serialize_flattened_vec<T, S>(
     input: &Vec<T>,
     serializer: S,
@@ -97,7 +115,7 @@ where
     T: Serialize,
 {
     if input.len() == 1 {
-        input[0].serialize(serializer)
+        Singleton(&input[0]).serialize(serializer)
     } else {
         input.serialize(serializer)
     }
@@ -1,5 +1,10 @@
 pub mod mime;
+pub mod opensearch;
 pub mod schema;
+pub mod search;
+pub mod template;
+pub mod v1_2;
 pub mod v2_0;
+pub mod xml;
 
 pub(crate) mod helpers;
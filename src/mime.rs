@@ -1,4 +1,402 @@
 //! Constants for common MIME types used within OPDS
+//!
+//! The [MediaType] type can be used to parse these, along with their parameters, so that types
+//! can be compared without worrying about case, spacing or the order of their parameters, and
+//! so that things like whether a link leads to an OPDS feed or to a DRM-protected publication
+//! can be checked.
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
 pub const APPLICATION_OPDS_JSON: &str = "application/opds+json";
 pub const APPLICATION_OPDS_PUBLICATION_JSON: &str = "application/opds-publication+json";
+pub const APPLICATION_WEBPUB_JSON: &str = "application/webpub+json";
+pub const APPLICATION_AUDIOBOOK_JSON: &str = "application/audiobook+json";
+pub const APPLICATION_DIVINA_JSON: &str = "application/divina+json";
+pub const APPLICATION_OPDS_AUTHENTICATION_JSON: &str = "application/opds-authentication+json";
+pub const APPLICATION_VND_OPDS_AUTHENTICATION_JSON: &str =
+    "application/vnd.opds.authentication.v1.0+json";
+
+pub const APPLICATION_OPENSEARCHDESCRIPTION_XML: &str = "application/opensearchdescription+xml";
+
+pub const APPLICATION_ATOM_XML: &str = "application/atom+xml";
+pub const APPLICATION_ATOM_XML_NAVIGATION: &str =
+    "application/atom+xml;profile=opds-catalog;kind=navigation";
+pub const APPLICATION_ATOM_XML_ACQUISITION: &str =
+    "application/atom+xml;profile=opds-catalog;kind=acquisition";
+pub const APPLICATION_ATOM_XML_ENTRY: &str = "application/atom+xml;type=entry;profile=opds-catalog";
+
+pub const APPLICATION_EPUB_ZIP: &str = "application/epub+zip";
+pub const APPLICATION_PDF: &str = "application/pdf";
+pub const APPLICATION_VND_COMICBOOK_ZIP: &str = "application/vnd.comicbook+zip";
+pub const APPLICATION_X_CBZ: &str = "application/x-cbz";
+pub const APPLICATION_WEBPUB_ZIP: &str = "application/webpub+zip";
+pub const APPLICATION_AUDIOBOOK_ZIP: &str = "application/audiobook+zip";
+pub const APPLICATION_DIVINA_ZIP: &str = "application/divina+zip";
+
+pub const APPLICATION_VND_READIUM_LCP_LICENSE_JSON: &str =
+    "application/vnd.readium.lcp.license.v1.0+json";
+pub const APPLICATION_VND_READIUM_LICENSE_STATUS_JSON: &str =
+    "application/vnd.readium.license.status.v1.0+json";
+pub const APPLICATION_PDF_LCP: &str = "application/pdf+lcp";
+pub const APPLICATION_AUDIOBOOK_LCP: &str = "application/audiobook+lcp";
+pub const APPLICATION_DIVINA_LCP: &str = "application/divina+lcp";
+pub const APPLICATION_VND_ADOBE_ADEPT_XML: &str = "application/vnd.adobe.adept+xml";
+
+pub const APPLICATION_X_MOBIPOCKET_EBOOK: &str = "application/x-mobipocket-ebook";
+pub const APPLICATION_X_MOBI8_EBOOK: &str = "application/x-mobi8-ebook";
+pub const APPLICATION_VND_AMAZON_EBOOK: &str = "application/vnd.amazon.ebook";
+
+/// An error encountered while parsing a [MediaType].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The string is not a `type/subtype` optionally followed by `;name=value` parameters.
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(value) => write!(f, "invalid media type {value:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A well-known format for publications and the files used to acquire them.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Format {
+    Epub,
+    Pdf,
+    /// A comic book archive.
+    Cbz,
+    /// A Readium Web Publication, either as a manifest or a package.
+    Webpub,
+    /// An audiobook, either as a manifest or a package.
+    Audiobook,
+    /// A Divina visual narrative, either as a manifest or a package.
+    Divina,
+    /// A Readium LCP license, which is used to acquire an LCP-protected publication.
+    LcpLicense,
+    /// A PDF protected with Readium LCP.
+    LcpPdf,
+    /// An audiobook protected with Readium LCP.
+    LcpAudiobook,
+    /// A Divina visual narrative protected with Readium LCP.
+    LcpDivina,
+    /// An Adobe Content Server Message, used to acquire publications protected with Adobe DRM.
+    AdobeAcsm,
+    /// A Mobipocket or Kindle book.
+    Kindle,
+}
+
+/// The formats that each well-known type and subtype belongs to.
+const FORMATS: &[(&str, Format)] = &[
+    (APPLICATION_EPUB_ZIP, Format::Epub),
+    (APPLICATION_PDF, Format::Pdf),
+    (APPLICATION_VND_COMICBOOK_ZIP, Format::Cbz),
+    (APPLICATION_X_CBZ, Format::Cbz),
+    (APPLICATION_WEBPUB_JSON, Format::Webpub),
+    (APPLICATION_WEBPUB_ZIP, Format::Webpub),
+    (APPLICATION_AUDIOBOOK_JSON, Format::Audiobook),
+    (APPLICATION_AUDIOBOOK_ZIP, Format::Audiobook),
+    (APPLICATION_DIVINA_JSON, Format::Divina),
+    (APPLICATION_DIVINA_ZIP, Format::Divina),
+    (APPLICATION_VND_READIUM_LCP_LICENSE_JSON, Format::LcpLicense),
+    (APPLICATION_PDF_LCP, Format::LcpPdf),
+    (APPLICATION_AUDIOBOOK_LCP, Format::LcpAudiobook),
+    (APPLICATION_DIVINA_LCP, Format::LcpDivina),
+    (APPLICATION_VND_ADOBE_ADEPT_XML, Format::AdobeAcsm),
+    (APPLICATION_X_MOBIPOCKET_EBOOK, Format::Kindle),
+    (APPLICATION_X_MOBI8_EBOOK, Format::Kindle),
+    (APPLICATION_VND_AMAZON_EBOOK, Format::Kindle),
+];
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|c| c.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&c))
+}
+
+/// A parsed media type, such as `application/atom+xml;profile=opds-catalog;kind=acquisition`.
+///
+/// Types, subtypes and parameter names are compared without regard to case, and parameters
+/// are compared without regard to their order.
+#[derive(Clone, Debug)]
+pub struct MediaType<'a> {
+    ty: Cow<'a, str>,
+    subtype: Cow<'a, str>,
+    params: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> MediaType<'a> {
+    pub fn parse(s: &'a str) -> Result<Self, Error> {
+        let invalid = || Error::Invalid(s.to_string());
+
+        let (essence, mut rest) = s.split_once(';').unwrap_or((s, ""));
+        let (ty, subtype) = essence.trim().split_once('/').ok_or_else(invalid)?;
+
+        if !is_token(ty) || !is_token(subtype) {
+            return Err(invalid());
+        }
+
+        let mut params = vec![];
+
+        loop {
+            rest = rest.trim_start_matches([' ', '\t', ';']);
+
+            if rest.is_empty() {
+                break;
+            }
+
+            let (name, after) = rest.split_once('=').ok_or_else(invalid)?;
+            let name = name.trim_end();
+
+            if !is_token(name) {
+                return Err(invalid());
+            }
+
+            let (value, after) = match after.strip_prefix('"') {
+                Some(quoted) => {
+                    let mut value = String::new();
+                    let mut chars = quoted.char_indices();
+
+                    let end = loop {
+                        match chars.next().ok_or_else(invalid)? {
+                            (i, '"') => break i,
+                            (_, '\\') => value.push(chars.next().ok_or_else(invalid)?.1),
+                            (_, c) => value.push(c),
+                        }
+                    };
+
+                    (Cow::Owned(value), &quoted[end + 1..])
+                }
+                None => {
+                    let (value, after) = after.split_at(after.find(';').unwrap_or(after.len()));
+                    let value = value.trim_end();
+
+                    if !is_token(value) {
+                        return Err(invalid());
+                    }
+
+                    (Cow::Borrowed(value), after)
+                }
+            };
+
+            if !after.trim_start().is_empty() && !after.trim_start().starts_with(';') {
+                return Err(invalid());
+            }
+
+            params.push((Cow::Borrowed(name), value));
+            rest = after;
+        }
+
+        Ok(Self {
+            ty: Cow::Borrowed(ty),
+            subtype: Cow::Borrowed(subtype),
+            params,
+        })
+    }
+
+    /// The top-level type, like `application`.
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+
+    /// The subtype, like `atom+xml`.
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// The structured syntax suffix of the subtype, like `xml` for `atom+xml`, or `lcp` for
+    /// `pdf+lcp`.
+    pub fn suffix(&self) -> Option<&str> {
+        self.subtype.rsplit_once('+').map(|(_, suffix)| suffix)
+    }
+
+    /// The type and subtype without any parameters, in lowercase.
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.ty, self.subtype).to_ascii_lowercase()
+    }
+
+    /// Whether this has the same type and subtype as `other`, ignoring parameters.
+    ///
+    /// Returns `false` if `other` is not a valid media type.
+    pub fn is(&self, other: &str) -> bool {
+        MediaType::parse(other).is_ok_and(|other| {
+            self.ty.eq_ignore_ascii_case(&other.ty)
+                && self.subtype.eq_ignore_ascii_case(&other.subtype)
+        })
+    }
+
+    /// The value of the parameter with the given name.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_ref())
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(n, v)| (n.as_ref(), v.as_ref()))
+    }
+
+    /// The `profile` parameter, like `opds-catalog`.
+    pub fn profile(&self) -> Option<&str> {
+        self.param("profile")
+    }
+
+    /// The well-known format of this type, if it has one.
+    pub fn format(&self) -> Option<Format> {
+        FORMATS
+            .iter()
+            .find(|(mime, _)| self.is(mime))
+            .map(|(_, format)| *format)
+    }
+
+    /// Whether this is the type of an OPDS 2.0 or OPDS 1.2 feed.
+    pub fn is_opds_feed(&self) -> bool {
+        self.is(APPLICATION_OPDS_JSON)
+            || (self.is(APPLICATION_ATOM_XML)
+                && self.profile() == Some("opds-catalog")
+                && self.param("type") != Some("entry"))
+    }
+
+    /// Whether this is the type of an OPDS 2.0 or OPDS 1.2 publication.
+    pub fn is_opds_publication(&self) -> bool {
+        self.is(APPLICATION_OPDS_PUBLICATION_JSON)
+            || (self.is(APPLICATION_ATOM_XML)
+                && self.profile() == Some("opds-catalog")
+                && self.param("type") == Some("entry"))
+    }
+
+    /// Whether this is the type of a publication protected by DRM, or of a file used to
+    /// acquire one, like a Readium LCP license or an Adobe ACSM file.
+    pub fn is_drm_protected(&self) -> bool {
+        let drm = matches!(
+            self.format(),
+            Some(
+                Format::LcpLicense
+                    | Format::LcpPdf
+                    | Format::LcpAudiobook
+                    | Format::LcpDivina
+                    | Format::AdobeAcsm
+            )
+        );
+
+        drm || self.suffix().is_some_and(|s| s.eq_ignore_ascii_case("lcp"))
+    }
+
+    /// The parameters in a consistent order, for comparing and hashing.
+    fn sorted_params(&self) -> Vec<(String, &str)> {
+        let mut params: Vec<_> = self
+            .params
+            .iter()
+            .map(|(n, v)| (n.to_ascii_lowercase(), v.as_ref()))
+            .collect();
+        params.sort();
+        params
+    }
+}
+
+impl PartialEq for MediaType<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.essence() == other.essence() && self.sorted_params() == other.sorted_params()
+    }
+}
+
+impl Eq for MediaType<'_> {}
+
+impl Hash for MediaType<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.essence().hash(state);
+        self.sorted_params().hash(state);
+    }
+}
+
+impl fmt::Display for MediaType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.ty, self.subtype)?;
+
+        for (name, value) in self.params.iter() {
+            if is_token(value) {
+                write!(f, ";{name}={value}")?;
+            } else {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, ";{name}=\"{escaped}\"")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mt(s: &str) -> MediaType<'_> {
+        MediaType::parse(s).expect("valid media type")
+    }
+
+    #[test]
+    fn test_parse() {
+        let acquisition = mt("Application/Atom+XML; kind=acquisition; profile=\"opds-catalog\"");
+        assert_eq!(acquisition.ty(), "Application");
+        assert_eq!(acquisition.subtype(), "Atom+XML");
+        assert_eq!(acquisition.suffix(), Some("XML"));
+        assert_eq!(acquisition.essence(), "application/atom+xml");
+        assert_eq!(acquisition.profile(), Some("opds-catalog"));
+        assert_eq!(acquisition.param("KIND"), Some("acquisition"));
+        assert_eq!(acquisition, mt(APPLICATION_ATOM_XML_ACQUISITION));
+        assert_ne!(acquisition, mt(APPLICATION_ATOM_XML_NAVIGATION));
+        assert!(acquisition.is(APPLICATION_ATOM_XML));
+
+        assert_eq!(
+            mt("text/plain;title=\"a \\\"b\\\"\"").param("title"),
+            Some("a \"b\"")
+        );
+        assert_eq!(
+            mt("text/plain ; title=\"a b\";").to_string(),
+            "text/plain;title=\"a b\""
+        );
+        assert_eq!(
+            mt(APPLICATION_ATOM_XML_ENTRY).to_string(),
+            APPLICATION_ATOM_XML_ENTRY
+        );
+
+        for invalid in [
+            "",
+            "application",
+            "application/",
+            "a b/c",
+            "text/plain;x",
+            "text/plain;x=\"y",
+        ] {
+            assert!(MediaType::parse(invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert!(mt(APPLICATION_OPDS_JSON).is_opds_feed());
+        assert!(mt(APPLICATION_ATOM_XML_NAVIGATION).is_opds_feed());
+        assert!(!mt(APPLICATION_ATOM_XML).is_opds_feed());
+        assert!(!mt(APPLICATION_ATOM_XML_ENTRY).is_opds_feed());
+        assert!(mt(APPLICATION_ATOM_XML_ENTRY).is_opds_publication());
+        assert!(mt(APPLICATION_OPDS_PUBLICATION_JSON).is_opds_publication());
+
+        assert!(mt(APPLICATION_VND_READIUM_LCP_LICENSE_JSON).is_drm_protected());
+        assert!(mt(APPLICATION_VND_ADOBE_ADEPT_XML).is_drm_protected());
+        assert!(mt("application/epub+lcp").is_drm_protected());
+        assert!(!mt(APPLICATION_EPUB_ZIP).is_drm_protected());
+
+        assert_eq!(mt("APPLICATION/EPUB+ZIP").format(), Some(Format::Epub));
+        assert_eq!(mt(APPLICATION_X_CBZ).format(), Some(Format::Cbz));
+        assert_eq!(mt(APPLICATION_X_MOBI8_EBOOK).format(), Some(Format::Kindle));
+        assert_eq!(mt("text/html").format(), None);
+    }
+}
@@ -0,0 +1,541 @@
+//! Support for OpenSearch description documents
+//!
+//! OPDS 1.2 catalogs usually advertise their search endpoint with a link to an
+//! [OpenSearch 1.1] description document, served as
+//! [crate::mime::APPLICATION_OPENSEARCHDESCRIPTION_XML], rather than with a URI template.
+//! The description lists one or more [Url] templates, each producing results in a different
+//! format, using OpenSearch's own template syntax such as `{searchTerms}` and `{startPage?}`.
+//!
+//! An [OpenSearchDescription] can be parsed using [OpenSearchDescription::from_xml], and
+//! written back out using [OpenSearchDescription::to_xml]. Its templates can be turned into
+//! [templated] OPDS 2.0 links using [Url::to_link] or [OpenSearchDescription::to_link], which
+//! rename the standard parameters to the names used by [crate::search], so that they can be
+//! passed to [SearchCapability::from_links].
+//!
+//! The [Parameter extension] for describing the query string of a template as separate
+//! parameters is also supported.
+//!
+//! [OpenSearch 1.1]: https://github.com/dewitt/opensearch/blob/master/opensearch-1-1-draft-6.md
+//! [templated]: crate::v2_0::Link::templated
+//! [SearchCapability::from_links]: crate::search::SearchCapability::from_links
+//! [Parameter extension]: https://github.com/dewitt/opensearch/blob/master/mediawiki/Specifications/OpenSearch/Extensions/Parameter/1.0/Draft%202.wiki
+use std::borrow::Cow;
+
+use crate::mime::{APPLICATION_ATOM_XML, APPLICATION_OPDS_JSON};
+use crate::v2_0::Link;
+use crate::v2_0::metadata::Relation;
+use crate::xml::Error;
+
+mod parse;
+mod write;
+
+/// OpenSearch template parameters that are renamed to their [crate::search] equivalents
+/// when converting to an RFC 6570 template.
+const RENAMED: &[(&str, &str)] = &[
+    ("searchTerms", "query"),
+    ("startPage", "page"),
+    ("atom:author", "author"),
+    ("atom:title", "title"),
+];
+
+/// A single query-string parameter of a [Url], from the Parameter extension.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Parameter<'a> {
+    pub name: Cow<'a, str>,
+
+    /// The value of the parameter, which may be a template parameter like `{searchTerms}`.
+    pub value: Cow<'a, str>,
+
+    /// The minimum number of times the parameter must appear, which defaults to 1.
+    pub minimum: Option<usize>,
+
+    /// The maximum number of times the parameter may appear, which defaults to 1.
+    pub maximum: Option<usize>,
+}
+
+impl<'a> Parameter<'a> {
+    pub fn new(name: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            minimum: None,
+            maximum: None,
+        }
+    }
+}
+
+/// A template for building search request URLs.
+///
+/// See [The "Url" element] for more information.
+///
+/// [The "Url" element]: https://github.com/dewitt/opensearch/blob/master/opensearch-1-1-draft-6.md#the-url-element
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Url<'a> {
+    /// The OpenSearch template, such as `https://example.com/search?q={searchTerms}`.
+    pub template: Cow<'a, str>,
+
+    /// The media type of the search results.
+    pub mime: Cow<'a, str>,
+
+    /// The roles of the resource being requested, which defaults to `results`.
+    pub rel: Vec<Cow<'a, str>>,
+
+    /// The index of the first search result, which defaults to 1.
+    pub index_offset: Option<usize>,
+
+    /// The number of the first page of search results, which defaults to 1.
+    pub page_offset: Option<usize>,
+
+    /// The HTTP method to use, from the Parameter extension's `parameters:method`.
+    pub method: Option<Cow<'a, str>>,
+
+    /// Query-string parameters to add to the template, from the Parameter extension.
+    pub parameters: Vec<Parameter<'a>>,
+}
+
+impl<'a> Url<'a> {
+    pub fn new(template: impl Into<Cow<'a, str>>, mime: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            template: template.into(),
+            mime: mime.into(),
+            rel: vec![],
+            index_offset: None,
+            page_offset: None,
+            method: None,
+            parameters: vec![],
+        }
+    }
+
+    /// Whether this template returns search results, rather than something like suggestions.
+    pub fn is_results(&self) -> bool {
+        self.rel.is_empty() || self.rel.iter().any(|rel| rel == "results")
+    }
+
+    /// The full OpenSearch template, including any [parameters][Url::parameters].
+    pub fn full_template(&self) -> Cow<'_, str> {
+        if self.parameters.is_empty() {
+            return Cow::Borrowed(&self.template);
+        }
+
+        let mut template = self.template.to_string();
+        let mut sep = if template.contains('?') { '&' } else { '?' };
+
+        for param in self.parameters.iter() {
+            template.push(sep);
+            template.push_str(&param.name);
+            template.push('=');
+            template.push_str(&param.value);
+            sep = '&';
+        }
+
+        Cow::Owned(template)
+    }
+
+    /// Convert this template into the equivalent [RFC 6570] URI template.
+    ///
+    /// The standard `searchTerms` and `startPage` parameters become `query` and `page`, and
+    /// `atom:author` and `atom:title` become `author` and `title`. The colon in any other
+    /// namespaced parameter is replaced with a dot, so that `{geo:box}` becomes `{geo.box}`.
+    ///
+    /// Pages are numbered from 1 by [crate::search], and a URI template can't adjust the
+    /// value it is given, so templates using `startPage` with a
+    /// [page offset][Url::page_offset] other than 1 are rejected. Any other parameters, such
+    /// as `startIndex`, are passed through as they are.
+    ///
+    /// [RFC 6570]: https://www.rfc-editor.org/rfc/rfc6570
+    pub fn uri_template(&self) -> Result<String, Error> {
+        let template = self.full_template();
+        let invalid = || Error::InvalidValue {
+            name: "template",
+            value: template.to_string(),
+        };
+
+        let mut out = String::new();
+        let mut rest = template.as_ref();
+
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..].find('}').ok_or_else(invalid)? + start;
+            let name = rest[start + 1..end].trim_end_matches('?');
+
+            if name.is_empty() {
+                return Err(invalid());
+            }
+
+            if let Some(offset) = self.page_offset.filter(|&o| o != 1 && name == "startPage") {
+                return Err(Error::InvalidValue {
+                    name: "pageOffset",
+                    value: offset.to_string(),
+                });
+            }
+
+            let name = match RENAMED.iter().find(|(from, _)| *from == name) {
+                Some((_, to)) => Cow::Borrowed(*to),
+                None => Cow::Owned(name.replace(':', ".")),
+            };
+
+            out.push_str(&rest[..start]);
+            out.push('{');
+            out.push_str(&name);
+            out.push('}');
+            rest = &rest[end + 1..];
+        }
+
+        if rest.contains('}') {
+            return Err(invalid());
+        }
+
+        out.push_str(rest);
+
+        Ok(out)
+    }
+
+    /// Convert this template into a [templated] search link.
+    ///
+    /// See [Url::uri_template] for how the template is converted.
+    ///
+    /// [templated]: Link::templated
+    pub fn to_link(&self) -> Result<Link<'static>, Error> {
+        let mut link = Link::template(
+            self.uri_template()?.into(),
+            Some(self.mime.to_string().into()),
+        );
+        link.rel.push(Relation::Search);
+
+        Ok(link)
+    }
+}
+
+/// An image that can be used to represent the search engine.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Image<'a> {
+    pub url: Cow<'a, str>,
+    pub mime: Option<Cow<'a, str>>,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+}
+
+impl<'a> Image<'a> {
+    pub fn new(url: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            url: url.into(),
+            mime: None,
+            width: None,
+            height: None,
+        }
+    }
+
+    pub fn with_mime(mut self, mime: impl Into<Cow<'a, str>>) -> Self {
+        self.mime = Some(mime.into());
+        self
+    }
+
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn with_height(mut self, height: usize) -> Self {
+        self.height = Some(height);
+        self
+    }
+}
+
+/// An example search, or a search related to the results.
+///
+/// See [The "Query" element] for more information.
+///
+/// [The "Query" element]: https://github.com/dewitt/opensearch/blob/master/opensearch-1-1-draft-6.md#the-query-element
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Query<'a> {
+    /// How this query relates to the search, such as `example` or `related`.
+    pub role: Cow<'a, str>,
+    pub title: Option<Cow<'a, str>>,
+    pub search_terms: Option<Cow<'a, str>>,
+}
+
+impl<'a> Query<'a> {
+    pub fn new(role: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            role: role.into(),
+            title: None,
+            search_terms: None,
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<Cow<'a, str>>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_search_terms(mut self, search_terms: impl Into<Cow<'a, str>>) -> Self {
+        self.search_terms = Some(search_terms.into());
+        self
+    }
+}
+
+/// An OpenSearch 1.1 description document.
+///
+/// See [OpenSearch description document] for more information.
+///
+/// [OpenSearch description document]: https://github.com/dewitt/opensearch/blob/master/opensearch-1-1-draft-6.md#opensearch-description-document
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct OpenSearchDescription<'a> {
+    /// A short name for the search engine, from `ShortName`.
+    pub short_name: Cow<'a, str>,
+
+    pub description: Cow<'a, str>,
+
+    /// The templates for building search requests, from `Url`.
+    pub urls: Vec<Url<'a>>,
+
+    /// A longer name for the search engine, from `LongName`.
+    pub long_name: Option<Cow<'a, str>>,
+
+    /// Space-separated keywords describing the search engine, from `Tags`.
+    pub tags: Option<Cow<'a, str>>,
+
+    /// An email address for the maintainer of the description, from `Contact`.
+    pub contact: Option<Cow<'a, str>>,
+
+    pub developer: Option<Cow<'a, str>>,
+    pub attribution: Option<Cow<'a, str>>,
+
+    /// How the search results may be used, such as `open` or `limited`, from
+    /// `SyndicationRight`.
+    pub syndication_right: Option<Cow<'a, str>>,
+
+    pub adult_content: bool,
+
+    pub images: Vec<Image<'a>>,
+    pub queries: Vec<Query<'a>>,
+
+    /// The languages of the search results, where `*` means any language.
+    pub languages: Vec<Cow<'a, str>>,
+
+    /// The character encodings accepted in search requests, from `InputEncoding`.
+    ///
+    /// When empty, only `UTF-8` is supported.
+    pub input_encodings: Vec<Cow<'a, str>>,
+
+    /// The character encodings used in search results, from `OutputEncoding`.
+    pub output_encodings: Vec<Cow<'a, str>>,
+}
+
+impl<'a> OpenSearchDescription<'a> {
+    pub fn new(short_name: impl Into<Cow<'a, str>>, description: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            short_name: short_name.into(),
+            description: description.into(),
+            urls: vec![],
+            long_name: None,
+            tags: None,
+            contact: None,
+            developer: None,
+            attribution: None,
+            syndication_right: None,
+            adult_content: false,
+            images: vec![],
+            queries: vec![],
+            languages: vec![],
+            input_encodings: vec![],
+            output_encodings: vec![],
+        }
+    }
+
+    pub fn with_url(mut self, url: Url<'a>) -> Self {
+        self.urls.push(url);
+        self
+    }
+
+    pub fn with_image(mut self, image: Image<'a>) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    pub fn with_query(mut self, query: Query<'a>) -> Self {
+        self.queries.push(query);
+        self
+    }
+
+    /// Parse an OpenSearch description document.
+    pub fn from_xml(xml: &str) -> Result<OpenSearchDescription<'static>, Error> {
+        parse::description_document(xml)
+    }
+
+    /// Write this description as an OpenSearch description document.
+    pub fn write_xml<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        write::description_document(self, writer)
+    }
+
+    /// Generate an OpenSearch description document for this description.
+    pub fn to_xml(&self) -> String {
+        let mut buf = Vec::new();
+        self.write_xml(&mut buf).expect("can write to buffer");
+        String::from_utf8(buf).expect("writer produces UTF-8")
+    }
+
+    /// The template for searching the catalog, preferring OPDS 2.0 results over OPDS 1.2.
+    pub fn url(&self) -> Option<&Url<'a>> {
+        let results = || self.urls.iter().filter(|url| url.is_results());
+
+        results()
+            .find(|url| url.mime == APPLICATION_OPDS_JSON)
+            .or_else(|| results().find(|url| url.mime.starts_with(APPLICATION_ATOM_XML)))
+            .or_else(|| results().next())
+    }
+
+    /// Convert the preferred [Url] into a [templated] search link, titled with the
+    /// [short name][OpenSearchDescription::short_name].
+    ///
+    /// See [Url::uri_template] for how the template is converted.
+    ///
+    /// [templated]: Link::templated
+    pub fn to_link(&self) -> Option<Result<Link<'static>, Error>> {
+        let url = self.url()?;
+
+        let link = url.to_link().map(|mut link| {
+            link.title = Some(self.short_name.to_string().into());
+            link
+        });
+
+        Some(link)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{SearchCapability, SearchQuery};
+    use anyhow::Context;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn read_file(name: &str) -> String {
+        let path = format!("{CRATE_DIR}/tests/{name}");
+
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("can load {path:?}"))
+            .expect("valid file")
+    }
+
+    #[test]
+    fn test_opensearch_roundtrip() {
+        for prefix in ["test-opensearch-calibre", "test-opensearch-parameters"] {
+            let xml_in = read_file(&format!("{prefix}.in.xml"));
+            let xml_exp = read_file(&format!("{prefix}.out.xml"));
+
+            let description = OpenSearchDescription::from_xml(&xml_in)
+                .with_context(|| format!("can parse {prefix:?} input"))
+                .expect("can parse description");
+            pretty_assertions::assert_eq!(description.to_xml(), xml_exp, "{prefix}");
+
+            let reparsed = OpenSearchDescription::from_xml(&xml_exp).expect("can reparse");
+            assert_eq!(reparsed.to_xml(), xml_exp, "{prefix}");
+        }
+    }
+
+    #[test]
+    fn test_opensearch_to_link() {
+        let xml = read_file("test-opensearch-calibre.in.xml");
+        let description = OpenSearchDescription::from_xml(&xml).unwrap();
+
+        assert_eq!(description.short_name, "calibre");
+        assert_eq!(description.input_encodings, vec!["UTF-8"]);
+
+        let link = description.to_link().unwrap().unwrap();
+        assert!(link.templated);
+        assert_eq!(link.title.as_deref(), Some("calibre"));
+        assert_eq!(
+            link.href.as_deref(),
+            Some("http://localhost:8080/opds/search/{query}")
+        );
+
+        let links = [link];
+        let search = SearchCapability::from_links(&links).unwrap();
+        assert_eq!(
+            search.url(&SearchQuery::new("moby dick")).unwrap(),
+            "http://localhost:8080/opds/search/moby%20dick"
+        );
+    }
+
+    #[test]
+    fn test_opensearch_parameters() {
+        let xml = read_file("test-opensearch-parameters.in.xml");
+        let description = OpenSearchDescription::from_xml(&xml).unwrap();
+
+        let url = description.url().unwrap();
+        assert_eq!(url.method.as_deref(), Some("GET"));
+        assert_eq!(url.parameters.len(), 4);
+        assert_eq!(
+            url.uri_template().unwrap(),
+            "https://example.com/opds/search?q={query}&author={author}&page={page}&box={geo.box}"
+        );
+
+        let mut url = Url::new("https://example.com/?q={searchTerms", APPLICATION_ATOM_XML);
+        assert!(url.uri_template().is_err());
+
+        url.template = "https://example.com/?q={}".into();
+        assert!(url.uri_template().is_err());
+
+        // Pages numbered from 0 can't be expressed using the 1-based page field.
+        url.template = "https://example.com/?q={searchTerms}&p={startPage?}".into();
+        url.page_offset = Some(0);
+        assert!(matches!(
+            url.uri_template(),
+            Err(Error::InvalidValue {
+                name: "pageOffset",
+                ..
+            })
+        ));
+
+        url.page_offset = Some(1);
+        assert_eq!(
+            url.uri_template().unwrap(),
+            "https://example.com/?q={query}&p={page}"
+        );
+
+        // The offset doesn't matter when the template doesn't use pages.
+        url.template = "https://example.com/?q={searchTerms}&i={startIndex}".into();
+        url.page_offset = Some(0);
+        assert_eq!(
+            url.uri_template().unwrap(),
+            "https://example.com/?q={query}&i={startIndex}"
+        );
+    }
+
+    #[test]
+    fn test_opensearch_builders() {
+        let description = OpenSearchDescription::new("Example", "Search the example catalog")
+            .with_url(Url::new(
+                "https://example.com/search?q={searchTerms}",
+                APPLICATION_OPDS_JSON,
+            ))
+            .with_image(
+                Image::new("https://example.com/icon.png")
+                    .with_mime("image/png")
+                    .with_width(16)
+                    .with_height(16),
+            )
+            .with_query(
+                Query::new("example")
+                    .with_title("Whales")
+                    .with_search_terms("moby dick"),
+            );
+
+        let reparsed = OpenSearchDescription::from_xml(&description.to_xml()).unwrap();
+        let image = &reparsed.images[0];
+        assert_eq!(image.url, "https://example.com/icon.png");
+        assert_eq!(image.mime.as_deref(), Some("image/png"));
+        assert_eq!((image.width, image.height), (Some(16), Some(16)));
+
+        let query = &reparsed.queries[0];
+        assert_eq!(query.role, "example");
+        assert_eq!(query.title.as_deref(), Some("Whales"));
+        assert_eq!(query.search_terms.as_deref(), Some("moby dick"));
+    }
+}
@@ -0,0 +1,164 @@
+//! Conversion of parsed XML elements into the types in [crate::opensearch].
+use std::borrow::Cow;
+
+use super::*;
+use crate::xml::{self, Element, OPENSEARCH, OPENSEARCH_PARAMETERS};
+
+const ROOT: &str = "OpenSearchDescription";
+
+fn text(element: &Element, name: &str) -> Option<Cow<'static, str>> {
+    element
+        .child(OPENSEARCH, name)
+        .map(|e| Cow::Owned(e.text()))
+}
+
+fn texts(element: &Element, name: &str) -> Vec<Cow<'static, str>> {
+    element
+        .elements()
+        .filter(|e| e.is(OPENSEARCH, name))
+        .map(|e| Cow::Owned(e.text()))
+        .collect()
+}
+
+fn required(
+    element: &Element,
+    parent: &'static str,
+    name: &'static str,
+) -> Result<Cow<'static, str>, Error> {
+    text(element, name).ok_or(Error::Missing { parent, name })
+}
+
+fn attr(
+    element: &Element,
+    parent: &'static str,
+    name: &'static str,
+) -> Result<Cow<'static, str>, Error> {
+    element
+        .attr(name)
+        .map(|value| Cow::Owned(value.to_string()))
+        .ok_or(Error::Missing { parent, name })
+}
+
+fn number(element: &Element, name: &'static str) -> Result<Option<usize>, Error> {
+    element
+        .attr(name)
+        .map(|value| {
+            value.trim().parse().map_err(|_| Error::InvalidValue {
+                name,
+                value: value.to_string(),
+            })
+        })
+        .transpose()
+}
+
+fn parameter(element: &Element) -> Result<Parameter<'static>, Error> {
+    let mut parameter = Parameter::new(
+        attr(element, "Parameter", "name")?,
+        attr(element, "Parameter", "value")?,
+    );
+    parameter.minimum = number(element, "minimum")?;
+    parameter.maximum = number(element, "maximum")?;
+
+    Ok(parameter)
+}
+
+fn url(element: &Element) -> Result<Url<'static>, Error> {
+    let mut url = Url::new(
+        attr(element, "Url", "template")?,
+        attr(element, "Url", "type")?,
+    );
+
+    if let Some(rel) = element.attr("rel") {
+        url.rel = rel
+            .split_whitespace()
+            .map(|rel| Cow::Owned(rel.to_string()))
+            .collect();
+    }
+
+    url.index_offset = number(element, "indexOffset")?;
+    url.page_offset = number(element, "pageOffset")?;
+    url.method = element
+        .attrs
+        .iter()
+        .find(|a| a.ns.as_deref() == Some(OPENSEARCH_PARAMETERS) && a.name == "method")
+        .map(|a| Cow::Owned(a.value.clone()));
+
+    for child in element.elements() {
+        if child.is(OPENSEARCH_PARAMETERS, "Parameter") {
+            url.parameters.push(parameter(child)?);
+        }
+    }
+
+    Ok(url)
+}
+
+fn image(element: &Element) -> Result<Image<'static>, Error> {
+    Ok(Image {
+        url: Cow::Owned(element.text()),
+        mime: element.attr("type").map(|t| Cow::Owned(t.to_string())),
+        width: number(element, "width")?,
+        height: number(element, "height")?,
+    })
+}
+
+fn query(element: &Element) -> Result<Query<'static>, Error> {
+    let value = |name| element.attr(name).map(|v| Cow::Owned(v.to_string()));
+
+    Ok(Query {
+        role: attr(element, "Query", "role")?,
+        title: value("title"),
+        search_terms: value("searchTerms"),
+    })
+}
+
+fn description(element: &Element) -> Result<OpenSearchDescription<'static>, Error> {
+    let mut description = OpenSearchDescription::new(
+        required(element, ROOT, "ShortName")?,
+        required(element, ROOT, "Description")?,
+    );
+
+    for child in element.elements() {
+        if child.is(OPENSEARCH, "Url") {
+            description.urls.push(url(child)?);
+        } else if child.is(OPENSEARCH, "Image") {
+            description.images.push(image(child)?);
+        } else if child.is(OPENSEARCH, "Query") {
+            description.queries.push(query(child)?);
+        }
+    }
+
+    if description.urls.is_empty() {
+        return Err(Error::Missing {
+            parent: ROOT,
+            name: "Url",
+        });
+    }
+
+    description.long_name = text(element, "LongName");
+    description.tags = text(element, "Tags");
+    description.contact = text(element, "Contact");
+    description.developer = text(element, "Developer");
+    description.attribution = text(element, "Attribution");
+    description.syndication_right = text(element, "SyndicationRight");
+    description.adult_content = text(element, "AdultContent")
+        .is_some_and(|v| !matches!(v.as_ref(), "false" | "FALSE" | "0" | "no" | "NO"));
+    description.languages = texts(element, "Language");
+    description.input_encodings = texts(element, "InputEncoding");
+    description.output_encodings = texts(element, "OutputEncoding");
+
+    Ok(description)
+}
+
+pub(super) fn description_document(xml: &str) -> Result<OpenSearchDescription<'static>, Error> {
+    match xml::parse(xml)? {
+        Some(root) if root.is(OPENSEARCH, ROOT) => description(&root),
+        Some(root) => Err(Error::UnexpectedRoot {
+            expected: ROOT,
+            found: root.name,
+        }),
+        None => Err(Error::UnexpectedRoot {
+            expected: ROOT,
+            found: String::new(),
+        }),
+    }
+}
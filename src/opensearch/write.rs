@@ -0,0 +1,145 @@
+//! Conversion of the types in [crate::opensearch] into XML elements.
+use super::*;
+use crate::xml::{Element, ElementWriter, OPENSEARCH, OPENSEARCH_PARAMETERS};
+
+/// The prefixes declared on the root element of every generated document.
+const PREFIXES: &[(&str, &str)] = &[(OPENSEARCH_PARAMETERS, "parameters")];
+
+fn text(name: &str, value: &str) -> Element {
+    Element::text_element(OPENSEARCH, name, value)
+}
+
+fn url(url: &Url<'_>) -> Element {
+    let mut element = Element::new(OPENSEARCH, "Url")
+        .with_attr("type", &url.mime)
+        .with_attr("template", &url.template);
+
+    if !url.rel.is_empty() {
+        element = element.with_attr("rel", &url.rel.join(" "));
+    }
+
+    if let Some(offset) = url.index_offset {
+        element = element.with_attr("indexOffset", &offset.to_string());
+    }
+
+    if let Some(offset) = url.page_offset {
+        element = element.with_attr("pageOffset", &offset.to_string());
+    }
+
+    if let Some(method) = &url.method {
+        element = element.with_ns_attr(Some(OPENSEARCH_PARAMETERS), "method", method);
+    }
+
+    for param in url.parameters.iter() {
+        let mut child = Element::new(OPENSEARCH_PARAMETERS, "Parameter")
+            .with_attr("name", &param.name)
+            .with_attr("value", &param.value);
+
+        if let Some(minimum) = param.minimum {
+            child = child.with_attr("minimum", &minimum.to_string());
+        }
+
+        if let Some(maximum) = param.maximum {
+            child = child.with_attr("maximum", &maximum.to_string());
+        }
+
+        element = element.with_child(child);
+    }
+
+    element
+}
+
+fn image(image: &Image<'_>) -> Element {
+    let mut element = Element::new(OPENSEARCH, "Image");
+
+    if let Some(height) = image.height {
+        element = element.with_attr("height", &height.to_string());
+    }
+
+    if let Some(width) = image.width {
+        element = element.with_attr("width", &width.to_string());
+    }
+
+    if let Some(mime) = &image.mime {
+        element = element.with_attr("type", mime);
+    }
+
+    element.with_text(&image.url)
+}
+
+fn query(query: &Query<'_>) -> Element {
+    let mut element = Element::new(OPENSEARCH, "Query").with_attr("role", &query.role);
+
+    if let Some(title) = &query.title {
+        element = element.with_attr("title", title);
+    }
+
+    if let Some(terms) = &query.search_terms {
+        element = element.with_attr("searchTerms", terms);
+    }
+
+    element
+}
+
+fn description(d: &OpenSearchDescription<'_>) -> Element {
+    let mut element = Element::new(OPENSEARCH, "OpenSearchDescription")
+        .with_child(text("ShortName", &d.short_name))
+        .with_child(text("Description", &d.description));
+
+    let optional = [
+        ("Tags", &d.tags),
+        ("Contact", &d.contact),
+        ("LongName", &d.long_name),
+        ("Developer", &d.developer),
+        ("Attribution", &d.attribution),
+        ("SyndicationRight", &d.syndication_right),
+    ];
+
+    for u in d.urls.iter() {
+        element = element.with_child(url(u));
+    }
+
+    for (name, value) in optional {
+        if let Some(value) = value {
+            element = element.with_child(text(name, value));
+        }
+    }
+
+    if d.adult_content {
+        element = element.with_child(text("AdultContent", "true"));
+    }
+
+    for i in d.images.iter() {
+        element = element.with_child(image(i));
+    }
+
+    for q in d.queries.iter() {
+        element = element.with_child(query(q));
+    }
+
+    let lists = [
+        ("Language", &d.languages),
+        ("InputEncoding", &d.input_encodings),
+        ("OutputEncoding", &d.output_encodings),
+    ];
+
+    for (name, values) in lists {
+        for value in values.iter() {
+            element = element.with_child(text(name, value));
+        }
+    }
+
+    element
+}
+
+pub(super) fn description_document<W: std::io::Write>(
+    d: &OpenSearchDescription<'_>,
+    writer: W,
+) -> std::io::Result<()> {
+    use quick_xml::events::{BytesDecl, Event};
+
+    let mut writer = quick_xml::Writer::new_with_indent(writer, b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    ElementWriter::new(PREFIXES).write_root(&mut writer, &description(d))?;
+    writer.get_mut().write_all(b"\n")
+}
@@ -7,6 +7,9 @@
 /// An article within a newspaper, magazine, or other publication.
 pub const SCHEMA_ORG_ARTICLE: &str = "http://schema.org/Article";
 
+/// An audiobook.
+pub const SCHEMA_ORG_AUDIOBOOK: &str = "http://schema.org/Audiobook";
+
 /// A book.
 pub const SCHEMA_ORG_BOOK: &str = "http://schema.org/Book";
 
@@ -0,0 +1,339 @@
+//! Searching OPDS catalogs
+//!
+//! Catalogs advertise their search endpoint through a link with the [Relation::Search]
+//! relation. In OPDS 2.0 this is a [templated] link to an `application/opds+json` feed, whose
+//! URI template lists the search parameters that the catalog understands. Catalogs based on
+//! OPDS 1.2 instead link to an [OpenSearch description] document, which needs to be fetched
+//! first to find out how to build search URLs. Once fetched, it can be converted into a
+//! templated link using [OpenSearchDescription::to_link].
+//!
+//! A [SearchCapability] can be extracted from a feed using [Feed::search], and then used to
+//! turn a [SearchQuery] into the URL of the results feed.
+//!
+//! [templated]: Link::templated
+//! [OpenSearchDescription::to_link]: crate::opensearch::OpenSearchDescription::to_link
+//! [OpenSearch description]: https://github.com/dewitt/opensearch/blob/master/opensearch-1-1-draft-6.md#opensearch-description-document
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::mime::{
+    APPLICATION_ATOM_XML, APPLICATION_OPDS_JSON, APPLICATION_OPENSEARCHDESCRIPTION_XML,
+};
+use crate::template::{self, UriTemplate, Variables};
+use crate::v2_0::Feed;
+use crate::v2_0::Link;
+use crate::v2_0::metadata::Relation;
+
+/// An error encountered while building a search URL.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The search link points to an OpenSearch description, which needs to be fetched before
+    /// search URLs can be built.
+    OpenSearchDescription,
+
+    /// The query uses a field that the catalog does not support.
+    Unsupported(SearchField),
+
+    /// The search link's URI template is malformed.
+    Template(template::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OpenSearchDescription => {
+                write!(f, "the OpenSearch description needs to be fetched first")
+            }
+            Self::Unsupported(field) => {
+                write!(f, "the catalog does not support searching by {field}")
+            }
+            Self::Template(e) => write!(f, "invalid search template: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Template(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<template::Error> for Error {
+    fn from(e: template::Error) -> Self {
+        Self::Template(e)
+    }
+}
+
+/// A search parameter that a catalog may support.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SearchField {
+    /// Free-text search terms.
+    Query,
+    Title,
+    Author,
+
+    /// The page of results to return, starting from 1.
+    Page,
+}
+
+impl SearchField {
+    const ALL: [SearchField; 4] = [Self::Query, Self::Title, Self::Author, Self::Page];
+
+    /// The name of the template variable for this field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Title => "title",
+            Self::Author => "author",
+            Self::Page => "page",
+        }
+    }
+}
+
+impl fmt::Display for SearchField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A structured search to run against a catalog.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct SearchQuery<'q> {
+    pub query: Option<Cow<'q, str>>,
+    pub title: Option<Cow<'q, str>>,
+    pub author: Option<Cow<'q, str>>,
+    pub page: Option<usize>,
+}
+
+impl<'q> SearchQuery<'q> {
+    pub fn new(query: impl Into<Cow<'q, str>>) -> Self {
+        Self::default().with_query(query)
+    }
+
+    pub fn with_query(mut self, query: impl Into<Cow<'q, str>>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<Cow<'q, str>>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<Cow<'q, str>>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_page(mut self, page: usize) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    /// The fields that have been set in this query, along with their values.
+    fn fields(&self) -> impl Iterator<Item = (SearchField, String)> + '_ {
+        let text = [
+            (SearchField::Query, &self.query),
+            (SearchField::Title, &self.title),
+            (SearchField::Author, &self.author),
+        ];
+
+        text.into_iter()
+            .filter_map(|(field, value)| Some((field, value.as_deref()?.to_string())))
+            .chain(self.page.map(|page| (SearchField::Page, page.to_string())))
+    }
+}
+
+impl<'q> From<&'q str> for SearchQuery<'q> {
+    fn from(query: &'q str) -> Self {
+        Self::new(query)
+    }
+}
+
+/// How a catalog can be searched, as advertised by a [Relation::Search] link.
+#[derive(Clone, Debug)]
+pub enum SearchCapability<'f, 'a> {
+    /// A templated link to a feed of results, along with the names of its template variables.
+    Template {
+        link: &'f Link<'a>,
+        variables: Vec<&'f str>,
+    },
+
+    /// A link to an OpenSearch description, as used by OPDS 1.2 catalogs.
+    OpenSearch { link: &'f Link<'a> },
+}
+
+impl<'f, 'a> SearchCapability<'f, 'a> {
+    /// Find the search capability advertised by a list of links.
+    ///
+    /// Templated links to OPDS 2.0 or OPDS 1.2 feeds are preferred over OpenSearch
+    /// descriptions. Templated links with a malformed URI template are ignored.
+    pub fn from_links(links: &'f [Link<'a>]) -> Option<Self> {
+        let search = || {
+            links
+                .iter()
+                .filter(|link| link.rel.contains(&Relation::Search) && link.href.is_some())
+        };
+
+        let template = search()
+            .filter(|link| {
+                link.templated
+                    && link.mime.as_deref().is_none_or(|mime| {
+                        mime == APPLICATION_OPDS_JSON || mime.starts_with(APPLICATION_ATOM_XML)
+                    })
+            })
+            .find_map(|link| {
+                let variables = link.template_variables().ok()?;
+                Some(Self::Template { link, variables })
+            });
+
+        template.or_else(|| {
+            search()
+                .find(|link| link.mime.as_deref() == Some(APPLICATION_OPENSEARCHDESCRIPTION_XML))
+                .map(|link| Self::OpenSearch { link })
+        })
+    }
+
+    /// The link that this capability was extracted from.
+    pub fn link(&self) -> &'f Link<'a> {
+        match self {
+            Self::Template { link, .. } => link,
+            Self::OpenSearch { link } => link,
+        }
+    }
+
+    /// The fields that can be used when searching.
+    ///
+    /// This is always empty for [SearchCapability::OpenSearch], since the supported fields
+    /// are only known after fetching the description.
+    pub fn fields(&self) -> Vec<SearchField> {
+        SearchField::ALL
+            .into_iter()
+            .filter(|field| self.supports(*field))
+            .collect()
+    }
+
+    /// Whether the catalog supports searching by the given field.
+    pub fn supports(&self, field: SearchField) -> bool {
+        match self {
+            Self::Template { variables, .. } => variables.contains(&field.as_str()),
+            Self::OpenSearch { .. } => false,
+        }
+    }
+
+    /// Build the URL of the results feed for a search.
+    ///
+    /// Fails if any of the fields set in the query are not [supported][Self::supports].
+    pub fn url(&self, query: &SearchQuery<'_>) -> Result<String, Error> {
+        let Self::Template { link, .. } = self else {
+            return Err(Error::OpenSearchDescription);
+        };
+
+        let mut variables = Variables::new();
+
+        for (field, value) in query.fields() {
+            if !self.supports(field) {
+                return Err(Error::Unsupported(field));
+            }
+
+            variables.insert(field.as_str(), value);
+        }
+
+        let href = link.href.as_deref().unwrap_or_default();
+
+        Ok(UriTemplate::parse(href)?.expand(&variables))
+    }
+}
+
+impl<'a> Feed<'a> {
+    /// Find how this feed's catalog can be searched.
+    ///
+    /// See [crate::search] for more information.
+    pub fn search(&self) -> Option<SearchCapability<'_, 'a>> {
+        SearchCapability::from_links(&self.links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    #[test]
+    fn test_feed_search() {
+        let json =
+            std::fs::read_to_string(format!("{CRATE_DIR}/tests/test-feed-archive-org.in.json"))
+                .expect("valid file input");
+        let feed: Feed = serde_json::from_str(&json).expect("can parse feed");
+
+        let search = feed.search().expect("feed has a search link");
+        assert_eq!(search.fields(), vec![SearchField::Query]);
+        assert_eq!(
+            search.url(&"moby dick".into()).unwrap(),
+            "https://opds.prod.archive.org/search?query=moby%20dick"
+        );
+        assert_eq!(
+            search
+                .url(&SearchQuery::new("whales").with_author("Melville"))
+                .unwrap_err(),
+            Error::Unsupported(SearchField::Author)
+        );
+    }
+
+    fn search_link(mut link: Link<'_>) -> Link<'_> {
+        link.rel.push(Relation::Search);
+        link
+    }
+
+    #[test]
+    fn test_search_capability() {
+        let links = vec![
+            search_link(Link::new(
+                "https://example.com/opensearch.xml".into(),
+                Some(APPLICATION_OPENSEARCHDESCRIPTION_XML.into()),
+            )),
+            search_link(Link::template(
+                "https://example.com/search{?query,title,author,page}".into(),
+                Some(APPLICATION_OPDS_JSON.into()),
+            )),
+        ];
+
+        let search = SearchCapability::from_links(&links).unwrap();
+        assert_eq!(
+            search.fields(),
+            vec![
+                SearchField::Query,
+                SearchField::Title,
+                SearchField::Author,
+                SearchField::Page
+            ]
+        );
+
+        let query = SearchQuery::default()
+            .with_title("Moby Dick")
+            .with_author("Herman Melville")
+            .with_page(2);
+        assert_eq!(
+            search.url(&query).unwrap(),
+            "https://example.com/search?title=Moby%20Dick&author=Herman%20Melville&page=2"
+        );
+
+        // Without a templated link, the OpenSearch description is used.
+        let search = SearchCapability::from_links(&links[..1]).unwrap();
+        assert!(matches!(search, SearchCapability::OpenSearch { .. }));
+        assert!(search.fields().is_empty());
+        assert_eq!(
+            search.url(&"whales".into()).unwrap_err(),
+            Error::OpenSearchDescription
+        );
+
+        assert!(SearchCapability::from_links(&[]).is_none());
+    }
+}
@@ -0,0 +1,683 @@
+//! Expansion of URI templates
+//!
+//! Templated links, such as those used for searching a catalog, have an `href` that is a
+//! [RFC 6570] URI template like `https://example.com/search{?query,page}`. This module
+//! implements all four levels of the specification, which can be used directly through
+//! [UriTemplate], or through [Link::expand] and [Link::template_variables] for OPDS links.
+//!
+//! [RFC 6570]: https://www.rfc-editor.org/rfc/rfc6570
+//! [Link::expand]: crate::v2_0::Link::expand
+//! [Link::template_variables]: crate::v2_0::Link::template_variables
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+/// An error encountered while parsing a URI template.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An expression was opened with `{` but never closed.
+    Unclosed { position: usize },
+
+    /// A `}` appeared outside of an expression.
+    Unopened { position: usize },
+
+    /// An expression used one of the operators reserved for future extensions.
+    ReservedOperator { operator: char },
+
+    /// A variable name contains characters that are not allowed.
+    InvalidName { name: String },
+
+    /// A prefix modifier was not a number between 1 and 9999.
+    InvalidPrefix { value: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unclosed { position } => write!(f, "unclosed expression at offset {position}"),
+            Self::Unopened { position } => write!(f, "unexpected '}}' at offset {position}"),
+            Self::ReservedOperator { operator } => write!(f, "reserved operator {operator:?}"),
+            Self::InvalidName { name } => write!(f, "invalid variable name {name:?}"),
+            Self::InvalidPrefix { value } => write!(f, "invalid prefix length {value:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The value of a template variable.
+///
+/// See [Section 2.4: Value Types] for more information.
+///
+/// [Section 2.4: Value Types]: https://www.rfc-editor.org/rfc/rfc6570#section-2.4
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    String(String),
+    List(Vec<String>),
+    Map(Vec<(String, String)>),
+}
+
+impl Value {
+    /// Whether this value is considered undefined, and so skipped during expansion.
+    fn is_undefined(&self) -> bool {
+        match self {
+            Self::String(_) => false,
+            Self::List(items) => items.is_empty(),
+            Self::Map(pairs) => pairs.is_empty(),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl<S: Into<String>> From<Vec<S>> for Value {
+    fn from(items: Vec<S>) -> Self {
+        Self::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+/// The values to substitute into a URI template, by variable name.
+///
+/// Variables without a value are left out of the expansion entirely.
+#[derive(Clone, Debug, Default)]
+pub struct Variables {
+    values: BTreeMap<String, Value>,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<Value>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Variables {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut variables = Self::new();
+
+        for (name, value) in iter {
+            variables.insert(name, value);
+        }
+
+        variables
+    }
+}
+
+/// How the values in an expression are joined and encoded.
+///
+/// See [Appendix A] for the meaning of each field.
+///
+/// [Appendix A]: https://www.rfc-editor.org/rfc/rfc6570#appendix-A
+#[derive(Clone, Copy, Debug)]
+struct Operator {
+    first: &'static str,
+    sep: &'static str,
+    named: bool,
+    ifemp: &'static str,
+    reserved: bool,
+}
+
+impl Operator {
+    fn parse(c: char) -> Result<Option<Self>, Error> {
+        let op = |first, sep, named, ifemp, reserved| Operator {
+            first,
+            sep,
+            named,
+            ifemp,
+            reserved,
+        };
+
+        let operator = match c {
+            '+' => op("", ",", false, "", true),
+            '#' => op("#", ",", false, "", true),
+            '.' => op(".", ".", false, "", false),
+            '/' => op("/", "/", false, "", false),
+            ';' => op(";", ";", true, "", false),
+            '?' => op("?", "&", true, "=", false),
+            '&' => op("&", "&", true, "=", false),
+            '=' | ',' | '!' | '@' | '|' => return Err(Error::ReservedOperator { operator: c }),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(operator))
+    }
+
+    const SIMPLE: Self = Self {
+        first: "",
+        sep: ",",
+        named: false,
+        ifemp: "",
+        reserved: false,
+    };
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Modifier {
+    None,
+    Prefix(usize),
+    Explode,
+}
+
+#[derive(Clone, Debug)]
+struct VarSpec<'t> {
+    name: &'t str,
+    modifier: Modifier,
+}
+
+#[derive(Clone, Debug)]
+enum Part<'t> {
+    Literal(&'t str),
+    Expression {
+        operator: Operator,
+        vars: Vec<VarSpec<'t>>,
+    },
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    let mut i = 0;
+
+    if name.is_empty() || name.starts_with('.') || name.ends_with('.') || name.contains("..") {
+        return false;
+    }
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                i += 3;
+            }
+            b if b.is_ascii_alphanumeric() || b == b'_' || b == b'.' => i += 1,
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+fn parse_varspec(spec: &str) -> Result<VarSpec<'_>, Error> {
+    let (name, modifier) = if let Some(name) = spec.strip_suffix('*') {
+        (name, Modifier::Explode)
+    } else if let Some((name, length)) = spec.split_once(':') {
+        let prefix = length
+            .parse()
+            .ok()
+            .filter(|n| (1..10000).contains(n) && !length.starts_with('0'))
+            .ok_or_else(|| Error::InvalidPrefix {
+                value: length.to_string(),
+            })?;
+        (name, Modifier::Prefix(prefix))
+    } else {
+        (spec, Modifier::None)
+    };
+
+    if !is_valid_name(name) {
+        return Err(Error::InvalidName {
+            name: name.to_string(),
+        });
+    }
+
+    Ok(VarSpec { name, modifier })
+}
+
+fn parse_expression(expr: &str) -> Result<Part<'_>, Error> {
+    let mut chars = expr.chars();
+
+    let (operator, vars) = match chars.next().map(Operator::parse).transpose()?.flatten() {
+        Some(operator) => (operator, chars.as_str()),
+        None => (Operator::SIMPLE, expr),
+    };
+
+    let vars = vars
+        .split(',')
+        .map(parse_varspec)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Part::Expression { operator, vars })
+}
+
+/// Whether a character may appear unencoded when only unreserved characters are allowed.
+fn is_unreserved(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')
+}
+
+/// Whether a character is in the reserved set, which `+` and `#` expressions leave unencoded.
+fn is_reserved(c: char) -> bool {
+    matches!(
+        c,
+        ':' | '/'
+            | '?'
+            | '#'
+            | '['
+            | ']'
+            | '@'
+            | '!'
+            | '$'
+            | '&'
+            | '\''
+            | '('
+            | ')'
+            | '*'
+            | '+'
+            | ','
+            | ';'
+            | '='
+    )
+}
+
+fn encode(out: &mut String, value: &str, reserved: bool) {
+    let bytes = value.as_bytes();
+
+    for (i, c) in value.char_indices() {
+        let is_pct_triplet = c == '%'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit);
+
+        if is_unreserved(c) || (reserved && (is_reserved(c) || is_pct_triplet)) {
+            out.push(c);
+        } else {
+            let mut buf = [0; 4];
+
+            for b in c.encode_utf8(&mut buf).bytes() {
+                let _ = write!(out, "%{b:02X}");
+            }
+        }
+    }
+}
+
+/// Truncate a value to at most `length` characters.
+fn prefix(value: &str, length: usize) -> &str {
+    match value.char_indices().nth(length) {
+        Some((i, _)) => &value[..i],
+        None => value,
+    }
+}
+
+fn expand_var(out: &mut String, op: &Operator, spec: &VarSpec<'_>, value: &Value) {
+    let named = |out: &mut String, name: &str, empty: bool| {
+        if op.named {
+            out.push_str(name);
+            out.push_str(if empty { op.ifemp } else { "=" });
+        }
+    };
+
+    match (value, spec.modifier) {
+        (Value::String(s), modifier) => {
+            let s = match modifier {
+                Modifier::Prefix(length) => prefix(s, length),
+                _ => s,
+            };
+
+            named(out, spec.name, s.is_empty());
+            encode(out, s, op.reserved);
+        }
+        (Value::List(items), Modifier::Explode) => {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(op.sep);
+                }
+
+                named(out, spec.name, item.is_empty());
+                encode(out, item, op.reserved);
+            }
+        }
+        (Value::Map(pairs), Modifier::Explode) => {
+            for (i, (k, v)) in pairs.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(op.sep);
+                }
+
+                encode(out, k, op.reserved);
+                out.push_str(if op.named && v.is_empty() {
+                    op.ifemp
+                } else {
+                    "="
+                });
+                encode(out, v, op.reserved);
+            }
+        }
+        (Value::List(items), _) => {
+            named(out, spec.name, false);
+
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+
+                encode(out, item, op.reserved);
+            }
+        }
+        (Value::Map(pairs), _) => {
+            named(out, spec.name, false);
+
+            for (i, (k, v)) in pairs.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+
+                encode(out, k, op.reserved);
+                out.push(',');
+                encode(out, v, op.reserved);
+            }
+        }
+    }
+}
+
+/// A parsed [RFC 6570] URI template.
+///
+/// [RFC 6570]: https://www.rfc-editor.org/rfc/rfc6570
+#[derive(Clone, Debug)]
+pub struct UriTemplate<'t> {
+    parts: Vec<Part<'t>>,
+}
+
+impl<'t> UriTemplate<'t> {
+    pub fn parse(template: &'t str) -> Result<Self, Error> {
+        let mut parts = vec![];
+        let mut rest = template;
+
+        while !rest.is_empty() {
+            let offset = template.len() - rest.len();
+
+            match rest.find(['{', '}']) {
+                Some(i) if rest.as_bytes()[i] == b'}' => {
+                    return Err(Error::Unopened {
+                        position: offset + i,
+                    });
+                }
+                Some(i) => {
+                    if i > 0 {
+                        parts.push(Part::Literal(&rest[..i]));
+                    }
+
+                    let expr = &rest[i + 1..];
+                    let end = expr.find('}').ok_or(Error::Unclosed {
+                        position: offset + i,
+                    })?;
+
+                    parts.push(parse_expression(&expr[..end])?);
+                    rest = &expr[end + 1..];
+                }
+                None => {
+                    parts.push(Part::Literal(rest));
+                    rest = "";
+                }
+            }
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// The names of the variables used by this template, in order of first appearance.
+    pub fn variables(&self) -> Vec<&'t str> {
+        let mut names = vec![];
+
+        for part in self.parts.iter() {
+            if let Part::Expression { vars, .. } = part {
+                for var in vars {
+                    if !names.contains(&var.name) {
+                        names.push(var.name);
+                    }
+                }
+            }
+        }
+
+        names
+    }
+
+    /// Substitute the given variables into this template.
+    pub fn expand(&self, variables: &Variables) -> String {
+        let mut out = String::new();
+
+        for part in self.parts.iter() {
+            let (op, vars) = match part {
+                Part::Literal(literal) => {
+                    encode(&mut out, literal, true);
+                    continue;
+                }
+                Part::Expression { operator, vars } => (operator, vars),
+            };
+
+            let mut first = true;
+
+            for spec in vars {
+                let Some(value) = variables.get(spec.name).filter(|v| !v.is_undefined()) else {
+                    continue;
+                };
+
+                out.push_str(if first { op.first } else { op.sep });
+                first = false;
+
+                expand_var(&mut out, op, spec, value);
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2_0::Link;
+
+    fn variables() -> Variables {
+        let keys = vec![
+            ("semi".to_string(), ";".to_string()),
+            ("dot".to_string(), ".".to_string()),
+            ("comma".to_string(), ",".to_string()),
+        ];
+
+        Variables::new()
+            .with("count", vec!["one", "two", "three"])
+            .with("dom", vec!["example", "com"])
+            .with("dub", "me/too")
+            .with("hello", "Hello World!")
+            .with("half", "50%")
+            .with("var", "value")
+            .with("who", "fred")
+            .with("base", "http://example.com/home/")
+            .with("path", "/foo/bar")
+            .with("list", vec!["red", "green", "blue"])
+            .with("keys", Value::Map(keys))
+            .with("v", "6")
+            .with("x", "1024")
+            .with("y", "768")
+            .with("empty", "")
+            .with("empty_keys", Value::Map(vec![]))
+    }
+
+    fn expand(template: &str) -> String {
+        UriTemplate::parse(template)
+            .expect("valid template")
+            .expand(&variables())
+    }
+
+    /// The examples from Section 3.2 of RFC 6570.
+    #[test]
+    fn test_rfc_examples() {
+        let cases = [
+            ("{var}", "value"),
+            ("{hello}", "Hello%20World%21"),
+            ("{half}", "50%25"),
+            ("O{empty}X", "OX"),
+            ("O{undef}X", "OX"),
+            ("{x,y}", "1024,768"),
+            ("{x,hello,y}", "1024,Hello%20World%21,768"),
+            ("?{x,empty}", "?1024,"),
+            ("?{x,undef}", "?1024"),
+            ("?{undef,y}", "?768"),
+            ("{var:3}", "val"),
+            ("{var:30}", "value"),
+            ("{list}", "red,green,blue"),
+            ("{list*}", "red,green,blue"),
+            ("{keys}", "semi,%3B,dot,.,comma,%2C"),
+            ("{keys*}", "semi=%3B,dot=.,comma=%2C"),
+            ("{+var}", "value"),
+            ("{+hello}", "Hello%20World!"),
+            ("{+half}", "50%25"),
+            ("{base}index", "http%3A%2F%2Fexample.com%2Fhome%2Findex"),
+            ("{+base}index", "http://example.com/home/index"),
+            ("O{+empty}X", "OX"),
+            ("{+path}/here", "/foo/bar/here"),
+            ("here?ref={+path}", "here?ref=/foo/bar"),
+            ("up{+path}{var}/here", "up/foo/barvalue/here"),
+            ("{+x,hello,y}", "1024,Hello%20World!,768"),
+            ("{+path,x}/here", "/foo/bar,1024/here"),
+            ("{+path:6}/here", "/foo/b/here"),
+            ("{+list}", "red,green,blue"),
+            ("{+keys*}", "semi=;,dot=.,comma=,"),
+            ("{#var}", "#value"),
+            ("{#hello}", "#Hello%20World!"),
+            ("{#half}", "#50%25"),
+            ("foo{#empty}", "foo#"),
+            ("foo{#undef}", "foo"),
+            ("{#x,hello,y}", "#1024,Hello%20World!,768"),
+            ("{#path:6}/here", "#/foo/b/here"),
+            ("{#keys}", "#semi,;,dot,.,comma,,"),
+            ("{.who}", ".fred"),
+            ("{.who,who}", ".fred.fred"),
+            ("{.half,who}", ".50%25.fred"),
+            ("www{.dom*}", "www.example.com"),
+            ("X{.var}", "X.value"),
+            ("X{.empty}", "X."),
+            ("X{.undef}", "X"),
+            ("X{.var:3}", "X.val"),
+            ("X{.list}", "X.red,green,blue"),
+            ("X{.list*}", "X.red.green.blue"),
+            ("X{.keys*}", "X.semi=%3B.dot=..comma=%2C"),
+            ("X{.empty_keys}", "X"),
+            ("{/who}", "/fred"),
+            ("{/who,who}", "/fred/fred"),
+            ("{/half,who}", "/50%25/fred"),
+            ("{/who,dub}", "/fred/me%2Ftoo"),
+            ("{/var}", "/value"),
+            ("{/var,empty}", "/value/"),
+            ("{/var,undef}", "/value"),
+            ("{/var,x}/here", "/value/1024/here"),
+            ("{/var:1,var}", "/v/value"),
+            ("{/list*}", "/red/green/blue"),
+            ("{/list*,path:4}", "/red/green/blue/%2Ffoo"),
+            ("{/keys*}", "/semi=%3B/dot=./comma=%2C"),
+            ("{;who}", ";who=fred"),
+            ("{;half}", ";half=50%25"),
+            ("{;empty}", ";empty"),
+            ("{;v,empty,who}", ";v=6;empty;who=fred"),
+            ("{;v,bar,who}", ";v=6;who=fred"),
+            ("{;x,y}", ";x=1024;y=768"),
+            ("{;x,y,empty}", ";x=1024;y=768;empty"),
+            ("{;x,y,undef}", ";x=1024;y=768"),
+            ("{;hello:5}", ";hello=Hello"),
+            ("{;list}", ";list=red,green,blue"),
+            ("{;list*}", ";list=red;list=green;list=blue"),
+            ("{;keys}", ";keys=semi,%3B,dot,.,comma,%2C"),
+            ("{;keys*}", ";semi=%3B;dot=.;comma=%2C"),
+            ("{?who}", "?who=fred"),
+            ("{?half}", "?half=50%25"),
+            ("{?x,y}", "?x=1024&y=768"),
+            ("{?x,y,empty}", "?x=1024&y=768&empty="),
+            ("{?x,y,undef}", "?x=1024&y=768"),
+            ("{?var:3}", "?var=val"),
+            ("{?list}", "?list=red,green,blue"),
+            ("{?list*}", "?list=red&list=green&list=blue"),
+            ("{?keys}", "?keys=semi,%3B,dot,.,comma,%2C"),
+            ("{?keys*}", "?semi=%3B&dot=.&comma=%2C"),
+            ("{&who}", "&who=fred"),
+            ("{&half}", "&half=50%25"),
+            ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+            ("{&x,y,empty}", "&x=1024&y=768&empty="),
+            ("{&var:3}", "&var=val"),
+            ("{&list}", "&list=red,green,blue"),
+            ("{&list*}", "&list=red&list=green&list=blue"),
+            ("{&keys}", "&keys=semi,%3B,dot,.,comma,%2C"),
+            ("{&keys*}", "&semi=%3B&dot=.&comma=%2C"),
+        ];
+
+        for (template, expected) in cases {
+            assert_eq!(expand(template), expected, "expanding {template:?}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert_eq!(
+            UriTemplate::parse("/search{?query").unwrap_err(),
+            Error::Unclosed { position: 7 }
+        );
+        assert_eq!(
+            UriTemplate::parse("/search}").unwrap_err(),
+            Error::Unopened { position: 7 }
+        );
+        assert_eq!(
+            UriTemplate::parse("{!query}").unwrap_err(),
+            Error::ReservedOperator { operator: '!' }
+        );
+        assert_eq!(
+            UriTemplate::parse("{query-string}").unwrap_err(),
+            Error::InvalidName {
+                name: "query-string".into()
+            }
+        );
+        assert_eq!(
+            UriTemplate::parse("{query:0}").unwrap_err(),
+            Error::InvalidPrefix { value: "0".into() }
+        );
+    }
+
+    #[test]
+    fn test_link_expand() {
+        let link = Link::template(
+            "https://example.com/search{?query,page}".into(),
+            Some(crate::mime::APPLICATION_OPDS_JSON.into()),
+        );
+
+        assert_eq!(link.template_variables().unwrap(), vec!["query", "page"]);
+
+        let vars = Variables::new().with("query", "moby dick");
+        let expanded = link.expand(&vars).expect("valid template");
+        assert!(!expanded.templated);
+        assert_eq!(
+            expanded.href.as_deref(),
+            Some("https://example.com/search?query=moby%20dick")
+        );
+        assert_eq!(expanded.mime, link.mime);
+
+        // Links that are not templated are returned unchanged.
+        let link = Link::new("https://example.com/{literal}".into(), None);
+        assert!(link.template_variables().unwrap().is_empty());
+        assert_eq!(
+            link.expand(&vars).unwrap().href.as_deref(),
+            Some("https://example.com/{literal}")
+        );
+    }
+}
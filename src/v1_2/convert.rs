@@ -0,0 +1,1525 @@
+//! Conversion between OPDS 1.2 and OPDS 2.0 feeds
+//!
+//! The two versions of OPDS do not carry exactly the same information, so each conversion
+//! produces a [Report] describing anything that could not be carried over, alongside the
+//! converted feed. The [From] implementations perform the same conversion, but discard the
+//! report.
+use std::borrow::Cow;
+use std::fmt;
+
+use langtag::LangTag;
+
+use super::*;
+use crate::v2_0::extensions::Extensions;
+use crate::v2_0::metadata::{
+    AltIdentifier, BelongsTo, Contributor, FeedMetadata, LinkProperties, PublicationMetadata,
+    StringWithAlternates, Subject,
+};
+use crate::v2_0::timestamp::Timestamp;
+use crate::v2_0::validate::escape;
+use crate::v2_0::{self, Facet, FeedGroup, Publication};
+
+/// The relation used by OPDS 1.2 for links to facets.
+const FACET_REL: &str = "http://opds-spec.org/facet";
+
+/// The relation used by OPDS 1.2 for links to a publication's cover image.
+const IMAGE_REL: &str = "http://opds-spec.org/image";
+
+/// The relation used by OPDS 1.2 for links to a publication's thumbnail image.
+const THUMBNAIL_REL: &str = "http://opds-spec.org/image/thumbnail";
+
+/// The relation used by OPDS 1.2 to link an entry to the group it is listed under.
+const COLLECTION_REL: &str = "collection";
+
+/// Why a piece of information was not carried over during a conversion.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum LossKind {
+    /// The field has no equivalent in the target version, and was dropped.
+    Unsupported,
+
+    /// The target version only allows a single value, and the remaining values were dropped.
+    ExtraValues { dropped: usize },
+
+    /// The value could not be converted into the type required by the target version.
+    InvalidValue { value: String },
+
+    /// The value was carried over into a less specific field, such as a translator becoming
+    /// a contributor.
+    Generalized,
+
+    /// The target version requires a value that the source doesn't have, so one was
+    /// generated in its place.
+    Generated { value: String },
+}
+
+/// A piece of information that was not carried over during a conversion.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Loss {
+    /// The location of the field within the source feed, such as `/entries/3/links/1/price`.
+    pub path: String,
+
+    /// Why the field was not carried over.
+    pub kind: LossKind,
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            LossKind::Unsupported => write!(f, "{}: no equivalent field", self.path),
+            LossKind::ExtraValues { dropped } => {
+                write!(f, "{}: dropped {dropped} additional values", self.path)
+            }
+            LossKind::InvalidValue { value } => {
+                write!(f, "{}: could not convert {value:?}", self.path)
+            }
+            LossKind::Generalized => {
+                write!(f, "{}: carried over into a less specific field", self.path)
+            }
+            LossKind::Generated { value } => {
+                write!(f, "{}: missing, so {value:?} was generated", self.path)
+            }
+        }
+    }
+}
+
+/// Everything that was not carried over during a conversion.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Report {
+    pub losses: Vec<Loss>,
+}
+
+impl Report {
+    /// Whether the conversion carried over everything.
+    pub fn is_empty(&self) -> bool {
+        self.losses.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, path: String, kind: LossKind) {
+        self.losses.push(Loss { path, kind });
+    }
+
+    pub(crate) fn unsupported(&mut self, path: String) {
+        self.push(path, LossKind::Unsupported);
+    }
+
+    pub(crate) fn invalid(&mut self, path: String, value: impl Into<String>) {
+        self.push(
+            path,
+            LossKind::InvalidValue {
+                value: value.into(),
+            },
+        );
+    }
+}
+
+/// Generate a `urn:uuid` identifier from the JSON form of `value`, so that converting the
+/// same feed again gives the same identifier.
+///
+/// The UUID uses version 8, with its bits taken from two 64-bit FNV-1a hashes of the JSON.
+fn generated_id(value: &impl serde::Serialize) -> String {
+    let json = serde_json::to_vec(value).unwrap_or_default();
+    let fnv = |basis: u64| {
+        json.iter().fold(basis, |hash, &b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    };
+
+    let hash =
+        (u128::from(fnv(0xcbf2_9ce4_8422_2325)) << 64) | u128::from(fnv(0x6c62_272e_07bb_0142));
+    let uuid = (hash & !(0xf << 76) & !(0x3 << 62)) | (0x8 << 76) | (0x2 << 62);
+    let hex = format!("{uuid:032x}");
+
+    format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn identifier(report: &mut Report, path: String, id: &str) -> Option<url::Url> {
+    match url::Url::parse(id) {
+        Ok(url) => Some(url),
+        Err(_) => {
+            report.invalid(path, id);
+            None
+        }
+    }
+}
+
+/// Parse a timestamp, keeping any that aren't in a supported form as they were written.
+fn timestamp(value: Option<Cow<'_, str>>) -> Option<Timestamp> {
+    value.map(|value| Timestamp::from(value.into_owned()))
+}
+
+fn contributor<'a>(report: &mut Report, path: String, author: Author<'a>) -> Contributor<'a> {
+    let mut contributor = Contributor::new(author.name);
+
+    if let Some(uri) = author.uri {
+        contributor.links.push(v2_0::Link::new(uri, None));
+    }
+
+    if author.email.is_some() {
+        report.unsupported(format!("{path}/email"));
+    }
+
+    contributor
+}
+
+fn subject<'a>(report: &mut Report, path: String, category: Category<'a>) -> Subject<'a> {
+    let name = category.label.unwrap_or_else(|| category.term.clone());
+    let mut subject = Subject::new(name);
+    subject.code = Some(category.term);
+    subject.scheme = category
+        .scheme
+        .and_then(|scheme| identifier(report, format!("{path}/scheme"), &scheme));
+    subject
+}
+
+/// Convert a link, ignoring the facet-related fields.
+fn link<'a>(report: &mut Report, path: &str, link: Link<'a>) -> v2_0::Link<'a> {
+    let mut out = v2_0::Link::new(link.href, link.mime);
+    out.title = link.title;
+    out.rel = link.rel.into_iter().collect();
+    out.size = link.length;
+    out.language = link.hreflang.into_iter().collect();
+
+    let mut properties = LinkProperties::default();
+    let mut prices = link.price.into_iter();
+    properties.price = prices.next();
+    properties.indirect_acquisition = link.indirect_acquisition;
+    properties.count = link.count;
+    out.properties = properties;
+
+    let dropped = prices.count();
+    if dropped > 0 {
+        report.push(format!("{path}/price"), LossKind::ExtraValues { dropped });
+    }
+
+    out
+}
+
+fn is_rel(link: &Link<'_>, rel: &str) -> bool {
+    link.rel.as_ref().is_some_and(|r| r.as_str() == rel)
+}
+
+/// Group the facet links within a feed into [Facet] objects by their `opds:facetGroup`, which
+/// every link passed in has.
+fn facets<'a>(report: &mut Report, links: Vec<(usize, Link<'a>)>) -> Vec<Facet<'a>> {
+    let mut groups: Vec<(Cow<'a, str>, Facet<'a>)> = vec![];
+
+    for (i, mut l) in links {
+        let path = format!("/links/{i}");
+        let group = l.facet_group.take().unwrap_or_default();
+
+        // OPDS 2.0 marks the active facet within a group with the "self" relation.
+        l.rel = l.active_facet.then_some(Relation::Myself);
+
+        let out = link(report, &path, l);
+
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, facet)) => facet.links.push(out),
+            None => {
+                let mut facet = Facet::new(group.clone());
+                facet.links.push(out);
+                groups.push((group, facet));
+            }
+        }
+    }
+
+    groups.into_iter().map(|(_, facet)| facet).collect()
+}
+
+fn navigation<'a>(report: &mut Report, path: String, entry: Entry<'a>) -> Option<v2_0::Link<'a>> {
+    let Entry {
+        id: _,
+        title,
+        updated,
+        published,
+        author,
+        contributor,
+        rights,
+        summary,
+        content,
+        category,
+        links,
+        identifier,
+        language,
+        publisher,
+        issued,
+        series,
+    } = entry;
+
+    let dropped = [
+        ("updated", updated.is_some()),
+        ("published", published.is_some()),
+        ("author", !author.is_empty()),
+        ("contributor", !contributor.is_empty()),
+        ("rights", rights.is_some()),
+        ("summary", summary.is_some()),
+        ("content", content.is_some()),
+        ("category", !category.is_empty()),
+        ("identifier", !identifier.is_empty()),
+        ("language", !language.is_empty()),
+        ("publisher", !publisher.is_empty()),
+        ("issued", issued.is_some()),
+        ("series", series.is_some()),
+    ];
+
+    for (field, present) in dropped {
+        if present {
+            report.unsupported(format!("{path}/{field}"));
+        }
+    }
+
+    let Some(i) = links.iter().position(Link::is_catalog) else {
+        // There is nothing to navigate to, so the whole entry is dropped.
+        report.unsupported(path);
+        return None;
+    };
+
+    let dropped = links.len() - 1;
+    let catalog = links.into_iter().nth(i).expect("position is in bounds");
+
+    let mut out = link(report, &format!("{path}/links/{i}"), catalog);
+    out.title = Some(title.value);
+
+    if dropped > 0 {
+        report.push(format!("{path}/links"), LossKind::ExtraValues { dropped });
+    }
+
+    Some(out)
+}
+
+fn publication<'a>(report: &mut Report, path: String, entry: Entry<'a>) -> Publication<'a> {
+    let Entry {
+        id,
+        title,
+        updated,
+        published,
+        author,
+        contributor: contributors,
+        rights,
+        summary,
+        content,
+        category,
+        links,
+        identifier: identifiers,
+        language,
+        publisher,
+        issued,
+        series,
+    } = entry;
+
+    let mut metadata = PublicationMetadata::new(title.value);
+    metadata.identifier = identifier(report, format!("{path}/id"), &id);
+    metadata.modified = timestamp(updated);
+    metadata.language = language;
+
+    metadata.published = match (issued, published) {
+        (Some(issued), published) => {
+            if published.is_some() {
+                report.unsupported(format!("{path}/published"));
+            }
+
+            timestamp(Some(issued))
+        }
+        (None, published) => timestamp(published),
+    };
+
+    metadata.description = match (summary, content) {
+        (Some(summary), Some(_)) => {
+            report.unsupported(format!("{path}/content"));
+            Some(summary.value)
+        }
+        (summary, content) => summary.or(content).map(|t| t.value),
+    };
+
+    if rights.is_some() {
+        report.unsupported(format!("{path}/rights"));
+    }
+
+    for (i, a) in author.into_iter().enumerate() {
+        let c = contributor(report, format!("{path}/author/{i}"), a);
+        metadata.author.push(c);
+    }
+
+    for (i, a) in contributors.into_iter().enumerate() {
+        let c = contributor(report, format!("{path}/contributor/{i}"), a);
+        metadata.contributor.push(c);
+    }
+
+    for (i, c) in category.into_iter().enumerate() {
+        let s = subject(report, format!("{path}/category/{i}"), c);
+        metadata.subject.push(s);
+    }
+
+    metadata.alt_identifier = identifiers.into_iter().map(AltIdentifier::new).collect();
+    metadata.publisher = publisher.into_iter().map(Contributor::new).collect();
+
+    if let Some(series) = series {
+        let mut out = v2_0::metadata::Series::new(series.name);
+
+        // OPDS 2.0 only allows whole-numbered positions within a series.
+        out.position = series.position.and_then(|position| {
+            if position >= 0.0 && position.fract() == 0.0 {
+                Some(position as usize)
+            } else {
+                let path = format!("{path}/series/position");
+                report.invalid(path, position.to_string());
+                None
+            }
+        });
+
+        let mut belongs_to = BelongsTo::default();
+        belongs_to.series.push(out);
+        metadata.belongs_to = Some(belongs_to);
+    }
+
+    let mut publication = Publication {
+        metadata,
+        links: vec![],
+        images: vec![],
+        extensions: Extensions::new(),
+    };
+
+    for (i, l) in links.into_iter().enumerate() {
+        let link_path = format!("{path}/links/{i}");
+
+        if is_rel(&l, IMAGE_REL) {
+            let mut image = link(report, &link_path, l);
+            image.rel = vec![Relation::Cover];
+            publication.images.push(image);
+        } else if is_rel(&l, THUMBNAIL_REL) {
+            let mut image = link(report, &link_path, l);
+            image.rel = vec![];
+            publication.images.push(image);
+        } else {
+            publication.links.push(link(report, &link_path, l));
+        }
+    }
+
+    publication
+}
+
+impl<'a> Entry<'a> {
+    /// Convert this entry into an OPDS 2.0 [Publication], along with a [Report] of anything
+    /// that could not be carried over.
+    pub fn into_publication(self) -> (Publication<'a>, Report) {
+        let mut report = Report::default();
+        let publication = publication(&mut report, String::new(), self);
+        (publication, report)
+    }
+}
+
+impl<'a> From<Entry<'a>> for Publication<'a> {
+    fn from(entry: Entry<'a>) -> Self {
+        entry.into_publication().0
+    }
+}
+
+impl<'a> Feed<'a> {
+    /// Convert this feed into an OPDS 2.0 [v2_0::Feed], along with a [Report] of anything
+    /// that could not be carried over.
+    ///
+    /// Navigation entries become [v2_0::Feed::navigation] links, publication entries become
+    /// [v2_0::Feed::publications], and links with an `opds:facetGroup` are grouped into
+    /// [v2_0::Feed::facets].
+    pub fn into_v2_0(self) -> (v2_0::Feed<'a>, Report) {
+        let mut report = Report::default();
+
+        let Feed {
+            id,
+            title,
+            subtitle,
+            updated,
+            icon,
+            author,
+            links,
+            total_results,
+            items_per_page,
+            start_index,
+            entries,
+        } = self;
+
+        let mut feed = v2_0::Feed::new(title.value);
+        let metadata = &mut feed.metadata;
+        metadata.identifier = identifier(&mut report, "/id".into(), &id);
+        metadata.subtitle = subtitle.into_iter().map(|t| t.value.into()).collect();
+        metadata.modified = timestamp(updated);
+        metadata.number_of_items = total_results;
+        metadata.items_per_page = items_per_page;
+
+        match (start_index, items_per_page) {
+            (Some(start), Some(per_page)) if per_page > 0 => {
+                metadata.current_page = Some(start.saturating_sub(1) / per_page + 1);
+            }
+            (Some(_), _) => report.unsupported("/start_index".into()),
+            (None, _) => {}
+        }
+
+        if icon.is_some() {
+            report.unsupported("/icon".into());
+        }
+
+        for i in 0..author.len() {
+            report.unsupported(format!("/author/{i}"));
+        }
+
+        let (facet_links, links): (Vec<_>, Vec<_>) = links
+            .into_iter()
+            .enumerate()
+            .partition(|(_, l)| l.facet_group.is_some());
+
+        for (i, l) in links {
+            let path = format!("/links/{i}");
+
+            // Facets in OPDS 2.0 are titled by their group, so facet links without a group are
+            // kept as plain links instead.
+            if is_rel(&l, FACET_REL) {
+                report.push(path.clone(), LossKind::Generalized);
+
+                if l.active_facet {
+                    report.unsupported(format!("{path}/active_facet"));
+                }
+            }
+
+            feed.links.push(link(&mut report, &path, l));
+        }
+
+        feed.facets = facets(&mut report, facet_links);
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            let path = format!("/entries/{i}");
+
+            if entry.is_navigation() {
+                let nav = navigation(&mut report, path, entry);
+                feed.navigation.extend(nav);
+            } else {
+                let publication = publication(&mut report, path, entry);
+                feed.publications.push(publication);
+            }
+        }
+
+        (feed, report)
+    }
+}
+
+impl<'a> From<Feed<'a>> for v2_0::Feed<'a> {
+    fn from(feed: Feed<'a>) -> Self {
+        feed.into_v2_0().0
+    }
+}
+
+/// Find the choice that best matches the requested language, either exactly or by its primary
+/// language subtag.
+fn preferred(choices: &[(Cow<'static, LangTag>, Cow<'static, str>)], language: &LangTag) -> usize {
+    let wanted = language.as_str();
+    let primary = |tag: &str| {
+        tag.split('-')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+
+    choices
+        .iter()
+        .position(|(tag, _)| tag.as_str().eq_ignore_ascii_case(wanted))
+        .or_else(|| {
+            let wanted = primary(wanted);
+            choices
+                .iter()
+                .position(|(tag, _)| primary(tag.as_str()) == wanted)
+        })
+        .unwrap_or(0)
+}
+
+/// The state used while converting an OPDS 2.0 feed into OPDS 1.2.
+struct Downgrade<'l> {
+    report: Report,
+    language: Option<&'l LangTag>,
+}
+
+impl Downgrade<'_> {
+    fn unsupported(&mut self, path: &str, fields: &[(&str, bool)]) {
+        for (field, present) in fields {
+            if *present {
+                self.report.unsupported(format!("{path}/{field}"));
+            }
+        }
+    }
+
+    /// Report the fields that are not modeled by this crate, which Atom has no place for.
+    fn extensions(&mut self, path: &str, extensions: Extensions) {
+        for key in extensions.keys() {
+            self.report.unsupported(format!("{path}/{}", escape(key)));
+        }
+    }
+
+    /// The URL of the first self link in `links`, or else an identifier generated from
+    /// `value`, which is reported as missing from `path`.
+    fn self_link<'a>(
+        &mut self,
+        path: &str,
+        links: &[v2_0::Link<'a>],
+        value: &impl serde::Serialize,
+    ) -> Cow<'a, str> {
+        let href = links
+            .iter()
+            .find(|link| link.rel.contains(&Relation::Myself))
+            .and_then(|link| link.href.clone());
+
+        href.unwrap_or_else(|| {
+            let generated = generated_id(value);
+            self.report.push(
+                path.to_string(),
+                LossKind::Generated {
+                    value: generated.clone(),
+                },
+            );
+            Cow::Owned(generated)
+        })
+    }
+
+    /// Keep the first of several values, reporting the rest.
+    fn first<T>(&mut self, path: String, values: Vec<T>) -> Option<T> {
+        let dropped = values.len().saturating_sub(1);
+
+        if dropped > 0 {
+            self.report.push(path, LossKind::ExtraValues { dropped });
+        }
+
+        values.into_iter().next()
+    }
+
+    /// Pick a single string, preferring the requested language when there are alternatives.
+    fn string<'a>(&mut self, path: String, value: StringWithAlternates<'a>) -> Cow<'a, str> {
+        let variants = match value {
+            StringWithAlternates::Always(value) => return value,
+            StringWithAlternates::Variants(variants) => variants,
+        };
+
+        let choices = variants.choices();
+        let index = self
+            .language
+            .map(|language| preferred(choices, language))
+            .unwrap_or(0);
+
+        if choices.len() > 1 {
+            let dropped = choices.len() - 1;
+            self.report.push(path, LossKind::ExtraValues { dropped });
+        }
+
+        choices
+            .get(index)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default()
+    }
+
+    fn link<'a>(&mut self, path: &str, link: v2_0::Link<'a>) -> Option<Link<'a>> {
+        let v2_0::Link {
+            title,
+            href,
+            templated,
+            mime,
+            rel,
+            properties,
+            height,
+            width,
+            size,
+            bitrate,
+            duration,
+            language,
+            alternate,
+            children,
+            extensions,
+        } = link;
+
+        let Some(href) = href else {
+            // Atom links must have an href, so the whole link is dropped.
+            self.report.unsupported(path.to_string());
+            return None;
+        };
+
+        let LinkProperties {
+            count,
+            page,
+            availability,
+            price,
+            indirect_acquisition,
+            holds,
+            copies,
+            extensions: property_extensions,
+        } = properties;
+
+        self.unsupported(
+            path,
+            &[
+                ("templated", templated),
+                ("properties/page", page.is_some()),
+                ("properties/availability", availability.is_some()),
+                ("properties/holds", holds.is_some()),
+                ("properties/copies", copies.is_some()),
+                ("height", height.is_some()),
+                ("width", width.is_some()),
+                ("bitrate", bitrate.is_some()),
+                ("duration", duration.is_some()),
+                ("alternate", !alternate.is_empty()),
+                ("children", !children.is_empty()),
+            ],
+        );
+        self.extensions(path, extensions);
+        self.extensions(&format!("{path}/properties"), property_extensions);
+
+        let rel = self.first(format!("{path}/rel"), rel);
+        let mut out = Link::new(href, rel, mime);
+        out.title = title;
+        out.hreflang = self.first(format!("{path}/language"), language);
+        out.length = size;
+        out.count = count;
+        out.price = price.into_iter().collect();
+        out.indirect_acquisition = indirect_acquisition;
+        Some(out)
+    }
+
+    fn person<'a>(&mut self, path: &str, contributor: Contributor<'a>) -> Author<'a> {
+        let Contributor {
+            name,
+            sort_as,
+            identifier,
+            alt_identifier,
+            role,
+            links,
+        } = contributor;
+
+        self.unsupported(
+            path,
+            &[
+                ("sortAs", sort_as.is_some()),
+                ("identifier", identifier.is_some()),
+                ("altIdentifier", !alt_identifier.is_empty()),
+                ("role", !role.is_empty()),
+            ],
+        );
+
+        let mut author = Author::new(self.string(format!("{path}/name"), name));
+        author.uri = self
+            .first(format!("{path}/links"), links)
+            .and_then(|link| link.href);
+        author
+    }
+
+    fn category<'a>(&mut self, path: &str, subject: Subject<'a>) -> Category<'a> {
+        let Subject {
+            name,
+            sort_as,
+            code,
+            scheme,
+            links,
+        } = subject;
+
+        self.unsupported(
+            path,
+            &[("sortAs", sort_as.is_some()), ("links", !links.is_empty())],
+        );
+
+        let name = self.string(format!("{path}/name"), name);
+        let mut category = match code {
+            Some(code) => {
+                let mut category = Category::new(code);
+                category.label = Some(name);
+                category
+            }
+            None => Category::new(name),
+        };
+
+        category.scheme = scheme.map(|scheme| Cow::Owned(scheme.into()));
+        category
+    }
+
+    fn series<'a>(&mut self, path: &str, belongs_to: BelongsTo<'a>) -> Option<Series<'a>> {
+        let BelongsTo {
+            collection,
+            journal,
+            magazine,
+            newspaper,
+            periodical,
+            season,
+            series,
+            story_arc,
+            volume,
+        } = belongs_to;
+
+        self.unsupported(
+            path,
+            &[
+                ("collection", !collection.is_empty()),
+                ("journal", !journal.is_empty()),
+                ("magazine", !magazine.is_empty()),
+                ("newspaper", !newspaper.is_empty()),
+                ("periodical", !periodical.is_empty()),
+                ("season", !season.is_empty()),
+                ("storyArc", !story_arc.is_empty()),
+                ("volume", !volume.is_empty()),
+            ],
+        );
+
+        let v2_0::metadata::Series {
+            name,
+            sort_as,
+            identifier,
+            alt_identifier,
+            position,
+            links,
+            chapter,
+            episode,
+            issue,
+            season,
+            story_arc,
+            volume,
+        } = self.first(format!("{path}/series"), series)?;
+
+        let path = format!("{path}/series/0");
+        self.unsupported(
+            &path,
+            &[
+                ("sortAs", sort_as.is_some()),
+                ("identifier", identifier.is_some()),
+                ("altIdentifier", !alt_identifier.is_empty()),
+                ("links", !links.is_empty()),
+                ("chapter", !chapter.is_empty()),
+                ("episode", !episode.is_empty()),
+                ("issue", !issue.is_empty()),
+                ("season", !season.is_empty()),
+                ("storyArc", !story_arc.is_empty()),
+                ("volume", !volume.is_empty()),
+            ],
+        );
+
+        let mut out = Series::new(self.string(format!("{path}/name"), name));
+        out.position = position.map(|position| position as f64);
+        Some(out)
+    }
+
+    fn entry<'a>(&mut self, path: &str, publication: Publication<'a>) -> Entry<'a> {
+        // Atom requires an identifier, so fall back to the publication's own URL, or else to
+        // one generated from its contents.
+        let id = match &publication.metadata.identifier {
+            Some(identifier) => Cow::Owned(identifier.to_string()),
+            None => self.self_link(
+                &format!("{path}/metadata/identifier"),
+                &publication.links,
+                &publication,
+            ),
+        };
+
+        let Publication {
+            metadata,
+            links,
+            images,
+            extensions,
+        } = publication;
+
+        let PublicationMetadata {
+            schema,
+            conforms_to,
+            title,
+            sort_as,
+            subtitle,
+            author,
+            description,
+            identifier: _,
+            alt_identifier,
+            accessibility,
+            modified,
+            published,
+            language,
+            subject,
+            layout,
+            reading_progression,
+            duration,
+            abridged,
+            number_of_pages,
+            belongs_to,
+            contains,
+            tdm,
+            translator,
+            editor,
+            artist,
+            illustrator,
+            letterer,
+            penciler,
+            colorist,
+            inker,
+            narrator,
+            contributor,
+            publisher,
+            imprint,
+            extensions: metadata_extensions,
+        } = metadata;
+
+        let meta = format!("{path}/metadata");
+        self.unsupported(
+            &meta,
+            &[
+                ("@type", schema.is_some()),
+                ("conformsTo", !conforms_to.is_empty()),
+                ("sortAs", sort_as.is_some()),
+                ("subtitle", subtitle.is_some()),
+                ("accessibility", accessibility.is_some()),
+                ("layout", layout.is_some()),
+                ("readingProgression", reading_progression.is_some()),
+                ("duration", duration.is_some()),
+                ("abridged", abridged.is_some()),
+                ("numberOfPages", number_of_pages.is_some()),
+                ("contains", contains.is_some()),
+                ("tdm", tdm.is_some()),
+            ],
+        );
+        self.extensions(&meta, metadata_extensions);
+        self.extensions(path, extensions);
+
+        let title = self.string(format!("{meta}/title"), title);
+        let mut entry = Entry::new(id, title);
+        entry.updated = modified.map(|t| Cow::Owned(t.to_string()));
+        entry.issued = published.map(|t| Cow::Owned(t.to_string()));
+        entry.language = language;
+        entry.summary = description.map(Text::from);
+
+        for (i, a) in author.into_iter().enumerate() {
+            let a = self.person(&format!("{meta}/author/{i}"), a);
+            entry.author.push(a);
+        }
+
+        for (i, c) in contributor.into_iter().enumerate() {
+            let c = self.person(&format!("{meta}/contributor/{i}"), c);
+            entry.contributor.push(c);
+        }
+
+        // Atom has no notion of roles, so everyone else becomes a generic contributor.
+        let roles = [
+            ("translator", translator),
+            ("editor", editor),
+            ("artist", artist),
+            ("illustrator", illustrator),
+            ("letterer", letterer),
+            ("penciler", penciler),
+            ("colorist", colorist),
+            ("inker", inker),
+            ("narrator", narrator),
+        ];
+
+        for (role, contributors) in roles {
+            for (i, c) in contributors.into_iter().enumerate() {
+                let path = format!("{meta}/{role}/{i}");
+                let c = self.person(&path, c);
+                self.report.push(path, LossKind::Generalized);
+                entry.contributor.push(c);
+            }
+        }
+
+        for (i, p) in publisher.into_iter().enumerate() {
+            let path = format!("{meta}/publisher/{i}");
+            let p = self.person(&path, p);
+            self.unsupported(&path, &[("links", p.uri.is_some())]);
+            entry.publisher.push(p.name);
+        }
+
+        for i in 0..imprint.len() {
+            self.report.unsupported(format!("{meta}/imprint/{i}"));
+        }
+
+        for (i, a) in alt_identifier.into_iter().enumerate() {
+            let path = format!("{meta}/altIdentifier/{i}");
+            self.unsupported(&path, &[("scheme", a.scheme.is_some())]);
+            entry.identifier.push(a.value);
+        }
+
+        for (i, s) in subject.into_iter().enumerate() {
+            let c = self.category(&format!("{meta}/subject/{i}"), s);
+            entry.category.push(c);
+        }
+
+        if let Some(belongs_to) = belongs_to {
+            entry.series = self.series(&format!("{meta}/belongsTo"), belongs_to);
+        }
+
+        for (i, l) in links.into_iter().enumerate() {
+            let l = self.link(&format!("{path}/links/{i}"), l);
+            entry.links.extend(l);
+        }
+
+        for (i, mut image) in images.into_iter().enumerate() {
+            let rel = if image.rel.contains(&Relation::Cover) {
+                IMAGE_REL
+            } else {
+                THUMBNAIL_REL
+            };
+
+            image.rel.clear();
+
+            if let Some(mut image) = self.link(&format!("{path}/images/{i}"), image) {
+                image.rel = Some(Relation::from(rel.to_string()));
+                entry.links.push(image);
+            }
+        }
+
+        entry
+    }
+
+    /// Convert a navigation link into a navigation entry.
+    fn navigation<'a>(&mut self, path: &str, link: v2_0::Link<'a>) -> Option<Entry<'a>> {
+        let mut link = self.link(path, link)?;
+        let title = link.title.take().unwrap_or_else(|| link.href.clone());
+        Some(Entry::new(link.href.clone(), title).with_link(link))
+    }
+
+    /// Pick the title for a facet or group, reporting the rest of its metadata.
+    fn title<'a>(&mut self, path: &str, metadata: FeedMetadata<'a>) -> Cow<'a, str> {
+        let FeedMetadata {
+            title,
+            subtitle,
+            identifier,
+            schema,
+            modified,
+            description,
+            items_per_page,
+            current_page,
+            number_of_items,
+            extensions,
+        } = metadata;
+
+        self.unsupported(
+            path,
+            &[
+                ("subtitle", !subtitle.is_empty()),
+                ("identifier", identifier.is_some()),
+                ("@type", schema.is_some()),
+                ("modified", modified.is_some()),
+                ("description", description.is_some()),
+                ("itemsPerPage", items_per_page.is_some()),
+                ("currentPage", current_page.is_some()),
+                ("numberOfItems", number_of_items.is_some()),
+            ],
+        );
+        self.extensions(path, extensions);
+
+        self.string(format!("{path}/title"), title)
+    }
+
+    fn facets<'a>(&mut self, facets: Vec<Facet<'a>>) -> Vec<Link<'a>> {
+        let mut out = vec![];
+
+        for (i, facet) in facets.into_iter().enumerate() {
+            let path = format!("/facets/{i}");
+            let group = self.title(&format!("{path}/metadata"), facet.metadata);
+
+            for (j, mut l) in facet.links.into_iter().enumerate() {
+                let path = format!("{path}/links/{j}");
+
+                // OPDS 2.0 marks the active facet within a group with the "self" relation.
+                let active = l.rel.contains(&Relation::Myself);
+                l.rel.retain(|rel| *rel != Relation::Myself);
+
+                if !l.rel.is_empty() {
+                    self.report.unsupported(format!("{path}/rel"));
+                    l.rel.clear();
+                }
+
+                if let Some(mut l) = self.link(&path, l) {
+                    l.rel = Some(Relation::from(FACET_REL.to_string()));
+                    l.facet_group = Some(group.clone());
+                    l.active_facet = active;
+                    out.push(l);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Convert a group into a navigation entry for the full group, followed by its contents,
+    /// which each link back to the group with the "collection" relation.
+    fn group<'a>(&mut self, path: &str, group: FeedGroup<'a>) -> Vec<Entry<'a>> {
+        let FeedGroup {
+            metadata,
+            links,
+            navigation,
+            publications,
+        } = group;
+
+        let title = self.title(&format!("{path}/metadata"), metadata);
+
+        let mut collection = None;
+        let mut dropped = 0;
+
+        for (i, l) in links.into_iter().enumerate() {
+            let Some(l) = self.link(&format!("{path}/links/{i}"), l) else {
+                continue;
+            };
+
+            let is_self = l.rel == Some(Relation::Myself);
+
+            match collection {
+                None => collection = Some(l),
+                Some(_) if is_self => {
+                    collection = Some(l);
+                    dropped += 1;
+                }
+                Some(_) => dropped += 1,
+            }
+        }
+
+        if dropped > 0 {
+            let path = format!("{path}/links");
+            self.report.push(path, LossKind::ExtraValues { dropped });
+        }
+
+        let mut entries = vec![];
+
+        let collection = match collection {
+            Some(l) => {
+                let mut subsection = Link::new(l.href.clone(), Some(Relation::Subsection), l.mime);
+                subsection.title = Some(title.clone());
+                entries.push(Entry::new(l.href.clone(), title.clone()).with_link(subsection));
+
+                let mut collection = Link::new(
+                    l.href,
+                    Some(Relation::from(COLLECTION_REL.to_string())),
+                    None,
+                );
+                collection.title = Some(title);
+                Some(collection)
+            }
+            None => {
+                // Without a link to the group, there is nowhere for entries to point at.
+                self.report.unsupported(format!("{path}/metadata/title"));
+                None
+            }
+        };
+
+        for (i, l) in navigation.into_iter().enumerate() {
+            let entry = self.navigation(&format!("{path}/navigation/{i}"), l);
+            entries.extend(entry);
+        }
+
+        for (i, p) in publications.into_iter().enumerate() {
+            let entry = self.entry(&format!("{path}/publications/{i}"), p);
+            entries.push(entry);
+        }
+
+        if let Some(collection) = collection {
+            for entry in entries.iter_mut().skip(1) {
+                entry.links.push(collection.clone());
+            }
+        }
+
+        entries
+    }
+}
+
+impl<'a> v2_0::Feed<'a> {
+    /// Convert this feed into an OPDS 1.2 [Feed], along with a [Report] of anything that could
+    /// not be carried over.
+    ///
+    /// Navigation links become navigation entries, publications become entries, and facets
+    /// become links with an `opds:facetGroup`. Each group becomes a navigation entry that leads
+    /// to the full group, followed by its contents, which link back to the group using the
+    /// "collection" relation.
+    ///
+    /// When a title or name has alternatives in several languages, the one matching `language`
+    /// is used, falling back to the first available one.
+    pub fn into_v1_2(self, language: Option<&LangTag>) -> (Feed<'a>, Report) {
+        let mut state = Downgrade {
+            report: Report::default(),
+            language,
+        };
+
+        // Atom requires an identifier, so fall back to the feed's own URL, or else to one
+        // generated from its contents.
+        let id = match &self.metadata.identifier {
+            Some(identifier) => Cow::Owned(identifier.to_string()),
+            None => state.self_link("/metadata/identifier", &self.links, &self),
+        };
+
+        let v2_0::Feed {
+            metadata,
+            links,
+            navigation,
+            facets,
+            publications,
+            groups,
+            extensions,
+        } = self;
+
+        let FeedMetadata {
+            title,
+            subtitle,
+            identifier: _,
+            schema,
+            modified,
+            description,
+            items_per_page,
+            current_page,
+            number_of_items,
+            extensions: metadata_extensions,
+        } = metadata;
+
+        state.unsupported("/metadata", &[("@type", schema.is_some())]);
+        state.extensions("/metadata", metadata_extensions);
+        state.extensions("", extensions);
+
+        let title = state.string("/metadata/title".into(), title);
+        let mut feed = Feed::new(id, title);
+        feed.updated = modified.map(|t| Cow::Owned(t.to_string()));
+        feed.total_results = number_of_items;
+        feed.items_per_page = items_per_page;
+
+        let subtitle = state.first("/metadata/subtitle".into(), subtitle);
+        feed.subtitle = match (subtitle, description) {
+            (Some(subtitle), description) => {
+                state.unsupported("/metadata", &[("description", description.is_some())]);
+                Some(state.string("/metadata/subtitle/0".into(), subtitle).into())
+            }
+            (None, description) => description.map(Text::from),
+        };
+
+        match (current_page, items_per_page) {
+            (Some(page), Some(per_page)) => {
+                feed.start_index = Some(page.saturating_sub(1) * per_page + 1);
+            }
+            (Some(_), None) => state.report.unsupported("/metadata/currentPage".into()),
+            (None, _) => {}
+        }
+
+        for (i, l) in links.into_iter().enumerate() {
+            let l = state.link(&format!("/links/{i}"), l);
+            feed.links.extend(l);
+        }
+
+        let facets = state.facets(facets);
+        feed.links.extend(facets);
+
+        for (i, l) in navigation.into_iter().enumerate() {
+            let entry = state.navigation(&format!("/navigation/{i}"), l);
+            feed.entries.extend(entry);
+        }
+
+        for (i, p) in publications.into_iter().enumerate() {
+            let entry = state.entry(&format!("/publications/{i}"), p);
+            feed.entries.push(entry);
+        }
+
+        for (i, g) in groups.into_iter().enumerate() {
+            let entries = state.group(&format!("/groups/{i}"), g);
+            feed.entries.extend(entries);
+        }
+
+        (feed, state.report)
+    }
+}
+
+impl<'a> From<v2_0::Feed<'a>> for Feed<'a> {
+    fn from(feed: v2_0::Feed<'a>) -> Self {
+        feed.into_v1_2(None).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mime;
+    use crate::v2_0::price::Amount;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn read_feed(name: &str) -> Feed<'static> {
+        let xml = std::fs::read_to_string(format!("{CRATE_DIR}/tests/{name}.in.xml"))
+            .expect("valid file input");
+        Feed::from_xml(&xml).expect("can parse feed")
+    }
+
+    #[test]
+    fn test_acquisition_feed_into_v2_0() {
+        let (feed, report) = read_feed("test-atom-opds-spec").into_v2_0();
+
+        assert_eq!(feed.metadata.number_of_items, Some(4));
+        assert_eq!(feed.links.len(), 5);
+        assert_eq!(feed.facets.len(), 1);
+        assert_eq!(feed.facets[0].links[0].rel, vec![Relation::Myself]);
+        assert_eq!(feed.facets[0].links[0].properties.count, Some(12));
+        assert!(feed.navigation.is_empty());
+        assert_eq!(feed.publications.len(), 2);
+
+        let publication = &feed.publications[0];
+        assert_eq!(publication.images.len(), 2);
+        assert_eq!(publication.images[0].rel, vec![Relation::Cover]);
+        assert_eq!(publication.links.len(), 2);
+        assert_eq!(
+            publication
+                .metadata
+                .published
+                .as_ref()
+                .map(|t| t.to_string())
+                .as_deref(),
+            Some("1917")
+        );
+
+        let series = &publication.metadata.belongs_to.as_ref().unwrap().series[0];
+        assert_eq!(series.position, Some(2));
+
+        let buy = &publication.links[0];
+        assert_eq!(buy.get_acquisition(), Some(AcquisitionKind::Buy));
+        assert_eq!(
+            buy.properties.price.as_ref().unwrap().value,
+            Amount::new(1099, 2)
+        );
+        assert_eq!(buy.properties.indirect_acquisition.len(), 1);
+
+        let paths: Vec<_> = report.losses.iter().map(|l| l.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["/author/0", "/entries/0/content", "/entries/1/rights"]
+        );
+    }
+
+    #[test]
+    fn test_navigation_feed_into_v2_0() {
+        let (feed, report) = read_feed("test-atom-opds-spec-navigation").into_v2_0();
+
+        assert!(feed.publications.is_empty());
+        assert_eq!(feed.navigation.len(), 3);
+        assert_eq!(
+            feed.navigation[0].title.as_deref(),
+            Some("Popular Publications")
+        );
+        assert_eq!(feed.navigation[2].rel, vec![Relation::Subsection]);
+
+        assert!(
+            report
+                .losses
+                .iter()
+                .all(|l| l.kind == LossKind::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_entry_classification_into_v2_0() {
+        let rel = |r: &str| Some(r.parse::<Relation>().unwrap());
+        let link = |href: &'static str, rel, mime: &'static str| {
+            Link::new(href.into(), rel, Some(mime.into()))
+        };
+
+        let navigation = Entry::new("urn:nav".into(), "Popular")
+            .with_link(link("/thumb.png", rel(THUMBNAIL_REL), "image/png"))
+            .with_link(link(
+                "/popular",
+                None,
+                mime::APPLICATION_ATOM_XML_ACQUISITION,
+            ));
+        let partial = Entry::new("urn:partial".into(), "Partial").with_link(link(
+            "/full",
+            rel("alternate"),
+            mime::APPLICATION_ATOM_XML_ENTRY,
+        ));
+        let cover_only = Entry::new("urn:cover".into(), "Cover").with_link(link(
+            "/cover.jpg",
+            rel(IMAGE_REL),
+            "image/jpeg",
+        ));
+
+        assert!(navigation.is_navigation());
+        assert!(!partial.is_navigation());
+        assert!(!cover_only.is_navigation());
+
+        let (feed, report) = Feed::new("urn:feed".into(), "Feed")
+            .with_entry(navigation)
+            .with_entry(partial)
+            .with_entry(cover_only)
+            .into_v2_0();
+
+        assert_eq!(feed.navigation.len(), 1);
+        assert_eq!(feed.navigation[0].href.as_deref(), Some("/popular"));
+        assert_eq!(feed.navigation[0].title.as_deref(), Some("Popular"));
+        assert_eq!(
+            report.losses[0],
+            Loss {
+                path: "/entries/0/links".to_string(),
+                kind: LossKind::ExtraValues { dropped: 1 },
+            }
+        );
+
+        assert_eq!(feed.publications.len(), 2);
+        assert_eq!(feed.publications[0].links[0].href.as_deref(), Some("/full"));
+        assert_eq!(
+            feed.publications[1].images[0].href.as_deref(),
+            Some("/cover.jpg")
+        );
+    }
+
+    #[test]
+    fn test_feed_into_v1_2() {
+        let json = std::fs::read_to_string(format!("{CRATE_DIR}/tests/test-feed-opds-io.in.json"))
+            .expect("valid file input");
+        let feed: v2_0::Feed<'_> = serde_json::from_str(&json).expect("can parse feed");
+        let (feed, report) = feed.into_v1_2(None);
+
+        assert_eq!(feed.title.value, "OPDS 2.0 Test Catalog");
+        assert_eq!(feed.id, "https://test.opds.io/2.0/home.json");
+
+        let navigation: Vec<_> = feed.entries.iter().filter(|e| e.is_navigation()).collect();
+        assert_eq!(navigation[0].links[0].href, navigation[0].id);
+
+        // The "French Classics" group becomes a navigation entry, and its publications link
+        // back to it.
+        let group = feed
+            .entries
+            .iter()
+            .position(|e| e.title.value == "French Classics")
+            .expect("group has a navigation entry");
+        assert_eq!(feed.entries[group].links[0].rel, Some(Relation::Subsection));
+
+        let swann = feed
+            .entries
+            .iter()
+            .find(|e| e.title.value == "Du côté de chez Swann")
+            .expect("group publication is converted");
+        let collection = swann.links.last().unwrap();
+        assert_eq!(collection.rel.as_ref().unwrap().as_str(), COLLECTION_REL);
+        assert_eq!(collection.title.as_deref(), Some("French Classics"));
+        assert_eq!(collection.href, feed.entries[group].id);
+
+        let series = swann.series.as_ref().expect("series is carried over");
+        assert_eq!(series.name, "À la recherche du temps perdu");
+        assert_eq!(series.position, Some(1.0));
+
+        // Groups without a link cannot be pointed at from their entries.
+        assert!(
+            report
+                .losses
+                .iter()
+                .any(|l| l.path == "/groups/1/metadata/title")
+        );
+        assert!(
+            report
+                .losses
+                .iter()
+                .any(|l| l.path == "/publications/0/images/0/height")
+        );
+
+        // The converted feed can be written out and parsed back in.
+        let xml = feed.to_xml();
+        let reparsed = Feed::from_xml(&xml).expect("can parse generated feed");
+        assert_eq!(reparsed.entries.len(), feed.entries.len());
+    }
+
+    #[test]
+    fn test_feed_into_v1_2_losses() {
+        let json = r#"{
+            "metadata": {"title": {"en": "Library", "fr": "Bibliothèque"}},
+            "links": [{"rel": "self", "href": "https://example.com/feed.json"}],
+            "publications": [{
+                "metadata": {
+                    "title": "Example",
+                    "accessibility": {"summary": "Fully accessible."},
+                    "translator": "Someone",
+                    "http://palaceproject.io/terms/timeTracking": true
+                },
+                "links": [{
+                    "rel": "http://opds-spec.org/acquisition/borrow",
+                    "href": "https://example.com/borrow",
+                    "properties": {"holds": {"total": 3}, "copies": {"total": 1, "available": 0}}
+                }]
+            }]
+        }"#;
+
+        let feed: v2_0::Feed<'_> = serde_json::from_str(json).expect("can parse feed");
+        let language = langtag::LangTagBuf::new("fr-CA".to_string()).unwrap();
+        let (converted, report) = feed.clone().into_v1_2(Some(&language));
+
+        assert_eq!(converted.title.value, "Bibliothèque");
+        assert_eq!(converted.entries[0].contributor[0].name, "Someone");
+
+        // The entry has no identifier or self link, so it is given one based on its contents,
+        // which is the same each time it is converted.
+        let id = converted.entries[0].id.to_string();
+        assert!(id.starts_with("urn:uuid:"), "{id}");
+        assert_eq!(feed.into_v1_2(Some(&language)).0.entries[0].id, id);
+
+        assert_eq!(
+            report.losses,
+            vec![
+                Loss {
+                    path: "/metadata/title".into(),
+                    kind: LossKind::ExtraValues { dropped: 1 },
+                },
+                Loss {
+                    path: "/publications/0/metadata/identifier".into(),
+                    kind: LossKind::Generated { value: id },
+                },
+                Loss {
+                    path: "/publications/0/metadata/accessibility".into(),
+                    kind: LossKind::Unsupported,
+                },
+                Loss {
+                    path: "/publications/0/metadata/http:~1~1palaceproject.io~1terms~1timeTracking"
+                        .into(),
+                    kind: LossKind::Unsupported,
+                },
+                Loss {
+                    path: "/publications/0/metadata/translator/0".into(),
+                    kind: LossKind::Generalized,
+                },
+                Loss {
+                    path: "/publications/0/links/0/properties/holds".into(),
+                    kind: LossKind::Unsupported,
+                },
+                Loss {
+                    path: "/publications/0/links/0/properties/copies".into(),
+                    kind: LossKind::Unsupported,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ungrouped_facets_into_v2_0() {
+        let facet = |href: &'static str, group: Option<&'static str>| {
+            let mut link = Link::new(
+                href.into(),
+                Some(FACET_REL.parse().unwrap()),
+                Some(mime::APPLICATION_ATOM_XML_ACQUISITION.into()),
+            );
+            link.facet_group = group.map(Cow::Borrowed);
+            link.active_facet = true;
+            link
+        };
+
+        let (feed, report) = Feed::new("urn:feed".into(), "Feed")
+            .with_link(facet("/fiction", Some("Categories")))
+            .with_link(facet("/recent", None))
+            .into_v2_0();
+
+        assert_eq!(feed.facets.len(), 1);
+        assert_eq!(feed.facets[0].metadata.title, "Categories".into());
+        assert_eq!(feed.links[0].href.as_deref(), Some("/recent"));
+        assert_eq!(
+            report.losses,
+            vec![
+                Loss {
+                    path: "/links/1".into(),
+                    kind: LossKind::Generalized,
+                },
+                Loss {
+                    path: "/links/1/active_facet".into(),
+                    kind: LossKind::Unsupported,
+                },
+            ]
+        );
+    }
+}
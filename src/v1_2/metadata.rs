@@ -0,0 +1,148 @@
+//! Types used to represent the fields within feeds, entries and links.
+use std::borrow::Cow;
+
+/// How the value of a [Text] construct should be interpreted.
+///
+/// See [Section 3.1: Text Constructs] of the Atom specification.
+///
+/// [Section 3.1: Text Constructs]: https://www.rfc-editor.org/rfc/rfc4287#section-3.1
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum TextKind {
+    /// Plain text.
+    #[default]
+    Text,
+
+    /// Escaped HTML markup.
+    Html,
+
+    /// A single XHTML `<div>` element, stored as serialized markup.
+    Xhtml,
+}
+
+impl TextKind {
+    pub(crate) fn from_attr(value: Option<&str>) -> Self {
+        match value {
+            Some("html") | Some("text/html") => Self::Html,
+            Some("xhtml") | Some("application/xhtml+xml") => Self::Xhtml,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// A human-readable piece of text, such as a title or summary.
+///
+/// See [Section 3.1: Text Constructs] of the Atom specification.
+///
+/// [Section 3.1: Text Constructs]: https://www.rfc-editor.org/rfc/rfc4287#section-3.1
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Text<'a> {
+    pub kind: TextKind,
+    pub value: Cow<'a, str>,
+}
+
+impl<'a> Text<'a> {
+    pub fn new(kind: TextKind, value: Cow<'a, str>) -> Self {
+        Self { kind, value }
+    }
+}
+
+impl<'a> From<String> for Text<'a> {
+    fn from(value: String) -> Self {
+        Self::from(Cow::Owned(value))
+    }
+}
+
+impl<'a> From<&'a str> for Text<'a> {
+    fn from(value: &'a str) -> Self {
+        Self::from(Cow::Borrowed(value))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Text<'a> {
+    fn from(value: Cow<'a, str>) -> Self {
+        Self::new(TextKind::Text, value)
+    }
+}
+
+/// A person who authored or contributed to a feed or entry.
+///
+/// See [Section 3.2: Person Constructs] of the Atom specification.
+///
+/// [Section 3.2: Person Constructs]: https://www.rfc-editor.org/rfc/rfc4287#section-3.2
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Author<'a> {
+    pub name: Cow<'a, str>,
+    pub uri: Option<Cow<'a, str>>,
+    pub email: Option<Cow<'a, str>>,
+}
+
+impl<'a> Author<'a> {
+    pub fn new(name: Cow<'a, str>) -> Self {
+        Self {
+            name,
+            uri: None,
+            email: None,
+        }
+    }
+}
+
+impl<'a> From<String> for Author<'a> {
+    fn from(name: String) -> Self {
+        Self::from(Cow::Owned(name))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Author<'a> {
+    fn from(name: Cow<'a, str>) -> Self {
+        Self::new(name)
+    }
+}
+
+/// A category that an entry belongs to, such as its genre.
+///
+/// See [Section 4.2.2: The "atom:category" Element] of the Atom specification.
+///
+/// [Section 4.2.2: The "atom:category" Element]: https://www.rfc-editor.org/rfc/rfc4287#section-4.2.2
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Category<'a> {
+    pub term: Cow<'a, str>,
+    pub scheme: Option<Cow<'a, str>>,
+    pub label: Option<Cow<'a, str>>,
+}
+
+impl<'a> Category<'a> {
+    pub fn new(term: Cow<'a, str>) -> Self {
+        Self {
+            term,
+            scheme: None,
+            label: None,
+        }
+    }
+}
+
+/// The series that an entry belongs to.
+///
+/// OPDS 1.2 has no standard element for this, so it is represented using the metadata that
+/// [Calibre] adds to its catalogs: `calibre:series` and `calibre:series_index`.
+///
+/// [Calibre]: https://calibre-ebook.com/
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Series<'a> {
+    pub name: Cow<'a, str>,
+
+    /// The position of the entry within the series, which may be fractional.
+    pub position: Option<f64>,
+}
+
+impl<'a> Series<'a> {
+    pub fn new(name: Cow<'a, str>) -> Self {
+        Self {
+            name,
+            position: None,
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! Support for working with the OPDS v1.2 specification
+//!
+//! OPDS 1.2 catalogs are [Atom] feeds, extended with elements from the OPDS, Dublin Core,
+//! OpenSearch and Atom Threading namespaces. The types in this module can be parsed from
+//! these XML documents using [Feed::from_xml] and [Entry::from_xml], and written back out
+//! using [Feed::to_xml] and [Entry::to_xml].
+//!
+//! As noted in [Section 2: OPDS Catalog Feed Documents] of the OPDS specification, there are
+//! two kinds of feeds within a catalog:
+//!
+//! - [Navigation Feeds][opds-spec-navigation], whose entries each link to another feed
+//!   within the catalog, for the client to show the end user while they browse.
+//! - [Acquisition Feeds][opds-spec-acquisition], whose entries are publications that have
+//!   acquisition links for obtaining the publication.
+//!
+//! Both kinds are represented by the [Feed] type, and [Entry::is_navigation] can be used to
+//! tell them apart. The acquisition-related types, [Price] and [Acquisition], are shared with
+//! [crate::v2_0], since they carry the same information in both versions.
+//!
+//! Feeds can be converted into their OPDS 2.0 equivalent using [Feed::into_v2_0], and back
+//! again using [crate::v2_0::Feed::into_v1_2]. Both report anything that could not be carried
+//! over. See [convert] for details.
+//!
+//! [Atom]: https://www.rfc-editor.org/rfc/rfc4287
+//! [Section 2: OPDS Catalog Feed Documents]: https://specs.opds.io/opds-1.2#2-opds-catalog-feed-documents
+//! [opds-spec-navigation]: https://specs.opds.io/opds-1.2#22-navigation-feeds
+//! [opds-spec-acquisition]: https://specs.opds.io/opds-1.2#23-acquisition-feeds
+use std::borrow::Cow;
+
+use crate::mime::MediaType;
+use crate::v1_2::metadata::*;
+use crate::v2_0::metadata::{Acquisition, AcquisitionKind, Price, Relation};
+use crate::xml::Error;
+
+pub mod convert;
+pub mod metadata;
+
+mod parse;
+mod write;
+
+/// An Atom link, with the OPDS extensions for acquisition and facets.
+///
+/// See [Section 5: Links] for more information.
+///
+/// [Section 5: Links]: https://specs.opds.io/opds-1.2#5-links
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Link<'a> {
+    /// URI of the linked resource.
+    pub href: Cow<'a, str>,
+
+    /// Relation between the linked resource and the containing feed or entry.
+    ///
+    /// Atom treats a missing relation as [Relation::Alternate].
+    pub rel: Option<Relation>,
+
+    /// MIME type of the linked resource.
+    ///
+    /// See [crate::mime] for common OPDS MIME types.
+    pub mime: Option<Cow<'a, str>>,
+
+    /// Title of the linked resource.
+    pub title: Option<Cow<'a, str>>,
+
+    /// Language of the linked resource.
+    pub hreflang: Option<Cow<'a, langtag::LangTag>>,
+
+    /// Size of the linked resource in bytes.
+    pub length: Option<usize>,
+
+    /// The prices of the publication, from `opds:price`.
+    pub price: Vec<Price>,
+
+    /// The media types that will be acquired after additional steps, from
+    /// `opds:indirectAcquisition`.
+    pub indirect_acquisition: Vec<Acquisition<'a>>,
+
+    /// A hint about the number of entries in the linked feed, from `thr:count`.
+    pub count: Option<usize>,
+
+    /// The name of the group of facets that this link belongs to, from `opds:facetGroup`.
+    pub facet_group: Option<Cow<'a, str>>,
+
+    /// Whether this facet is currently selected, from `opds:activeFacet`.
+    pub active_facet: bool,
+}
+
+impl<'a> Link<'a> {
+    pub fn new(href: Cow<'a, str>, rel: Option<Relation>, mime: Option<Cow<'a, str>>) -> Self {
+        Link {
+            href,
+            rel,
+            mime,
+
+            title: None,
+            hreflang: None,
+            length: None,
+            price: vec![],
+            indirect_acquisition: vec![],
+            count: None,
+            facet_group: None,
+            active_facet: false,
+        }
+    }
+
+    pub fn get_acquisition(&self) -> Option<AcquisitionKind> {
+        self.rel.as_ref().and_then(Relation::as_acquisition)
+    }
+
+    /// Whether this link leads to another catalog feed, rather than to a publication or a
+    /// resource like an image.
+    pub fn is_catalog(&self) -> bool {
+        self.mime
+            .as_deref()
+            .and_then(|mime| MediaType::parse(mime).ok())
+            .is_some_and(|mime| mime.is_opds_feed())
+    }
+}
+
+/// An Atom entry, describing either a publication or a navigation link.
+///
+/// See [Section 5: OPDS Catalog Entry Documents] for more information.
+///
+/// [Section 5: OPDS Catalog Entry Documents]: https://specs.opds.io/opds-1.2#5-opds-catalog-entry-documents
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Entry<'a> {
+    /// A permanent, universally unique identifier for this entry.
+    pub id: Cow<'a, str>,
+
+    pub title: Text<'a>,
+
+    /// When this entry was last updated.
+    ///
+    /// While this element is required by Atom, some catalogs omit it.
+    pub updated: Option<Cow<'a, str>>,
+
+    /// When this entry was first made available.
+    pub published: Option<Cow<'a, str>>,
+
+    pub author: Vec<Author<'a>>,
+
+    pub contributor: Vec<Author<'a>>,
+
+    pub rights: Option<Text<'a>>,
+
+    /// A short description of the publication.
+    pub summary: Option<Text<'a>>,
+
+    /// A complete description of the publication.
+    pub content: Option<Text<'a>>,
+
+    pub category: Vec<Category<'a>>,
+
+    pub links: Vec<Link<'a>>,
+
+    /// Identifiers for the publication, such as an ISBN, from `dc:identifier`.
+    pub identifier: Vec<Cow<'a, str>>,
+
+    /// Languages of the publication, from `dc:language`.
+    pub language: Vec<Cow<'a, langtag::LangTag>>,
+
+    /// Publishers of the publication, from `dc:publisher`.
+    pub publisher: Vec<Cow<'a, str>>,
+
+    /// When the publication was first published, from `dc:issued`.
+    pub issued: Option<Cow<'a, str>>,
+
+    /// The series that the publication belongs to.
+    pub series: Option<Series<'a>>,
+}
+
+impl<'a> Entry<'a> {
+    pub fn new(id: Cow<'a, str>, title: impl Into<Text<'a>>) -> Self {
+        Self {
+            id,
+            title: title.into(),
+
+            updated: None,
+            published: None,
+            author: vec![],
+            contributor: vec![],
+            rights: None,
+            summary: None,
+            content: None,
+            category: vec![],
+            links: vec![],
+            identifier: vec![],
+            language: vec![],
+            publisher: vec![],
+            issued: None,
+            series: None,
+        }
+    }
+
+    /// Parse a standalone OPDS Catalog Entry Document.
+    pub fn from_xml(xml: &str) -> Result<Entry<'static>, Error> {
+        parse::entry_document(xml)
+    }
+
+    /// Write this entry as a standalone OPDS Catalog Entry Document.
+    pub fn write_xml<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        write::entry_document(self, writer)
+    }
+
+    /// Generate a standalone OPDS Catalog Entry Document for this entry.
+    pub fn to_xml(&self) -> String {
+        let mut buf = Vec::new();
+        self.write_xml(&mut buf).expect("can write to buffer");
+        String::from_utf8(buf).expect("writer produces UTF-8")
+    }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    /// Whether this entry is a navigation entry, rather than a publication.
+    ///
+    /// Navigation entries link to another catalog feed, while entries within Acquisition Feeds
+    /// must have at least one acquisition link. Entries with neither, such as partial entries
+    /// that only link to their complete entry, are treated as publications.
+    pub fn is_navigation(&self) -> bool {
+        self.links
+            .iter()
+            .all(|link| link.get_acquisition().is_none())
+            && self.links.iter().any(Link::is_catalog)
+    }
+}
+
+/// An OPDS catalog feed.
+///
+/// See [Section 2: OPDS Catalog Feed Documents] for more information.
+///
+/// [Section 2: OPDS Catalog Feed Documents]: https://specs.opds.io/opds-1.2#2-opds-catalog-feed-documents
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Feed<'a> {
+    /// A permanent, universally unique identifier for this feed.
+    pub id: Cow<'a, str>,
+
+    pub title: Text<'a>,
+
+    pub subtitle: Option<Text<'a>>,
+
+    /// When this feed was last updated.
+    ///
+    /// While this element is required by Atom, some catalogs omit it.
+    pub updated: Option<Cow<'a, str>>,
+
+    /// URI of a small image representing the catalog.
+    pub icon: Option<Cow<'a, str>>,
+
+    pub author: Vec<Author<'a>>,
+
+    pub links: Vec<Link<'a>>,
+
+    /// The total number of search results, from `opensearch:totalResults`.
+    pub total_results: Option<usize>,
+
+    /// The number of results per page, from `opensearch:itemsPerPage`.
+    pub items_per_page: Option<usize>,
+
+    /// The index of the first result on this page, from `opensearch:startIndex`.
+    pub start_index: Option<usize>,
+
+    pub entries: Vec<Entry<'a>>,
+}
+
+impl<'a> Feed<'a> {
+    pub fn new(id: Cow<'a, str>, title: impl Into<Text<'a>>) -> Self {
+        Self {
+            id,
+            title: title.into(),
+
+            subtitle: None,
+            updated: None,
+            icon: None,
+            author: vec![],
+            links: vec![],
+            total_results: None,
+            items_per_page: None,
+            start_index: None,
+            entries: vec![],
+        }
+    }
+
+    /// Parse an OPDS Catalog Feed Document.
+    pub fn from_xml(xml: &str) -> Result<Feed<'static>, Error> {
+        parse::feed_document(xml)
+    }
+
+    /// Write this feed as an OPDS Catalog Feed Document.
+    pub fn write_xml<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        write::feed_document(self, writer)
+    }
+
+    /// Generate an OPDS Catalog Feed Document for this feed.
+    pub fn to_xml(&self) -> String {
+        let mut buf = Vec::new();
+        self.write_xml(&mut buf).expect("can write to buffer");
+        String::from_utf8(buf).expect("writer produces UTF-8")
+    }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    pub fn with_entry(mut self, entry: Entry<'a>) -> Self {
+        self.entries.push(entry);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2_0::price::{Amount, Currency};
+    use anyhow::Context;
+    use std::path::PathBuf;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn get_prefixes(prefix: &str) -> Vec<String> {
+        let crate_dir = PathBuf::from(CRATE_DIR);
+        let tests = crate_dir.join("tests");
+
+        std::fs::read_dir(&tests)
+            .unwrap()
+            .map(|f| f.unwrap().path())
+            .filter(|f| {
+                f.file_name()
+                    .and_then(|f| f.to_str())
+                    .unwrap_or_default()
+                    .starts_with(prefix)
+            })
+            .flat_map(|f| {
+                let f = f.to_str()?;
+                let p = f.strip_suffix(".in.xml")?;
+                Some(p.to_string())
+            })
+            .collect()
+    }
+
+    fn read_input(prefix: &str) -> String {
+        let input = format!("{prefix}.in.xml");
+
+        std::fs::read_to_string(&input)
+            .with_context(|| format!("can load {input:?}"))
+            .expect("valid file input")
+    }
+
+    fn read_output(prefix: &str) -> String {
+        let output = format!("{prefix}.out.xml");
+
+        std::fs::read_to_string(&output)
+            .with_context(|| format!("can load {output:?}"))
+            .expect("valid file output")
+    }
+
+    #[test]
+    fn test_feed_parse() {
+        for prefix in get_prefixes("test-atom") {
+            let xml = read_input(&prefix);
+
+            Feed::from_xml(&xml)
+                .with_context(|| format!("can parse {prefix:?} input as a Feed"))
+                .expect("can parse feed");
+        }
+    }
+
+    #[test]
+    fn test_feed_roundtrip() {
+        for prefix in get_prefixes("test-atom") {
+            let xml_in = read_input(&prefix);
+            let xml_exp = read_output(&prefix);
+
+            let feed = Feed::from_xml(&xml_in)
+                .with_context(|| format!("can parse {prefix:?} input as a Feed"))
+                .expect("can parse feed");
+
+            let xml_out = feed.to_xml();
+
+            pretty_assertions::assert_eq!(
+                xml_out.trim_end(),
+                xml_exp.trim_end(),
+                "{prefix} input matches expected output file after parsing"
+            );
+
+            // The generated document should parse back into the same feed.
+            let reparsed = Feed::from_xml(&xml_out).expect("can parse generated feed");
+            assert_eq!(reparsed.to_xml(), xml_out);
+        }
+    }
+
+    #[test]
+    fn test_acquisition_feed() {
+        let xml = read_input(&format!("{CRATE_DIR}/tests/test-atom-opds-spec"));
+        let feed = Feed::from_xml(&xml).expect("can parse feed");
+
+        assert_eq!(feed.id, "urn:uuid:433a5d6a-0b8c-4933-af65-4ca4f02763eb");
+        assert_eq!(feed.title.value, "Unpopular Publications");
+        assert_eq!(feed.total_results, Some(4));
+        assert_eq!(feed.links.len(), 6);
+
+        let facet = &feed.links[5];
+        assert_eq!(facet.facet_group.as_deref(), Some("Categories"));
+        assert_eq!(facet.count, Some(12));
+        assert!(facet.active_facet);
+
+        assert_eq!(feed.entries.len(), 2);
+        let entry = &feed.entries[0];
+        assert!(!entry.is_navigation());
+        assert_eq!(entry.author[0].name, "Bob Bobson");
+        assert_eq!(entry.issued.as_deref(), Some("1917"));
+        assert_eq!(entry.language[0].as_str(), "en");
+        assert_eq!(entry.identifier[0], "urn:isbn:9780000000001");
+        assert_eq!(entry.category[0].label.as_deref(), Some("Fiction"));
+        assert_eq!(entry.content.as_ref().unwrap().kind, TextKind::Xhtml);
+
+        let buy = &entry.links[2];
+        assert_eq!(buy.get_acquisition(), Some(AcquisitionKind::Buy));
+        assert_eq!(buy.price[0].currency, Currency::USD);
+        assert_eq!(buy.price[0].value, Amount::new(1099, 2));
+        assert_eq!(
+            buy.indirect_acquisition[0].mime,
+            "application/vnd.adobe.adept+xml"
+        );
+        assert_eq!(
+            buy.indirect_acquisition[0].child[0].mime,
+            "application/epub+zip"
+        );
+    }
+
+    #[test]
+    fn test_navigation_entry() {
+        let xml = read_input(&format!("{CRATE_DIR}/tests/test-atom-opds-spec-navigation"));
+        let feed = Feed::from_xml(&xml).expect("can parse feed");
+
+        assert!(feed.entries.iter().all(Entry::is_navigation));
+        assert_eq!(feed.entries[0].links[0].rel, Some(Relation::SortPopular));
+        assert_eq!(feed.entries[2].links[0].rel, Some(Relation::Subsection));
+    }
+
+    #[test]
+    fn test_wrong_root() {
+        let res = Feed::from_xml("<entry xmlns=\"http://www.w3.org/2005/Atom\"/>");
+        assert!(matches!(res, Err(Error::UnexpectedRoot { .. })));
+
+        let res = Feed::from_xml("");
+        assert!(matches!(res, Err(Error::UnexpectedRoot { .. })));
+    }
+}
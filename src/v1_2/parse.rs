@@ -0,0 +1,278 @@
+//! Conversion of parsed XML elements into the types in [crate::v1_2].
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use super::*;
+use crate::xml::{
+    self, ATOM, CALIBRE, DC_ELEMENTS, DC_TERMS, Element, OPDS, OPENSEARCH, THREADING,
+};
+
+fn is_dc(element: &Element, name: &str) -> bool {
+    element.is(DC_TERMS, name) || element.is(DC_ELEMENTS, name)
+}
+
+fn root(xml: &str, expected: &'static str) -> Result<Element, Error> {
+    match xml::parse(xml)? {
+        Some(root) if root.is(ATOM, expected) => Ok(root),
+        Some(root) => Err(Error::UnexpectedRoot {
+            expected,
+            found: root.name,
+        }),
+        None => Err(Error::UnexpectedRoot {
+            expected,
+            found: String::new(),
+        }),
+    }
+}
+
+fn required(element: &Element, parent: &'static str, name: &'static str) -> Result<String, Error> {
+    element
+        .child(ATOM, name)
+        .map(Element::text)
+        .ok_or(Error::Missing { parent, name })
+}
+
+fn optional(element: &Element, name: &str) -> Option<Cow<'static, str>> {
+    element.child(ATOM, name).map(|e| Cow::Owned(e.text()))
+}
+
+fn number(name: &'static str, value: &str) -> Result<usize, Error> {
+    value.trim().parse().map_err(|_| Error::InvalidValue {
+        name,
+        value: value.to_string(),
+    })
+}
+
+fn langtag(name: &'static str, value: &str) -> Result<Cow<'static, langtag::LangTag>, Error> {
+    let tag =
+        langtag::LangTagBuf::new(value.trim().to_string()).map_err(|_| Error::InvalidValue {
+            name,
+            value: value.to_string(),
+        })?;
+
+    Ok(Cow::Owned(tag))
+}
+
+fn text(element: &Element) -> Text<'static> {
+    let kind = TextKind::from_attr(element.attr("type"));
+
+    let value = match kind {
+        TextKind::Xhtml => xml::to_fragment(&element.children).trim().to_string(),
+        TextKind::Text | TextKind::Html => element.text(),
+    };
+
+    Text::new(kind, Cow::Owned(value))
+}
+
+fn author(element: &Element) -> Result<Author<'static>, Error> {
+    let name = element
+        .child(ATOM, "name")
+        .map(Element::text)
+        .ok_or(Error::Missing {
+            parent: "author",
+            name: "name",
+        })?;
+
+    let mut author = Author::from(name);
+    author.uri = optional(element, "uri");
+    author.email = optional(element, "email");
+
+    Ok(author)
+}
+
+fn category(element: &Element) -> Result<Category<'static>, Error> {
+    let term = element.attr("term").ok_or(Error::Missing {
+        parent: "category",
+        name: "term",
+    })?;
+
+    let mut category = Category::new(Cow::Owned(term.to_string()));
+    category.scheme = element.attr("scheme").map(|s| Cow::Owned(s.to_string()));
+    category.label = element.attr("label").map(|s| Cow::Owned(s.to_string()));
+
+    Ok(category)
+}
+
+fn price(element: &Element) -> Result<Price, Error> {
+    let currency = element.attr("currencycode").ok_or(Error::Missing {
+        parent: "opds:price",
+        name: "currencycode",
+    })?;
+
+    let value = element.text();
+    let value = value.parse().map_err(|_| Error::InvalidValue {
+        name: "opds:price",
+        value,
+    })?;
+
+    let currency = currency.parse().map_err(|_| Error::InvalidValue {
+        name: "currencycode",
+        value: currency.to_string(),
+    })?;
+
+    Ok(Price { value, currency })
+}
+
+fn indirect_acquisition(element: &Element) -> Result<Acquisition<'static>, Error> {
+    let mime = element.attr("type").ok_or(Error::Missing {
+        parent: "opds:indirectAcquisition",
+        name: "type",
+    })?;
+
+    let child = element
+        .elements()
+        .filter(|e| e.is(OPDS, "indirectAcquisition"))
+        .map(indirect_acquisition)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Acquisition {
+        mime: Cow::Owned(mime.to_string()),
+        child,
+    })
+}
+
+fn link(element: &Element) -> Result<Link<'static>, Error> {
+    let href = element.attr("href").ok_or(Error::Missing {
+        parent: "link",
+        name: "href",
+    })?;
+    let rel = element.attr("rel").map(|rel| {
+        let Ok(rel) = Relation::from_str(rel);
+        rel
+    });
+    let mime = element.attr("type").map(|s| Cow::Owned(s.to_string()));
+
+    let mut link = Link::new(Cow::Owned(href.to_string()), rel, mime);
+    link.title = element.attr("title").map(|s| Cow::Owned(s.to_string()));
+    link.hreflang = element
+        .attr("hreflang")
+        .map(|s| langtag("hreflang", s))
+        .transpose()?;
+    link.length = element
+        .attr("length")
+        .map(|s| number("length", s))
+        .transpose()?;
+
+    for attr in element.attrs.iter() {
+        match (attr.ns.as_deref(), attr.name.as_str()) {
+            (Some(THREADING), "count") => {
+                link.count = Some(number("thr:count", &attr.value)?);
+            }
+            (Some(OPDS), "facetGroup") => {
+                link.facet_group = Some(Cow::Owned(attr.value.clone()));
+            }
+            (Some(OPDS), "activeFacet") => {
+                link.active_facet = attr.value.trim() == "true";
+            }
+            _ => {}
+        }
+    }
+
+    for child in element.elements() {
+        if child.is(OPDS, "price") {
+            link.price.push(price(child)?);
+        } else if child.is(OPDS, "indirectAcquisition") {
+            link.indirect_acquisition.push(indirect_acquisition(child)?);
+        }
+    }
+
+    Ok(link)
+}
+
+fn entry(element: &Element) -> Result<Entry<'static>, Error> {
+    let id = required(element, "entry", "id")?;
+    let title = element.child(ATOM, "title").ok_or(Error::Missing {
+        parent: "entry",
+        name: "title",
+    })?;
+
+    let mut entry = Entry::new(Cow::Owned(id), text(title));
+
+    for child in element.elements() {
+        if child.is(ATOM, "updated") {
+            entry.updated = Some(Cow::Owned(child.text()));
+        } else if child.is(ATOM, "published") {
+            entry.published = Some(Cow::Owned(child.text()));
+        } else if child.is(ATOM, "author") {
+            entry.author.push(author(child)?);
+        } else if child.is(ATOM, "contributor") {
+            entry.contributor.push(author(child)?);
+        } else if child.is(ATOM, "rights") {
+            entry.rights = Some(text(child));
+        } else if child.is(ATOM, "summary") {
+            entry.summary = Some(text(child));
+        } else if child.is(ATOM, "content") {
+            entry.content = Some(text(child));
+        } else if child.is(ATOM, "category") {
+            entry.category.push(category(child)?);
+        } else if child.is(ATOM, "link") {
+            entry.links.push(link(child)?);
+        } else if is_dc(child, "identifier") {
+            entry.identifier.push(Cow::Owned(child.text()));
+        } else if is_dc(child, "language") {
+            entry.language.push(langtag("dc:language", &child.text())?);
+        } else if is_dc(child, "publisher") {
+            entry.publisher.push(Cow::Owned(child.text()));
+        } else if is_dc(child, "issued") {
+            entry.issued = Some(Cow::Owned(child.text()));
+        } else if child.is(CALIBRE, "series") {
+            entry.series = Some(Series::new(Cow::Owned(child.text())));
+        }
+    }
+
+    if let Some(series) = entry.series.as_mut()
+        && let Some(index) = element.child(CALIBRE, "series_index")
+    {
+        let index = index.text();
+        let position = index.parse().map_err(|_| Error::InvalidValue {
+            name: "calibre:series_index",
+            value: index,
+        })?;
+
+        series.position = Some(position);
+    }
+
+    Ok(entry)
+}
+
+fn feed(element: &Element) -> Result<Feed<'static>, Error> {
+    let id = required(element, "feed", "id")?;
+    let title = element.child(ATOM, "title").ok_or(Error::Missing {
+        parent: "feed",
+        name: "title",
+    })?;
+
+    let mut feed = Feed::new(Cow::Owned(id), text(title));
+
+    for child in element.elements() {
+        if child.is(ATOM, "subtitle") {
+            feed.subtitle = Some(text(child));
+        } else if child.is(ATOM, "updated") {
+            feed.updated = Some(Cow::Owned(child.text()));
+        } else if child.is(ATOM, "icon") {
+            feed.icon = Some(Cow::Owned(child.text()));
+        } else if child.is(ATOM, "author") {
+            feed.author.push(author(child)?);
+        } else if child.is(ATOM, "link") {
+            feed.links.push(link(child)?);
+        } else if child.is(ATOM, "entry") {
+            feed.entries.push(entry(child)?);
+        } else if child.is(OPENSEARCH, "totalResults") {
+            feed.total_results = Some(number("opensearch:totalResults", &child.text())?);
+        } else if child.is(OPENSEARCH, "itemsPerPage") {
+            feed.items_per_page = Some(number("opensearch:itemsPerPage", &child.text())?);
+        } else if child.is(OPENSEARCH, "startIndex") {
+            feed.start_index = Some(number("opensearch:startIndex", &child.text())?);
+        }
+    }
+
+    Ok(feed)
+}
+
+pub(super) fn feed_document(xml: &str) -> Result<Feed<'static>, Error> {
+    feed(&root(xml, "feed")?)
+}
+
+pub(super) fn entry_document(xml: &str) -> Result<Entry<'static>, Error> {
+    entry(&root(xml, "entry")?)
+}
@@ -0,0 +1,253 @@
+//! Conversion of the types in [crate::v1_2] into XML elements.
+use super::*;
+use crate::xml::{
+    self, ATOM, CALIBRE, DC_TERMS, Element, ElementWriter, Node, OPDS, OPENSEARCH, THREADING,
+};
+
+/// The prefixes declared on the root element of every generated document.
+const PREFIXES: &[(&str, &str)] = &[
+    (CALIBRE, "calibre"),
+    (DC_TERMS, "dc"),
+    (OPDS, "opds"),
+    (OPENSEARCH, "opensearch"),
+    (THREADING, "thr"),
+];
+
+fn text(name: &str, text: &Text<'_>) -> Element {
+    let element = Element::new(ATOM, name);
+
+    match text.kind {
+        TextKind::Text => element.with_text(&text.value),
+        TextKind::Html => element.with_attr("type", "html").with_text(&text.value),
+        TextKind::Xhtml => {
+            let mut element = element.with_attr("type", "xhtml");
+
+            match xml::parse_fragment(&text.value) {
+                Some(children) => element.children = children,
+                None => element.children.push(Node::Text(text.value.to_string())),
+            }
+
+            element
+        }
+    }
+}
+
+fn author(name: &str, author: &Author<'_>) -> Element {
+    let mut element =
+        Element::new(ATOM, name).with_child(Element::text_element(ATOM, "name", &author.name));
+
+    if let Some(uri) = &author.uri {
+        element = element.with_child(Element::text_element(ATOM, "uri", uri));
+    }
+
+    if let Some(email) = &author.email {
+        element = element.with_child(Element::text_element(ATOM, "email", email));
+    }
+
+    element
+}
+
+fn category(category: &Category<'_>) -> Element {
+    let mut element = Element::new(ATOM, "category");
+
+    if let Some(scheme) = &category.scheme {
+        element = element.with_attr("scheme", scheme);
+    }
+
+    element = element.with_attr("term", &category.term);
+
+    if let Some(label) = &category.label {
+        element = element.with_attr("label", label);
+    }
+
+    element
+}
+
+fn indirect_acquisition(acquisition: &Acquisition<'_>) -> Element {
+    acquisition.child.iter().fold(
+        Element::new(OPDS, "indirectAcquisition").with_attr("type", &acquisition.mime),
+        |element, child| element.with_child(indirect_acquisition(child)),
+    )
+}
+
+fn link(link: &Link<'_>) -> Element {
+    let mut element = Element::new(ATOM, "link");
+
+    if let Some(rel) = &link.rel {
+        element = element.with_attr("rel", rel.as_str());
+    }
+
+    element = element.with_attr("href", &link.href);
+
+    if let Some(mime) = &link.mime {
+        element = element.with_attr("type", mime);
+    }
+
+    if let Some(title) = &link.title {
+        element = element.with_attr("title", title);
+    }
+
+    if let Some(hreflang) = &link.hreflang {
+        element = element.with_attr("hreflang", hreflang.as_str());
+    }
+
+    if let Some(length) = link.length {
+        element = element.with_attr("length", &length.to_string());
+    }
+
+    if let Some(group) = &link.facet_group {
+        element = element.with_ns_attr(Some(OPDS), "facetGroup", group);
+    }
+
+    if link.active_facet {
+        element = element.with_ns_attr(Some(OPDS), "activeFacet", "true");
+    }
+
+    if let Some(count) = link.count {
+        element = element.with_ns_attr(Some(THREADING), "count", &count.to_string());
+    }
+
+    for price in link.price.iter() {
+        let price = Element::text_element(OPDS, "price", &price.value.to_string())
+            .with_attr("currencycode", price.currency.as_str());
+        element = element.with_child(price);
+    }
+
+    for acquisition in link.indirect_acquisition.iter() {
+        element = element.with_child(indirect_acquisition(acquisition));
+    }
+
+    element
+}
+
+fn entry(entry: &Entry<'_>) -> Element {
+    let mut element = Element::new(ATOM, "entry")
+        .with_child(Element::text_element(ATOM, "id", &entry.id))
+        .with_child(text("title", &entry.title));
+
+    if let Some(updated) = &entry.updated {
+        element = element.with_child(Element::text_element(ATOM, "updated", updated));
+    }
+
+    if let Some(published) = &entry.published {
+        element = element.with_child(Element::text_element(ATOM, "published", published));
+    }
+
+    for a in entry.author.iter() {
+        element = element.with_child(author("author", a));
+    }
+
+    for c in entry.contributor.iter() {
+        element = element.with_child(author("contributor", c));
+    }
+
+    if let Some(rights) = &entry.rights {
+        element = element.with_child(text("rights", rights));
+    }
+
+    for identifier in entry.identifier.iter() {
+        element = element.with_child(Element::text_element(DC_TERMS, "identifier", identifier));
+    }
+
+    for publisher in entry.publisher.iter() {
+        element = element.with_child(Element::text_element(DC_TERMS, "publisher", publisher));
+    }
+
+    for language in entry.language.iter() {
+        let language = Element::text_element(DC_TERMS, "language", language.as_str());
+        element = element.with_child(language);
+    }
+
+    if let Some(issued) = &entry.issued {
+        element = element.with_child(Element::text_element(DC_TERMS, "issued", issued));
+    }
+
+    if let Some(series) = &entry.series {
+        element = element.with_child(Element::text_element(CALIBRE, "series", &series.name));
+
+        if let Some(position) = series.position {
+            let position = position.to_string();
+            element = element.with_child(Element::text_element(CALIBRE, "series_index", &position));
+        }
+    }
+
+    for c in entry.category.iter() {
+        element = element.with_child(category(c));
+    }
+
+    if let Some(summary) = &entry.summary {
+        element = element.with_child(text("summary", summary));
+    }
+
+    if let Some(content) = &entry.content {
+        element = element.with_child(text("content", content));
+    }
+
+    for l in entry.links.iter() {
+        element = element.with_child(link(l));
+    }
+
+    element
+}
+
+fn feed(feed: &Feed<'_>) -> Element {
+    let mut element = Element::new(ATOM, "feed")
+        .with_child(Element::text_element(ATOM, "id", &feed.id))
+        .with_child(text("title", &feed.title));
+
+    if let Some(subtitle) = &feed.subtitle {
+        element = element.with_child(text("subtitle", subtitle));
+    }
+
+    if let Some(updated) = &feed.updated {
+        element = element.with_child(Element::text_element(ATOM, "updated", updated));
+    }
+
+    if let Some(icon) = &feed.icon {
+        element = element.with_child(Element::text_element(ATOM, "icon", icon));
+    }
+
+    for a in feed.author.iter() {
+        element = element.with_child(author("author", a));
+    }
+
+    for l in feed.links.iter() {
+        element = element.with_child(link(l));
+    }
+
+    let opensearch = [
+        ("totalResults", feed.total_results),
+        ("itemsPerPage", feed.items_per_page),
+        ("startIndex", feed.start_index),
+    ];
+
+    for (name, value) in opensearch {
+        if let Some(value) = value {
+            let value = value.to_string();
+            element = element.with_child(Element::text_element(OPENSEARCH, name, &value));
+        }
+    }
+
+    for e in feed.entries.iter() {
+        element = element.with_child(entry(e));
+    }
+
+    element
+}
+
+fn write_document<W: std::io::Write>(root: &Element, writer: W) -> std::io::Result<()> {
+    use quick_xml::events::{BytesDecl, Event};
+
+    let mut writer = quick_xml::Writer::new_with_indent(writer, b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    ElementWriter::new(PREFIXES).write_root(&mut writer, root)?;
+    writer.get_mut().write_all(b"\n")
+}
+
+pub(super) fn feed_document<W: std::io::Write>(f: &Feed<'_>, writer: W) -> std::io::Result<()> {
+    write_document(&feed(f), writer)
+}
+
+pub(super) fn entry_document<W: std::io::Write>(e: &Entry<'_>, writer: W) -> std::io::Result<()> {
+    write_document(&entry(e), writer)
+}
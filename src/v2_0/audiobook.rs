@@ -0,0 +1,295 @@
+//! Support for the Readium Audiobook profile
+//!
+//! An audiobook is a [Manifest] whose reading order is made up entirely of audio files, each
+//! with a known [Link::duration], and which declares that it follows the profile in
+//! [PublicationMetadata::conforms_to]. The [Audiobook] type checks these requirements once, and
+//! then provides helpers for working with positions across the whole book, such as finding
+//! the track that a timestamp falls within.
+//!
+//! See the [Audiobook Profile] for more information.
+//!
+//! [Audiobook Profile]: https://readium.org/webpub-manifest/profiles/audiobook.html
+use std::fmt;
+
+use super::manifest::Manifest;
+use super::*;
+use crate::mime::MediaType;
+
+/// The URL used in `conformsTo` to indicate that a manifest follows the audiobook profile.
+pub const PROFILE: &str = "https://readium.org/webpub-manifest/profiles/audiobook";
+
+/// How far, in seconds, the total duration of the reading order may differ from
+/// [PublicationMetadata::duration], since publishers round track and publication durations
+/// independently.
+const DURATION_TOLERANCE: f64 = 1.0;
+
+/// A reason why a [Manifest] does not follow the audiobook profile.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The manifest's `conformsTo` does not include [PROFILE].
+    MissingProfile,
+
+    /// The reading order does not contain any tracks.
+    EmptyReadingOrder,
+
+    /// A track in the reading order is not an audio file.
+    NotAudio { index: usize, mime: Option<String> },
+
+    /// A track in the reading order does not have a valid duration.
+    MissingDuration { index: usize },
+
+    /// The tracks in the reading order do not add up to [PublicationMetadata::duration].
+    DurationMismatch { expected: f64, actual: f64 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingProfile => write!(f, "the manifest does not conform to {PROFILE}"),
+            Self::EmptyReadingOrder => write!(f, "the reading order is empty"),
+            Self::NotAudio { index, mime: None } => {
+                write!(f, "track {index} has no media type")
+            }
+            Self::NotAudio {
+                index,
+                mime: Some(mime),
+            } => {
+                write!(f, "track {index} has non-audio media type {mime:?}")
+            }
+            Self::MissingDuration { index } => write!(f, "track {index} has no valid duration"),
+            Self::DurationMismatch { expected, actual } => write!(
+                f,
+                "tracks last {actual} seconds, but the publication lasts {expected} seconds"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A single audio file within an [Audiobook]'s reading order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Track<'m, 'a> {
+    /// The position of this track within the reading order.
+    pub index: usize,
+
+    /// The link to this track's audio file.
+    pub link: &'m Link<'a>,
+
+    /// The time in seconds from the start of the audiobook at which this track begins.
+    pub start: f64,
+
+    /// The length of this track in seconds.
+    pub duration: f64,
+}
+
+impl Track<'_, '_> {
+    /// The time in seconds from the start of the audiobook at which this track ends.
+    pub fn end(&self) -> f64 {
+        self.start + self.duration
+    }
+}
+
+/// A [Manifest] that follows the audiobook profile.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Audiobook<'a> {
+    manifest: Manifest<'a>,
+}
+
+impl<'a> Audiobook<'a> {
+    /// The underlying manifest.
+    pub fn manifest(&self) -> &Manifest<'a> {
+        &self.manifest
+    }
+
+    pub fn into_manifest(self) -> Manifest<'a> {
+        self.manifest
+    }
+
+    /// The total length of the audiobook in seconds, according to its reading order.
+    pub fn duration(&self) -> f64 {
+        self.manifest
+            .reading_order
+            .iter()
+            .filter_map(|link| link.duration)
+            .sum()
+    }
+
+    /// The tracks of the audiobook, in reading order.
+    pub fn tracks(&self) -> impl Iterator<Item = Track<'_, 'a>> {
+        let mut start = 0.0;
+
+        self.manifest
+            .reading_order
+            .iter()
+            .enumerate()
+            .map(move |(index, link)| {
+                let duration = link.duration.unwrap_or_default();
+                let track = Track {
+                    index,
+                    link,
+                    start,
+                    duration,
+                };
+                start += duration;
+                track
+            })
+    }
+
+    /// The time in seconds from the start of the audiobook at which each track begins.
+    pub fn track_offsets(&self) -> Vec<f64> {
+        self.tracks().map(|track| track.start).collect()
+    }
+
+    /// Find the track playing at `timestamp` seconds from the start of the audiobook, along
+    /// with how many seconds into that track the timestamp falls.
+    ///
+    /// Returns `None` if the timestamp is before the start or after the end of the audiobook.
+    pub fn track_at(&self, timestamp: f64) -> Option<(Track<'_, 'a>, f64)> {
+        if timestamp < 0.0 {
+            return None;
+        }
+
+        self.tracks()
+            .find(|track| timestamp < track.end())
+            .map(|track| (track, timestamp - track.start))
+    }
+}
+
+impl<'a> TryFrom<Manifest<'a>> for Audiobook<'a> {
+    type Error = Error;
+
+    fn try_from(manifest: Manifest<'a>) -> Result<Self, Self::Error> {
+        let conforms_to = &manifest.metadata.conforms_to;
+
+        if !conforms_to.iter().any(|url| url.as_str() == PROFILE) {
+            return Err(Error::MissingProfile);
+        }
+
+        if manifest.reading_order.is_empty() {
+            return Err(Error::EmptyReadingOrder);
+        }
+
+        let is_audio = |mime: &str| {
+            MediaType::parse(mime).is_ok_and(|mime| mime.ty().eq_ignore_ascii_case("audio"))
+        };
+
+        for (index, link) in manifest.reading_order.iter().enumerate() {
+            let mime = link.mime.as_deref();
+
+            if !mime.is_some_and(is_audio) {
+                let mime = mime.map(str::to_string);
+                return Err(Error::NotAudio { index, mime });
+            }
+
+            if !link.duration.is_some_and(|d| d.is_finite() && d >= 0.0) {
+                return Err(Error::MissingDuration { index });
+            }
+        }
+
+        let audiobook = Self { manifest };
+
+        if let Some(expected) = audiobook.manifest.metadata.duration {
+            let actual = audiobook.duration();
+
+            if (actual - expected).abs() > DURATION_TOLERANCE {
+                return Err(Error::DurationMismatch { expected, actual });
+            }
+        }
+
+        Ok(audiobook)
+    }
+}
+
+impl<'a> From<Audiobook<'a>> for Manifest<'a> {
+    fn from(audiobook: Audiobook<'a>) -> Self {
+        audiobook.manifest
+    }
+}
+
+into_owned!(Audiobook { manifest; });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn read_input() -> String {
+        std::fs::read_to_string(format!("{CRATE_DIR}/tests/test-manifest-audiobook.in.json"))
+            .expect("valid file input")
+    }
+
+    fn parse(json: &str) -> Manifest<'_> {
+        serde_json::from_str(json).expect("can parse manifest")
+    }
+
+    #[test]
+    fn test_audiobook_tracks() {
+        let json = read_input();
+        let audiobook = Audiobook::try_from(parse(&json)).expect("valid audiobook");
+
+        assert_eq!(audiobook.duration(), 15137.0);
+        assert_eq!(audiobook.track_offsets(), vec![0.0, 1371.0, 3040.5]);
+
+        let (track, offset) = audiobook.track_at(1400.0).unwrap();
+        assert_eq!(track.index, 1);
+        assert_eq!(track.link.bitrate, Some(128.0));
+        assert_eq!(offset, 29.0);
+
+        assert_eq!(audiobook.track_at(0.0).unwrap().0.index, 0);
+        assert_eq!(audiobook.track_at(3040.5).unwrap().0.index, 2);
+        assert!(audiobook.track_at(15137.0).is_none());
+        assert!(audiobook.track_at(-1.0).is_none());
+    }
+
+    #[test]
+    fn test_audiobook_validation() {
+        let json = read_input();
+
+        let mut manifest = parse(&json);
+        manifest.metadata.conforms_to.clear();
+        assert_eq!(
+            Audiobook::try_from(manifest).unwrap_err(),
+            Error::MissingProfile
+        );
+
+        // Media types are compared without regard to case.
+        let mut manifest = parse(&json);
+        manifest.reading_order[0].mime = Some("AUDIO/MPEG".into());
+        assert!(Audiobook::try_from(manifest).is_ok());
+
+        let mut manifest = parse(&json);
+        manifest.metadata.duration = Some(16000.0);
+        assert_eq!(
+            Audiobook::try_from(manifest).unwrap_err(),
+            Error::DurationMismatch {
+                expected: 16000.0,
+                actual: 15137.0
+            }
+        );
+
+        let mut manifest = parse(&json);
+        manifest.metadata.duration = Some(15137.205);
+        assert!(Audiobook::try_from(manifest).is_ok());
+
+        let mut manifest = parse(&json);
+        manifest.reading_order[1].duration = None;
+        assert_eq!(
+            Audiobook::try_from(manifest).unwrap_err(),
+            Error::MissingDuration { index: 1 }
+        );
+
+        let mut manifest = parse(&json);
+        manifest.reading_order[2].mime = Some("text/html".into());
+        assert_eq!(
+            Audiobook::try_from(manifest).unwrap_err(),
+            Error::NotAudio {
+                index: 2,
+                mime: Some("text/html".into())
+            }
+        );
+    }
+}
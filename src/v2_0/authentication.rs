@@ -0,0 +1,541 @@
+//! Support for the Authentication for OPDS specification
+//!
+//! Catalogs that require the user to sign in respond with an [AuthenticationDocument], usually
+//! alongside a `401 Unauthorized` status, which describes the ways that a client can
+//! authenticate. The document is served as either
+//! [crate::mime::APPLICATION_OPDS_AUTHENTICATION_JSON] or
+//! [crate::mime::APPLICATION_VND_OPDS_AUTHENTICATION_JSON].
+//!
+//! This module also supports the extensions used by [Library Simplified] catalogs, such as
+//! [Inputs] and [Features].
+//!
+//! Documents are built up from their required fields, with `with_*` methods for the rest:
+//!
+//! ```
+//! use opds::v2_0::Link;
+//! use opds::v2_0::authentication::{
+//!     Announcement, Authentication, AuthenticationDocument, AuthenticationKind, Features, Input,
+//!     Inputs, Labels, PublicKey, WebColorScheme,
+//! };
+//! use opds::v2_0::metadata::Relation;
+//!
+//! let card = Input::new()
+//!     .with_keyboard("Number pad")
+//!     .with_maximum_length(14)
+//!     .with_barcode_format("Codabar");
+//! let basic = Authentication::new(AuthenticationKind::Basic)
+//!     .with_labels(Labels::new().with_login("Library card").with_password("PIN"))
+//!     .with_inputs(Inputs::new().with_login(card));
+//!
+//! let logo = Link::new("https://example.com/logo.png".into(), None).with_rel(Relation::Logo);
+//! let reservations = "https://librarysimplified.org/rel/policy/reservations";
+//! let colors = WebColorScheme::new()
+//!     .with_primary("#003366")
+//!     .with_secondary("#ffcc00");
+//!
+//! let document = AuthenticationDocument::new("https://example.com/auth", "Example Library")
+//!     .with_authentication(basic)
+//!     .with_link(logo)
+//!     .with_service_description("Books and audiobooks for card holders.")
+//!     .with_public_key(PublicKey::new("RSA", "-----BEGIN PUBLIC KEY-----..."))
+//!     .with_color_scheme("blue")
+//!     .with_web_color_scheme(colors)
+//!     .with_features(Features::new().with_enabled(reservations))
+//!     .with_announcement(Announcement::new("closure", "Closed on Monday."));
+//!
+//! let json = serde_json::to_string(&document).unwrap();
+//! let parsed: AuthenticationDocument<'_> = serde_json::from_str(&json).unwrap();
+//! assert_eq!(parsed, document);
+//! ```
+//!
+//! See [Authentication for OPDS 1.0] for more information.
+//!
+//! [Authentication for OPDS 1.0]: https://drafts.opds.io/authentication-for-opds-1.0.html
+//! [Library Simplified]: https://github.com/NYPL-Simplified/Simplified/wiki/Authentication-For-OPDS-Extensions
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::Link;
+use super::extensions::Extensions;
+use super::owned::into_owned;
+
+/// The kind of authentication flow used by an [Authentication] object.
+///
+/// See [Section 3: Authentication Flows] for more information.
+///
+/// [Section 3: Authentication Flows]: https://drafts.opds.io/authentication-for-opds-1.0.html#3-authentication-flows
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub enum AuthenticationKind {
+    /// HTTP Basic Authentication, using the login and password that the user provides.
+    #[serde(rename = "http://opds-spec.org/auth/basic")]
+    Basic,
+
+    /// The OAuth 2.0 Implicit Grant, where the user signs in through the `authenticate` link.
+    #[serde(rename = "http://opds-spec.org/auth/oauth/implicit")]
+    OAuthImplicit,
+
+    /// The OAuth 2.0 Resource Owner Password Credentials Grant, where the client exchanges
+    /// the user's login and password for a token at the `authenticate` link.
+    #[serde(rename = "http://opds-spec.org/auth/oauth/password")]
+    OAuthPassword,
+
+    /// SAML 2.0, as used by Library Simplified.
+    #[serde(rename = "http://librarysimplified.org/authtype/SAML-2.0")]
+    Saml,
+
+    /// OAuth through an intermediary, as used by Library Simplified.
+    #[serde(rename = "http://librarysimplified.org/authtype/OAuth-with-intermediary")]
+    OAuthIntermediary,
+
+    /// Anonymous access, as used by Library Simplified.
+    #[serde(rename = "http://librarysimplified.org/rel/auth/anonymous")]
+    Anonymous,
+
+    #[serde(untagged)]
+    Custom(String),
+}
+
+/// Labels for the fields that a client shows the user when asking for their credentials.
+///
+/// See [Section 3.1: Basic Authentication] for more information.
+///
+/// [Section 3.1: Basic Authentication]: https://drafts.opds.io/authentication-for-opds-1.0.html#31-basic-authentication
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Labels<'a> {
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub login: Option<Cow<'a, str>>,
+
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub password: Option<Cow<'a, str>>,
+}
+
+impl<'a> Labels<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_login(mut self, login: impl Into<Cow<'a, str>>) -> Self {
+        self.login = Some(login.into());
+        self
+    }
+
+    pub fn with_password(mut self, password: impl Into<Cow<'a, str>>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+}
+
+/// Hints for how a client should show a single credential field.
+///
+/// This is a Library Simplified extension.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Input<'a> {
+    /// The kind of keyboard to show, such as `Default`, `Email address`, `Number pad` or
+    /// `No input`.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub keyboard: Option<Cow<'a, str>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum_length: Option<usize>,
+
+    /// The barcode format that can be scanned to fill in the field, such as `Codabar`.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub barcode_format: Option<Cow<'a, str>>,
+}
+
+impl<'a> Input<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_keyboard(mut self, keyboard: impl Into<Cow<'a, str>>) -> Self {
+        self.keyboard = Some(keyboard.into());
+        self
+    }
+
+    pub fn with_maximum_length(mut self, maximum_length: usize) -> Self {
+        self.maximum_length = Some(maximum_length);
+        self
+    }
+
+    pub fn with_barcode_format(mut self, barcode_format: impl Into<Cow<'a, str>>) -> Self {
+        self.barcode_format = Some(barcode_format.into());
+        self
+    }
+}
+
+/// Hints for the credential fields of an authentication flow.
+///
+/// This is a Library Simplified extension.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Inputs<'a> {
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub login: Option<Input<'a>>,
+
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub password: Option<Input<'a>>,
+}
+
+impl<'a> Inputs<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_login(mut self, login: Input<'a>) -> Self {
+        self.login = Some(login);
+        self
+    }
+
+    pub fn with_password(mut self, password: Input<'a>) -> Self {
+        self.password = Some(password);
+        self
+    }
+}
+
+/// A single way that a client can authenticate with a catalog.
+///
+/// See [Section 2.2: Authentication Object] for more information.
+///
+/// [Section 2.2: Authentication Object]: https://drafts.opds.io/authentication-for-opds-1.0.html#22-authentication-object
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Authentication<'a> {
+    #[serde(rename = "type")]
+    pub kind: AuthenticationKind,
+
+    /// A description of this flow to show the user, when several are available.
+    ///
+    /// This is a Library Simplified extension.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub description: Option<Cow<'a, str>>,
+
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Labels<'a>>,
+
+    /// This is a Library Simplified extension.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Inputs<'a>>,
+
+    /// Links used by this flow, such as those with the [Relation::Authenticate] and
+    /// [Relation::Refresh] relations.
+    ///
+    /// [Relation::Authenticate]: super::metadata::Relation::Authenticate
+    /// [Relation::Refresh]: super::metadata::Relation::Refresh
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link<'a>>,
+
+    /// Settings for flows defined by other specifications or vendors.
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+impl<'a> Authentication<'a> {
+    pub fn new(kind: AuthenticationKind) -> Self {
+        Self {
+            kind,
+            description: None,
+            labels: None,
+            inputs: None,
+            links: vec![],
+            extensions: Extensions::new(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_labels(mut self, labels: Labels<'a>) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    pub fn with_inputs(mut self, inputs: Inputs<'a>) -> Self {
+        self.inputs = Some(inputs);
+        self
+    }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
+}
+
+/// A public key used to encrypt information sent to the catalog.
+///
+/// This is a Library Simplified extension.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct PublicKey<'a> {
+    /// The kind of key, such as `RSA`.
+    #[serde(borrow, rename = "type")]
+    pub kind: Cow<'a, str>,
+
+    #[serde(borrow)]
+    pub value: Cow<'a, str>,
+}
+
+impl<'a> PublicKey<'a> {
+    pub fn new(kind: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            kind: kind.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// Colors that a web client should use when showing a catalog.
+///
+/// This is a Library Simplified extension.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct WebColorScheme<'a> {
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub primary: Option<Cow<'a, str>>,
+
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub secondary: Option<Cow<'a, str>>,
+
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub background: Option<Cow<'a, str>>,
+
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<Cow<'a, str>>,
+}
+
+impl<'a> WebColorScheme<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_primary(mut self, primary: impl Into<Cow<'a, str>>) -> Self {
+        self.primary = Some(primary.into());
+        self
+    }
+
+    pub fn with_secondary(mut self, secondary: impl Into<Cow<'a, str>>) -> Self {
+        self.secondary = Some(secondary.into());
+        self
+    }
+
+    pub fn with_background(mut self, background: impl Into<Cow<'a, str>>) -> Self {
+        self.background = Some(background.into());
+        self
+    }
+
+    pub fn with_foreground(mut self, foreground: impl Into<Cow<'a, str>>) -> Self {
+        self.foreground = Some(foreground.into());
+        self
+    }
+}
+
+/// Optional client features that a catalog has turned on or off.
+///
+/// This is a Library Simplified extension.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Features<'a> {
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub enabled: Vec<Cow<'a, str>>,
+
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<Cow<'a, str>>,
+}
+
+impl<'a> Features<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enabled(mut self, feature: impl Into<Cow<'a, str>>) -> Self {
+        self.enabled.push(feature.into());
+        self
+    }
+
+    pub fn with_disabled(mut self, feature: impl Into<Cow<'a, str>>) -> Self {
+        self.disabled.push(feature.into());
+        self
+    }
+}
+
+/// A message from the catalog to show the user.
+///
+/// This is a Library Simplified extension.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Announcement<'a> {
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+
+    #[serde(borrow)]
+    pub content: Cow<'a, str>,
+}
+
+impl<'a> Announcement<'a> {
+    pub fn new(id: impl Into<Cow<'a, str>>, content: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            id: id.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// A document describing the ways that a client can authenticate with a catalog.
+///
+/// See [Section 2: Authentication Document] for more information.
+///
+/// [Section 2: Authentication Document]: https://drafts.opds.io/authentication-for-opds-1.0.html#2-authentication-document
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct AuthenticationDocument<'a> {
+    /// A unique identifier for the catalog that this document applies to.
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+
+    /// The title of the catalog, to show the user when asking for their credentials.
+    #[serde(borrow)]
+    pub title: Cow<'a, str>,
+
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub description: Option<Cow<'a, str>>,
+
+    /// The supported authentication flows, in order of preference.
+    #[serde(borrow)]
+    pub authentication: Vec<Authentication<'a>>,
+
+    /// Links related to the catalog, such as those with the [Relation::Logo],
+    /// [Relation::Register] and [Relation::Help] relations.
+    ///
+    /// [Relation::Logo]: super::metadata::Relation::Logo
+    /// [Relation::Register]: super::metadata::Relation::Register
+    /// [Relation::Help]: super::metadata::Relation::Help
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link<'a>>,
+
+    /// This is a Library Simplified extension.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub service_description: Option<Cow<'a, str>>,
+
+    /// This is a Library Simplified extension.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<PublicKey<'a>>,
+
+    /// The name of the color scheme that a mobile client should use.
+    ///
+    /// This is a Library Simplified extension.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub color_scheme: Option<Cow<'a, str>>,
+
+    /// This is a Library Simplified extension.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub web_color_scheme: Option<WebColorScheme<'a>>,
+
+    /// This is a Library Simplified extension.
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
+    pub features: Option<Features<'a>>,
+
+    /// This is a Library Simplified extension.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub announcements: Vec<Announcement<'a>>,
+
+    /// Fields used by other catalog software, beyond the Library Simplified ones above.
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+impl<'a> AuthenticationDocument<'a> {
+    pub fn new(id: impl Into<Cow<'a, str>>, title: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            description: None,
+            authentication: vec![],
+            links: vec![],
+            service_description: None,
+            public_key: None,
+            color_scheme: None,
+            web_color_scheme: None,
+            features: None,
+            announcements: vec![],
+            extensions: Extensions::new(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_authentication(mut self, authentication: Authentication<'a>) -> Self {
+        self.authentication.push(authentication);
+        self
+    }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    pub fn with_service_description(
+        mut self,
+        service_description: impl Into<Cow<'a, str>>,
+    ) -> Self {
+        self.service_description = Some(service_description.into());
+        self
+    }
+
+    pub fn with_public_key(mut self, public_key: PublicKey<'a>) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    pub fn with_color_scheme(mut self, color_scheme: impl Into<Cow<'a, str>>) -> Self {
+        self.color_scheme = Some(color_scheme.into());
+        self
+    }
+
+    pub fn with_web_color_scheme(mut self, web_color_scheme: WebColorScheme<'a>) -> Self {
+        self.web_color_scheme = Some(web_color_scheme);
+        self
+    }
+
+    pub fn with_features(mut self, features: Features<'a>) -> Self {
+        self.features = Some(features);
+        self
+    }
+
+    pub fn with_announcement(mut self, announcement: Announcement<'a>) -> Self {
+        self.announcements.push(announcement);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
+}
+
+into_owned!(Labels { login, password; });
+into_owned!(Input { keyboard, barcode_format; maximum_length });
+into_owned!(Inputs { login, password; });
+into_owned!(Authentication { description, labels, inputs, links; kind, extensions });
+into_owned!(PublicKey { kind, value; });
+into_owned!(WebColorScheme { primary, secondary, background, foreground; });
+into_owned!(Features { enabled, disabled; });
+into_owned!(Announcement { id, content; });
+into_owned!(AuthenticationDocument {
+    id, title, description, authentication, links, service_description, public_key,
+    color_scheme, web_color_scheme, features, announcements; extensions
+});
@@ -0,0 +1,206 @@
+//! Translated labels for navigation links, facets and groups
+//!
+//! A [Catalog] maps message keys, like `series` or `newest`, to their translations, and
+//! produces a [StringWithAlternates::Variants] for each key, which can then be shown in the
+//! reader's language using [StringWithAlternates::resolve].
+//!
+//! Catalogs are loaded from JSON objects that map each key to a map from BCP 47 language tags
+//! to messages:
+//!
+//! ```json
+//! {
+//!   "series": { "en": "Series", "fr": "Séries" },
+//!   "staff-picks": { "en": "Staff Picks", "de": "Empfehlungen" }
+//! }
+//! ```
+//!
+//! The [Catalog::builtin] catalog provides translations of the common OPDS navigation and
+//! facet labels into Arabic, Chinese, Dutch, English, French, German, Italian, Japanese,
+//! Korean, Polish, Portuguese, Russian, Spanish and Swedish. Servers can add their own keys
+//! and languages, or replace the built-in messages, using [Catalog::merge]:
+//!
+//! ```
+//! use opds::v2_0::catalog::Catalog;
+//!
+//! let custom = r#"{"staff-picks": {"en": "Staff Picks", "de": "Empfehlungen"}}"#;
+//! let mut catalog = Catalog::builtin().clone();
+//! catalog.merge(Catalog::from_json(custom).unwrap());
+//!
+//! assert!(catalog.get("series").is_some());
+//! assert!(catalog.get("staff-picks").is_some());
+//! ```
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use langtag::{LangTag, LangTagBuf};
+
+use super::*;
+
+const BUILTIN: &str = include_str!("../../messages/catalog.json");
+
+/// A set of messages, each translated into one or more languages.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Catalog {
+    messages: BTreeMap<String, BTreeMap<LangTagBuf, String>>,
+}
+
+impl Catalog {
+    /// Create an empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The catalog of labels shipped with this crate.
+    ///
+    /// This includes the labels of the [StringWithAlternates] constants under the keys
+    /// `authors`, `books-alphabetical`, `books-recently-added`, `categories`, `file-formats`,
+    /// `languages` and `publishers`, along with `accessibility`, `all`, `formats`, `newest`,
+    /// `popular`, `search`, `series` and `subjects`.
+    pub fn builtin() -> &'static Self {
+        static BUILTIN_CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+        BUILTIN_CATALOG.get_or_init(|| Self::from_json(BUILTIN).expect("built-in catalog is valid"))
+    }
+
+    /// Load a catalog from a JSON object mapping each key to its translations.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Add or replace the translation of `key` into `language`.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        language: &LangTag,
+        message: impl Into<String>,
+    ) {
+        self.messages
+            .entry(key.into())
+            .or_default()
+            .insert(language.to_owned(), message.into());
+    }
+
+    /// Add the keys and translations from `other`, replacing any translations of the same key
+    /// into the same language.
+    pub fn merge(&mut self, other: Catalog) {
+        for (key, translations) in other.messages {
+            self.messages.entry(key).or_default().extend(translations);
+        }
+    }
+
+    /// The translations of `key`, if it is in the catalog.
+    pub fn get(&self, key: &str) -> Option<StringWithAlternates<'static>> {
+        let translations = self.messages.get(key)?;
+        let choices = translations
+            .iter()
+            .map(|(tag, s)| (Cow::Owned(tag.clone()), Cow::Owned(s.clone())));
+
+        Some(StringWithAlternates::Variants(TaggedStrings::new(choices)))
+    }
+
+    /// The translation of `key` into exactly the given language.
+    pub fn translation(&self, key: &str, language: &LangTag) -> Option<&str> {
+        self.messages.get(key)?.get(language).map(String::as_str)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.messages.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::v2_0::language::LanguageRange;
+
+    #[test]
+    fn test_builtin() {
+        let catalog = Catalog::builtin();
+        let languages = [
+            "ar", "de", "en", "es", "fr", "it", "ja", "ko", "nl", "pl", "pt", "ru", "sv", "zh",
+        ];
+
+        for key in catalog.keys() {
+            let Some(StringWithAlternates::Variants(variants)) = catalog.get(key) else {
+                panic!("expected variants for {key}");
+            };
+            let tags: Vec<_> = variants.iter().map(|(tag, _)| tag.as_str()).collect();
+            assert_eq!(tags, languages, "languages for {key}");
+        }
+
+        let constants = [
+            ("authors", StringWithAlternates::AUTHORS),
+            (
+                "books-alphabetical",
+                StringWithAlternates::BOOKS_ALPHABETICAL,
+            ),
+            (
+                "books-recently-added",
+                StringWithAlternates::BOOKS_RECENTLY_ADDED,
+            ),
+            ("categories", StringWithAlternates::CATEGORIES),
+            ("file-formats", StringWithAlternates::FILE_FORMATS),
+            ("languages", StringWithAlternates::LANGUAGES),
+            ("publishers", StringWithAlternates::PUBLISHERS),
+        ];
+
+        for (key, constant) in constants {
+            let StringWithAlternates::Variants(variants) = constant else {
+                panic!("expected variants for {key}");
+            };
+
+            for (tag, s) in variants.iter() {
+                assert_eq!(catalog.translation(key, tag), Some(s), "{key} in {tag}");
+            }
+        }
+
+        let series = catalog.get("series").unwrap();
+        let ranges = LanguageRange::from_accept_language("sv-SE, en;q=0.5");
+        assert_eq!(series.resolve(&ranges), "Serier");
+    }
+
+    #[test]
+    fn test_custom() {
+        let json = r#"{
+            "series": {"fr": "Collections", "eo": "Serioj"},
+            "staff-picks": {"en": "Staff Picks", "de": "Empfehlungen"}
+        }"#;
+
+        let mut catalog = Catalog::builtin().clone();
+        catalog.merge(Catalog::from_json(json).unwrap());
+        catalog.insert("staff-picks", langtag::langtag!("fr"), "Coups de cœur");
+
+        let fr = langtag::langtag!("fr");
+        assert_eq!(catalog.translation("series", fr), Some("Collections"));
+        assert_eq!(
+            catalog.translation("series", langtag::langtag!("eo")),
+            Some("Serioj")
+        );
+        assert_eq!(
+            catalog.translation("series", langtag::langtag!("de")),
+            Some("Reihen")
+        );
+        assert_eq!(
+            catalog.translation("staff-picks", fr),
+            Some("Coups de cœur")
+        );
+        assert!(catalog.contains_key("staff-picks"));
+        assert!(catalog.get("missing").is_none());
+
+        let picks = catalog.get("staff-picks").unwrap();
+        assert_eq!(
+            serde_json::to_value(&picks).unwrap(),
+            serde_json::json!({"de": "Empfehlungen", "en": "Staff Picks", "fr": "Coups de cœur"})
+        );
+
+        assert!(Catalog::from_json(r#"{"series": {"not a tag!": "x"}}"#).is_err());
+        assert!(Catalog::from_json(r#"{"series": "Series"}"#).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Support for the Readium Divina profile
+//!
+//! Divina is used for visual narratives such as comics and manga. A Divina publication is a
+//! [Manifest] that declares that it follows the profile in [PublicationMetadata::conforms_to],
+//! and whose reading order is made up entirely of images with known dimensions, which can be
+//! shown on their own or paired into spreads, and which may also provide [guided] navigation
+//! through the panels of each page.
+//!
+//! See the [Divina Profile] for more information.
+//!
+//! [guided]: Manifest::guided
+//! [Divina Profile]: https://readium.org/webpub-manifest/profiles/divina.html
+use std::fmt;
+
+use super::manifest::Manifest;
+use super::*;
+use crate::mime::MediaType;
+
+/// The URL used in `conformsTo` to indicate that a manifest follows the Divina profile.
+pub const PROFILE: &str = "https://readium.org/webpub-manifest/profiles/divina";
+
+/// A reason why a [Manifest] does not follow the Divina profile.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The manifest's `conformsTo` does not include [PROFILE].
+    MissingProfile,
+
+    /// The reading order does not contain any images.
+    EmptyReadingOrder,
+
+    /// An item in the reading order is not an image.
+    NotImage { index: usize, mime: Option<String> },
+
+    /// An item in the reading order is missing its width or height.
+    MissingDimensions { index: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingProfile => write!(f, "the manifest does not conform to {PROFILE}"),
+            Self::EmptyReadingOrder => write!(f, "the reading order is empty"),
+            Self::NotImage { index, mime: None } => {
+                write!(f, "page {index} has no media type")
+            }
+            Self::NotImage {
+                index,
+                mime: Some(mime),
+            } => {
+                write!(f, "page {index} has non-image media type {mime:?}")
+            }
+            Self::MissingDimensions { index } => {
+                write!(f, "page {index} is missing its width or height")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// How one or two pages from the reading order should be shown together.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Spread<'m, 'a> {
+    /// A page that is shown on its own.
+    Single(&'m Link<'a>),
+
+    /// Two facing pages, given by where they appear on screen rather than in reading order.
+    Double {
+        left: &'m Link<'a>,
+        right: &'m Link<'a>,
+    },
+}
+
+/// A [Manifest] that follows the Divina profile.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Divina<'a> {
+    manifest: Manifest<'a>,
+}
+
+impl<'a> Divina<'a> {
+    /// The underlying manifest.
+    pub fn manifest(&self) -> &Manifest<'a> {
+        &self.manifest
+    }
+
+    pub fn into_manifest(self) -> Manifest<'a> {
+        self.manifest
+    }
+
+    /// The direction in which the pages should be read, defaulting to left-to-right.
+    pub fn reading_progression(&self) -> ReadingProgression {
+        self.manifest
+            .metadata
+            .reading_progression
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Pair the pages of the reading order into spreads.
+    ///
+    /// Two consecutive pages form a spread when the first one is marked with the
+    /// [PageDisplay] for the side that is read first, and the second one is marked for the
+    /// other side. With a left-to-right reading progression, that is a [PageDisplay::Left]
+    /// page followed by a [PageDisplay::Right] one, and the reverse when reading right-to-left.
+    /// Every other page is shown on its own.
+    pub fn spreads(&self) -> Vec<Spread<'_, 'a>> {
+        let (first, second) = match self.reading_progression() {
+            ReadingProgression::LeftToRight => (PageDisplay::Left, PageDisplay::Right),
+            ReadingProgression::RightToLeft => (PageDisplay::Right, PageDisplay::Left),
+        };
+
+        let is_page =
+            |link: &Link<'_>, page: &PageDisplay| link.properties.page.as_ref() == Some(page);
+
+        let mut spreads = vec![];
+        let mut pages = self.manifest.reading_order.iter().peekable();
+
+        while let Some(page) = pages.next() {
+            let Some(next) = pages.next_if(|next| is_page(page, &first) && is_page(next, &second))
+            else {
+                spreads.push(Spread::Single(page));
+                continue;
+            };
+
+            let spread = match first {
+                PageDisplay::Left => Spread::Double {
+                    left: page,
+                    right: next,
+                },
+                _ => Spread::Double {
+                    left: next,
+                    right: page,
+                },
+            };
+
+            spreads.push(spread);
+        }
+
+        spreads
+    }
+}
+
+impl<'a> TryFrom<Manifest<'a>> for Divina<'a> {
+    type Error = Error;
+
+    fn try_from(manifest: Manifest<'a>) -> Result<Self, Self::Error> {
+        let conforms_to = &manifest.metadata.conforms_to;
+
+        if !conforms_to.iter().any(|url| url.as_str() == PROFILE) {
+            return Err(Error::MissingProfile);
+        }
+
+        if manifest.reading_order.is_empty() {
+            return Err(Error::EmptyReadingOrder);
+        }
+
+        let is_image = |mime: &str| {
+            MediaType::parse(mime).is_ok_and(|mime| mime.ty().eq_ignore_ascii_case("image"))
+        };
+
+        for (index, link) in manifest.reading_order.iter().enumerate() {
+            let mime = link.mime.as_deref();
+
+            if !mime.is_some_and(is_image) {
+                let mime = mime.map(str::to_string);
+                return Err(Error::NotImage { index, mime });
+            }
+
+            if link.width.is_none() || link.height.is_none() {
+                return Err(Error::MissingDimensions { index });
+            }
+        }
+
+        Ok(Self { manifest })
+    }
+}
+
+impl<'a> From<Divina<'a>> for Manifest<'a> {
+    fn from(divina: Divina<'a>) -> Self {
+        divina.manifest
+    }
+}
+
+into_owned!(Divina { manifest; });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn read_input() -> String {
+        std::fs::read_to_string(format!("{CRATE_DIR}/tests/test-manifest-divina.in.json"))
+            .expect("valid file input")
+    }
+
+    fn parse(json: &str) -> Manifest<'_> {
+        serde_json::from_str(json).expect("can parse manifest")
+    }
+
+    fn href<'m>(link: &'m Link<'_>) -> &'m str {
+        link.href.as_deref().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_divina_spreads() {
+        let json = read_input();
+        let divina = Divina::try_from(parse(&json)).expect("valid Divina manifest");
+
+        assert_eq!(
+            divina.reading_progression(),
+            ReadingProgression::RightToLeft
+        );
+        assert_eq!(divina.manifest().guided.len(), 2);
+
+        let spreads = divina.spreads();
+        assert_eq!(spreads.len(), 4);
+        assert!(matches!(spreads[0], Spread::Single(page) if href(page).ends_with("cover.jpg")));
+        assert!(matches!(
+            spreads[1],
+            Spread::Double { left, right }
+                if href(left).ends_with("page2.jpg") && href(right).ends_with("page1.jpg")
+        ));
+        assert!(matches!(spreads[2], Spread::Single(page) if href(page).ends_with("page3.jpg")));
+        assert!(matches!(spreads[3], Spread::Single(page) if href(page).ends_with("page4.jpg")));
+
+        // When reading left-to-right, the pages pair up differently.
+        let mut manifest = divina.into_manifest();
+        manifest.metadata.reading_progression = Some(ReadingProgression::LeftToRight);
+        let divina = Divina::try_from(manifest).expect("valid Divina manifest");
+
+        let spreads = divina.spreads();
+        assert_eq!(spreads.len(), 4);
+        assert!(matches!(spreads[1], Spread::Single(page) if href(page).ends_with("page1.jpg")));
+        assert!(matches!(
+            spreads[2],
+            Spread::Double { left, right }
+                if href(left).ends_with("page2.jpg") && href(right).ends_with("page3.jpg")
+        ));
+    }
+
+    #[test]
+    fn test_divina_validation() {
+        let json = read_input();
+
+        let mut manifest = parse(&json);
+        manifest.metadata.conforms_to.clear();
+        assert_eq!(
+            Divina::try_from(manifest).unwrap_err(),
+            Error::MissingProfile
+        );
+
+        // Media types are compared without regard to case.
+        let mut manifest = parse(&json);
+        manifest.reading_order[0].mime = Some("Image/JPEG".into());
+        assert!(Divina::try_from(manifest).is_ok());
+
+        let mut manifest = parse(&json);
+        manifest.reading_order[3].height = None;
+        assert_eq!(
+            Divina::try_from(manifest).unwrap_err(),
+            Error::MissingDimensions { index: 3 }
+        );
+
+        let mut manifest = parse(&json);
+        manifest.reading_order[0].mime = None;
+        assert_eq!(
+            Divina::try_from(manifest).unwrap_err(),
+            Error::NotImage {
+                index: 0,
+                mime: None
+            }
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Support for keeping fields that this crate does not model
+//!
+//! Catalogs often include properties beyond the ones defined by the specifications, such as
+//! vendor-specific terms like `http://palaceproject.io/terms/timeTracking`, or link
+//! properties defined by other specifications. Rather than dropping these, they are collected
+//! into an [Extensions] map on the object where they appear, and written back out after the
+//! known fields when the object is serialized.
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+
+/// The name of the newtype that the keys of [Extensions] entries are wrapped in when they are
+/// serialized.
+///
+/// Serializers for formats like JSON write newtypes out as the value they wrap, but this lets
+/// a serializer that only wants the modeled fields recognize and leave out the extensions.
+pub(crate) const EXTENSION_KEY: &str = "$opds::private::ExtensionKey";
+
+struct ExtensionKey<'k>(&'k str);
+
+impl Serialize for ExtensionKey<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(EXTENSION_KEY, self.0)
+    }
+}
+
+/// Fields that are not modeled by this crate, stored as raw JSON values.
+///
+/// Entries are kept in the order they appeared in the source document, as are the keys of
+/// any objects nested within their values, since this crate enables the `preserve_order`
+/// feature of [serde_json]. Two maps with the same entries in different orders are equal.
+#[derive(Clone, Debug, Default)]
+pub struct Extensions {
+    entries: Vec<(String, Value)>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Set the value for `key`, returning the previous value if there was one.
+    ///
+    /// Replacing an existing value keeps its position, while new keys are added at the end.
+    pub fn insert(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        let key = key.into();
+
+        match self.get_mut(&key) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let i = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(i).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(k, _)| k.as_str())
+    }
+}
+
+impl PartialEq for Extensions {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.entries.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl Eq for Extensions {}
+
+impl Hash for Extensions {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.hash(state);
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for Extensions {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        let mut extensions = Self::new();
+
+        for (k, v) in iter {
+            extensions.insert(k, v);
+        }
+
+        extensions
+    }
+}
+
+impl IntoIterator for Extensions {
+    type Item = (String, Value);
+    type IntoIter = std::vec::IntoIter<(String, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl Serialize for Extensions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+
+        for (k, v) in self.entries.iter() {
+            map.serialize_entry(&ExtensionKey(k), v)?;
+        }
+
+        map.end()
+    }
+}
+
+struct ExtensionsVisitor;
+
+impl<'de> Visitor<'de> for ExtensionsVisitor {
+    type Value = Extensions;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of extension properties")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut extensions = Extensions::new();
+
+        while let Some((k, v)) = access.next_entry::<String, Value>()? {
+            extensions.insert(k, v);
+        }
+
+        Ok(extensions)
+    }
+}
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ExtensionsVisitor)
+    }
+}
@@ -0,0 +1,700 @@
+//! Support for recognizing standard publication identifiers
+//!
+//! Identifiers in feeds are plain URIs, such as `urn:isbn:9780000000001` or
+//! `https://doi.org/10.1000/182`, and the same book is often identified differently by
+//! different catalogs. A [ParsedIdentifier] recognizes the common identifier schemes, checks
+//! them, and puts them into a single normal form, so that identifiers from different sources
+//! can be compared:
+//!
+//! - ISBNs, from `urn:isbn:`, are checked and always converted to ISBN-13.
+//! - ISSNs, from `urn:issn:`, are checked.
+//! - DOIs, from `doi:`, `https://doi.org/` or `https://dx.doi.org/`, compare without case.
+//! - UUIDs, from `urn:uuid:`, compare without case or hyphens.
+//!
+//! Any other URN or URL is kept as it is.
+//!
+//! [AltIdentifier]s are recognized using their `scheme`, which can be the matching `urn:`
+//! prefix (like `urn:isbn`) or the [Library of Congress identifier scheme], like
+//! `http://id.loc.gov/vocabulary/identifiers/isbn`.
+//!
+//! [Library of Congress identifier scheme]: https://id.loc.gov/vocabulary/identifiers.html
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use super::*;
+
+/// An error encountered while parsing an identifier.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The value is not valid for the scheme that it uses, such as an ISBN with the wrong
+    /// check digit.
+    Invalid { scheme: &'static str, value: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid { scheme, value } => write!(f, "invalid {scheme} {value:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn invalid(scheme: &'static str, value: &str) -> Error {
+    Error::Invalid {
+        scheme,
+        value: value.to_string(),
+    }
+}
+
+/// Remove a case-insensitive prefix from a string.
+fn strip_prefix_ignore_case<'s>(s: &'s str, prefix: &str) -> Option<&'s str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &s[prefix.len()..])
+}
+
+/// Collect the digits of a value, ignoring hyphens and spaces, and allowing a final `X`.
+fn digits<const N: usize>(scheme: &'static str, value: &str) -> Result<[u8; N], Error> {
+    let mut digits = [0; N];
+    let mut count = 0;
+
+    for c in value.chars().filter(|c| !matches!(c, '-' | ' ')) {
+        let digit = match c {
+            '0'..='9' => c as u8 - b'0',
+            'X' | 'x' if count == N - 1 => 10,
+            _ => return Err(invalid(scheme, value)),
+        };
+
+        *digits
+            .get_mut(count)
+            .ok_or_else(|| invalid(scheme, value))? = digit;
+        count += 1;
+    }
+
+    if count == N {
+        Ok(digits)
+    } else {
+        Err(invalid(scheme, value))
+    }
+}
+
+/// The check digit of an ISBN-13 from its first 12 digits.
+fn isbn13_check(digits: &[u8]) -> u8 {
+    let sum: u32 = digits[..12]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| u32::from(*d) * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// The weighted sum used to check ISBN-10s and ISSNs, which are valid when it divides by 11.
+fn mod11_sum(digits: &[u8]) -> u32 {
+    let n = digits.len() as u32;
+
+    digits
+        .iter()
+        .enumerate()
+        .map(|(i, d)| u32::from(*d) * (n - i as u32))
+        .sum()
+}
+
+/// An International Standard Book Number, stored as an ISBN-13.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Isbn {
+    digits: [u8; 13],
+}
+
+impl Isbn {
+    /// The ISBN-10 form of this ISBN, for those that begin with `978`.
+    pub fn to_isbn10(&self) -> Option<String> {
+        if self.digits[..3] != [9, 7, 8] {
+            return None;
+        }
+
+        let digits = &self.digits[3..12];
+        let sum: u32 = digits
+            .iter()
+            .enumerate()
+            .map(|(i, d)| u32::from(*d) * (10 - i as u32))
+            .sum();
+        let check = (11 - sum % 11) % 11;
+        let check = if check == 10 {
+            'X'
+        } else {
+            char::from(b'0' + check as u8)
+        };
+
+        let mut isbn: String = digits.iter().map(|d| char::from(b'0' + d)).collect();
+        isbn.push(check);
+        Some(isbn)
+    }
+}
+
+impl FromStr for Isbn {
+    type Err = Error;
+
+    /// Parse an ISBN-10 or ISBN-13, with or without hyphens.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = s.chars().filter(|c| !matches!(c, '-' | ' ')).count();
+
+        let digits = if len == 10 {
+            let isbn10: [u8; 10] = digits("ISBN", s)?;
+
+            if mod11_sum(&isbn10) % 11 != 0 {
+                return Err(invalid("ISBN", s));
+            }
+
+            let mut digits = [9, 7, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+            digits[3..12].copy_from_slice(&isbn10[..9]);
+            digits[12] = isbn13_check(&digits);
+            digits
+        } else {
+            let digits: [u8; 13] = digits("ISBN", s)?;
+
+            if !matches!(digits[..3], [9, 7, 8] | [9, 7, 9]) || isbn13_check(&digits) != digits[12]
+            {
+                return Err(invalid("ISBN", s));
+            }
+
+            digits
+        };
+
+        Ok(Self { digits })
+    }
+}
+
+impl fmt::Display for Isbn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.digits.iter().try_for_each(|d| write!(f, "{d}"))
+    }
+}
+
+/// An International Standard Serial Number.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Issn {
+    digits: [u8; 8],
+}
+
+impl FromStr for Issn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits: [u8; 8] = digits("ISSN", s)?;
+
+        if mod11_sum(&digits) % 11 != 0 {
+            return Err(invalid("ISSN", s));
+        }
+
+        Ok(Self { digits })
+    }
+}
+
+impl fmt::Display for Issn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, d) in self.digits.iter().enumerate() {
+            if i == 4 {
+                write!(f, "-")?;
+            }
+
+            match d {
+                10 => write!(f, "X")?,
+                d => write!(f, "{d}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The ways that a DOI can be written as a URI.
+const DOI_PREFIXES: &[&str] = &[
+    "doi:",
+    "https://doi.org/",
+    "http://doi.org/",
+    "https://dx.doi.org/",
+    "http://dx.doi.org/",
+];
+
+fn strip_doi_prefix(s: &str) -> Option<&str> {
+    DOI_PREFIXES
+        .iter()
+        .find_map(|prefix| strip_prefix_ignore_case(s, prefix))
+}
+
+/// Decode the `%XX` escapes in a URL path, leaving any invalid ones as they are.
+fn percent_decode(s: &str) -> Cow<'_, str> {
+    if !s.contains('%') {
+        return Cow::Borrowed(s);
+    }
+
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    match String::from_utf8(decoded) {
+        Ok(decoded) => Cow::Owned(decoded),
+        Err(_) => Cow::Borrowed(s),
+    }
+}
+
+/// A Digital Object Identifier, like `10.1000/182`.
+///
+/// DOIs are compared without regard to case, so they are stored in lowercase.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Doi {
+    doi: String,
+}
+
+impl Doi {
+    pub fn as_str(&self) -> &str {
+        &self.doi
+    }
+
+    /// The URL that resolves this DOI.
+    ///
+    /// The suffix is percent-encoded as a single path segment, so that any `/`, `..` or `?`
+    /// in it stays part of the DOI.
+    pub fn to_url(&self) -> url::Url {
+        let mut url = url::Url::parse("https://doi.org/").expect("valid base URL");
+        let (prefix, suffix) = self.doi.split_once('/').expect("DOI has a suffix");
+
+        url.path_segments_mut()
+            .expect("base URL has a path")
+            .clear()
+            .push(prefix)
+            .push(suffix);
+
+        url
+    }
+}
+
+impl FromStr for Doi {
+    type Err = Error;
+
+    /// Parse a DOI, either on its own or as a `doi:` URI or `doi.org` URL.
+    ///
+    /// DOIs taken from a URL are percent-decoded, so that parsing the result of
+    /// [Doi::to_url] gives back the same DOI.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let doi = match strip_doi_prefix(s) {
+            Some(doi) if s.contains("://") => percent_decode(doi),
+            Some(doi) => Cow::Borrowed(doi),
+            None => Cow::Borrowed(s),
+        };
+
+        match doi.split_once('/') {
+            Some((prefix, suffix))
+                if prefix.len() > 3
+                    && prefix.starts_with("10.")
+                    && !matches!(suffix, "" | "." | "..") =>
+            {
+                Ok(Self {
+                    doi: doi.to_lowercase(),
+                })
+            }
+            _ => Err(invalid("DOI", s)),
+        }
+    }
+}
+
+impl fmt::Display for Doi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.doi)
+    }
+}
+
+/// A Universally Unique Identifier.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Uuid {
+    bytes: [u8; 16],
+}
+
+impl Uuid {
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.bytes
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = Error;
+
+    /// Parse a UUID, either in its usual hyphenated form or as 32 hex digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hyphenated = s.len() == 36
+            && s.char_indices()
+                .all(|(i, c)| matches!(i, 8 | 13 | 18 | 23) == (c == '-'));
+
+        if !hyphenated && (s.len() != 32 || s.contains('-')) {
+            return Err(invalid("UUID", s));
+        }
+
+        let hex: Vec<u8> = s
+            .chars()
+            .filter(|c| *c != '-')
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()
+            .filter(|hex: &Vec<u8>| hex.len() == 32)
+            .ok_or_else(|| invalid("UUID", s))?;
+
+        let mut bytes = [0; 16];
+
+        for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+            *byte = (pair[0] << 4) | pair[1];
+        }
+
+        Ok(Self { bytes })
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                write!(f, "-")?;
+            }
+
+            write!(f, "{byte:02x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An identifier, recognized as one of the standard identifier schemes where possible.
+///
+/// See [crate::v2_0::identifier] for more information.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ParsedIdentifier {
+    Isbn(Isbn),
+    Issn(Issn),
+    Doi(Doi),
+    Uuid(Uuid),
+    /// Any other URN.
+    Urn(urn::Urn),
+    /// Any other URL.
+    Url(url::Url),
+}
+
+/// The schemes that an identifier can be recognized as.
+#[derive(Clone, Copy)]
+enum Scheme {
+    Isbn,
+    Issn,
+    Doi,
+    Uuid,
+}
+
+impl Scheme {
+    /// Recognize the scheme of an [AltIdentifier].
+    fn from_url(scheme: &url::Url) -> Option<Self> {
+        let scheme = scheme.as_str().trim_end_matches('/').to_ascii_lowercase();
+        let name = scheme
+            .strip_prefix("urn:")
+            .or_else(|| scheme.strip_prefix("http://id.loc.gov/vocabulary/identifiers/"))
+            .or_else(|| scheme.strip_prefix("https://id.loc.gov/vocabulary/identifiers/"))?;
+
+        match name {
+            "isbn" => Some(Self::Isbn),
+            "issn" => Some(Self::Issn),
+            "doi" => Some(Self::Doi),
+            "uuid" => Some(Self::Uuid),
+            _ => None,
+        }
+    }
+
+    fn parse(&self, value: &str) -> Result<ParsedIdentifier, Error> {
+        match self {
+            Self::Isbn => value.parse().map(ParsedIdentifier::Isbn),
+            Self::Issn => value.parse().map(ParsedIdentifier::Issn),
+            Self::Doi => value.parse().map(ParsedIdentifier::Doi),
+            Self::Uuid => value.parse().map(ParsedIdentifier::Uuid),
+        }
+    }
+}
+
+impl ParsedIdentifier {
+    /// The URI for this identifier in its normal form, like `urn:isbn:9780000000001`.
+    pub fn to_uri(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl FromStr for ParsedIdentifier {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let prefixes = [
+            ("urn:isbn:", Scheme::Isbn),
+            ("urn:issn:", Scheme::Issn),
+            ("urn:uuid:", Scheme::Uuid),
+        ];
+
+        for (prefix, scheme) in prefixes {
+            if let Some(value) = strip_prefix_ignore_case(s, prefix) {
+                return scheme.parse(value);
+            }
+        }
+
+        if strip_doi_prefix(s).is_some() {
+            return s.parse().map(Self::Doi);
+        }
+
+        if strip_prefix_ignore_case(s, "urn:").is_some() {
+            return urn::Urn::from_str(s)
+                .map(Self::Urn)
+                .map_err(|_| invalid("URN", s));
+        }
+
+        url::Url::parse(s)
+            .map(Self::Url)
+            .map_err(|_| invalid("URL", s))
+    }
+}
+
+impl fmt::Display for ParsedIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Isbn(isbn) => write!(f, "urn:isbn:{isbn}"),
+            Self::Issn(issn) => write!(f, "urn:issn:{issn}"),
+            Self::Doi(doi) => write!(f, "{}", doi.to_url()),
+            Self::Uuid(uuid) => write!(f, "urn:uuid:{uuid}"),
+            Self::Urn(urn) => write!(f, "{urn}"),
+            Self::Url(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+impl Identifier {
+    /// Recognize which standard scheme this identifier uses.
+    pub fn parse(&self) -> Result<ParsedIdentifier, Error> {
+        match self {
+            Self::Url(url) => url.as_str().parse(),
+            Self::Urn(urn) => urn.as_str().parse(),
+        }
+    }
+}
+
+impl AltIdentifier<'_> {
+    /// Recognize which standard scheme this identifier uses, based on its `scheme` if it has a
+    /// known one, or else its value.
+    pub fn parse(&self) -> Result<ParsedIdentifier, Error> {
+        let scheme = self.scheme.as_ref().and_then(Scheme::from_url);
+
+        match scheme {
+            Some(scheme) => scheme.parse(&self.value).or_else(|_| self.value.parse()),
+            None => self.value.parse(),
+        }
+    }
+}
+
+impl PublicationMetadata<'_> {
+    /// All of this publication's identifiers that could be recognized, without duplicates.
+    ///
+    /// This includes both [PublicationMetadata::identifier] and
+    /// [PublicationMetadata::alt_identifier].
+    pub fn identifiers(&self) -> Vec<ParsedIdentifier> {
+        let main = self.identifier.iter().map(|url| url.as_str().parse());
+        let alt = self.alt_identifier.iter().map(AltIdentifier::parse);
+
+        let mut identifiers = vec![];
+
+        for id in main.chain(alt).flatten() {
+            if !identifiers.contains(&id) {
+                identifiers.push(id);
+            }
+        }
+
+        identifiers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> ParsedIdentifier {
+        s.parse().expect("valid identifier")
+    }
+
+    #[test]
+    fn test_isbn() {
+        let isbn: Isbn = "0-306-40615-2".parse().unwrap();
+        assert_eq!(isbn.to_string(), "9780306406157");
+        assert_eq!(isbn.to_isbn10().as_deref(), Some("0306406152"));
+        assert_eq!(isbn, "978-0-306-40615-7".parse().unwrap());
+
+        let isbn: Isbn = "080442957X".parse().unwrap();
+        assert_eq!(isbn.to_string(), "9780804429573");
+        assert_eq!(isbn.to_isbn10().as_deref(), Some("080442957X"));
+
+        let isbn: Isbn = "979-10-90636-07-1".parse().unwrap();
+        assert_eq!(isbn.to_isbn10(), None);
+
+        for invalid in [
+            "0-306-40615-3",
+            "9780306406158",
+            "1234567890123",
+            "X306406152",
+            "12345",
+        ] {
+            assert!(invalid.parse::<Isbn>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_other_schemes() {
+        let issn: Issn = "0317-8471".parse().unwrap();
+        assert_eq!(issn.to_string(), "0317-8471");
+        assert_eq!("2434561X".parse::<Issn>().unwrap().to_string(), "2434-561X");
+        assert!("0317-8472".parse::<Issn>().is_err());
+
+        let uuid: Uuid = "F81D4FAE-7DEC-11D0-A765-00A0C91E6BF6".parse().unwrap();
+        assert_eq!(uuid.to_string(), "f81d4fae-7dec-11d0-a765-00a0c91e6bf6");
+        assert_eq!(uuid, "f81d4fae7dec11d0a76500a0c91e6bf6".parse().unwrap());
+        assert!("f81d4fae-7dec-11d0-a765".parse::<Uuid>().is_err());
+        assert!(
+            "f81d4fae7-dec-11d0-a765-00a0c91e6bf6"
+                .parse::<Uuid>()
+                .is_err()
+        );
+        assert!("f81d4fae7dec11d0a76500a0c91e6bf-".parse::<Uuid>().is_err());
+        assert!("f81d4fae-7dec11d0a76500a0c91e6bf".parse::<Uuid>().is_err());
+        assert!(
+            "urn:uuid:f81d4fae7dec11d0a76500a0c91e6bf-"
+                .parse::<ParsedIdentifier>()
+                .is_err()
+        );
+
+        let doi: Doi = "https://doi.org/10.1000/ABC".parse().unwrap();
+        assert_eq!(doi, "doi:10.1000/abc".parse().unwrap());
+        assert_eq!(doi.to_url().as_str(), "https://doi.org/10.1000/abc");
+        assert!("doi:11.1000/abc".parse::<Doi>().is_err());
+        assert!("doi:10.1000/".parse::<Doi>().is_err());
+        assert!("doi:10.1000/..".parse::<Doi>().is_err());
+
+        // The suffix can't escape the DOI's path on doi.org.
+        let doi: Doi = "doi:10.1000/../../evil".parse().unwrap();
+        assert_eq!(
+            doi.to_url().as_str(),
+            "https://doi.org/10.1000/..%2F..%2Fevil"
+        );
+        assert_eq!(
+            parse("doi:10.1000/../../evil").to_uri(),
+            "https://doi.org/10.1000/..%2F..%2Fevil"
+        );
+        let doi: Doi = "doi:10.1000/a?b#c".parse().unwrap();
+        assert_eq!(doi.to_url().as_str(), "https://doi.org/10.1000/a%3Fb%23c");
+
+        // DOIs survive a round trip through their URL.
+        for s in [
+            "doi:10.1002/(SICI)1097-4571(199806)49:8<693::AID-ASI4>3.0.CO;2-0",
+            "doi:10.1000/a?b#c",
+            "doi:10.1000/../../evil",
+            "doi:10.1000/100%",
+            "doi:10.1000/café au lait",
+        ] {
+            let id = parse(s);
+            assert_eq!(parse(&id.to_uri()), id, "{s}");
+        }
+        assert_eq!(
+            parse("https://doi.org/10.1000/a%2Fb"),
+            parse("doi:10.1000/a/b")
+        );
+        assert_eq!(
+            "https://doi.org/10.1000/%zz"
+                .parse::<Doi>()
+                .unwrap()
+                .as_str(),
+            "10.1000/%zz"
+        );
+    }
+
+    #[test]
+    fn test_parsed_identifier() {
+        assert_eq!(
+            parse("urn:isbn:0-306-40615-2"),
+            parse("URN:ISBN:9780306406157")
+        );
+        assert_eq!(
+            parse("urn:isbn:0-306-40615-2").to_uri(),
+            "urn:isbn:9780306406157"
+        );
+        assert_eq!(
+            parse("http://dx.doi.org/10.1000/182"),
+            parse("doi:10.1000/182")
+        );
+        assert!(matches!(
+            parse("urn:uuid:f81d4fae-7dec-11d0-a765-00a0c91e6bf6"),
+            ParsedIdentifier::Uuid(_)
+        ));
+        assert!(matches!(parse("urn:oclc:12345"), ParsedIdentifier::Urn(_)));
+        assert!(matches!(
+            parse("https://example.com/books/1"),
+            ParsedIdentifier::Url(_)
+        ));
+
+        assert!(
+            "urn:isbn:9780306406158"
+                .parse::<ParsedIdentifier>()
+                .is_err()
+        );
+        assert!("not an identifier".parse::<ParsedIdentifier>().is_err());
+    }
+
+    #[test]
+    fn test_publication_identifiers() {
+        let json = r#"{
+            "title": "Example",
+            "identifier": "urn:isbn:9780306406157",
+            "altIdentifier": [
+                {"value": "0-306-40615-2", "scheme": "http://id.loc.gov/vocabulary/identifiers/isbn"},
+                {"value": "10.1000/182", "scheme": "urn:doi"},
+                "urn:uuid:f81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+                {"value": "12345", "scheme": "https://example.com/catalog-numbers"},
+                "urn:isbn:0000000000000"
+            ]
+        }"#;
+
+        let metadata: PublicationMetadata<'_> = serde_json::from_str(json).unwrap();
+        let uris: Vec<_> = metadata
+            .identifiers()
+            .iter()
+            .map(|id| id.to_uri())
+            .collect();
+
+        assert_eq!(
+            uris,
+            vec![
+                "urn:isbn:9780306406157",
+                "https://doi.org/10.1000/182",
+                "urn:uuid:f81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+            ]
+        );
+    }
+}
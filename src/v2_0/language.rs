@@ -0,0 +1,282 @@
+//! Support for picking between the per-language choices of a [StringWithAlternates]
+//!
+//! Readers' language preferences are given as a list of [LanguageRange]s, most preferred
+//! first, such as those from an HTTP `Accept-Language` header, which can be parsed with
+//! [LanguageRange::from_accept_language]. The best choice is then picked by
+//! [StringWithAlternates::resolve] in the following order:
+//!
+//! 1. The [RFC 4647 lookup] of the ranges, where `pt-BR` matches a `pt-BR` choice, and then
+//!    falls back to a `pt` choice.
+//! 2. The [RFC 4647 basic filtering] of the ranges and their fallbacks, where `pt-BR` matches
+//!    a `pt-PT` choice through `pt`, and `*` matches the first choice.
+//! 3. An English (`en`) choice.
+//! 4. The first choice, in the order the document lists them.
+//!
+//! [RFC 4647 lookup]: https://www.rfc-editor.org/rfc/rfc4647#section-3.4
+//! [RFC 4647 basic filtering]: https://www.rfc-editor.org/rfc/rfc4647#section-3.3.1
+use std::fmt;
+use std::str::FromStr;
+
+use langtag::LangTag;
+
+use super::*;
+
+/// The language used when none of the requested languages are available.
+const DEFAULT_LANGUAGE: &str = "en";
+
+/// An error encountered while parsing a [LanguageRange].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The string is not a basic language range.
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(range) => write!(f, "invalid language range {range:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A basic language range, like `pt-BR`, `pt` or `*`.
+///
+/// See [RFC 4647] for more information.
+///
+/// [RFC 4647]: https://www.rfc-editor.org/rfc/rfc4647#section-2.1
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LanguageRange {
+    range: String,
+}
+
+impl LanguageRange {
+    /// The range that matches every language.
+    pub fn wildcard() -> Self {
+        Self {
+            range: "*".to_string(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.range
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        self.range == "*"
+    }
+
+    /// Parse the ranges in an HTTP `Accept-Language` header, such as
+    /// `fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5`, ordered from most to least preferred.
+    ///
+    /// Malformed entries, and those with a quality of zero, are left out.
+    pub fn from_accept_language(header: &str) -> Vec<Self> {
+        let mut ranges: Vec<(Self, f32)> = header
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let range = parts.next()?.trim().parse().ok()?;
+                let mut quality = 1.0;
+
+                for param in parts {
+                    let (name, value) = param.split_once('=')?;
+
+                    if name.trim().eq_ignore_ascii_case("q") {
+                        quality = value.trim().parse().ok()?;
+                    }
+                }
+
+                (quality > 0.0 && quality <= 1.0).then_some((range, quality))
+            })
+            .collect();
+
+        // The sort is stable, so ranges with the same quality keep their order.
+        ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        ranges.into_iter().map(|(range, _)| range).collect()
+    }
+
+    /// The ranges tried in turn during lookup, like `zh-Hant-CN`, `zh-Hant` and then `zh`.
+    fn fallbacks(&self) -> impl Iterator<Item = &str> {
+        let mut range = (!self.is_wildcard()).then_some(self.range.as_str());
+
+        std::iter::from_fn(move || {
+            let current = range?;
+
+            range = current.rsplit_once('-').map(|(rest, _)| {
+                // A single-letter subtag only makes sense with the subtag following it.
+                match rest.rsplit_once('-') {
+                    Some((before, last)) if last.len() == 1 => before,
+                    _ => rest,
+                }
+            });
+
+            Some(current)
+        })
+    }
+}
+
+/// Whether `tag` is `prefix`, or starts with it followed by more subtags, ignoring case.
+fn is_prefix(prefix: &str, tag: &str) -> bool {
+    match tag.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => {
+            matches!(tag.as_bytes().get(prefix.len()), None | Some(b'-'))
+        }
+        _ => false,
+    }
+}
+
+impl FromStr for LanguageRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = s == "*"
+            || s.split('-').enumerate().all(|(i, subtag)| {
+                (1..=8).contains(&subtag.len())
+                    && if i == 0 {
+                        subtag.bytes().all(|c| c.is_ascii_alphabetic())
+                    } else {
+                        subtag.bytes().all(|c| c.is_ascii_alphanumeric())
+                    }
+            });
+
+        if valid {
+            Ok(Self {
+                range: s.to_ascii_lowercase(),
+            })
+        } else {
+            Err(Error::Invalid(s.to_string()))
+        }
+    }
+}
+
+impl From<&LangTag> for LanguageRange {
+    fn from(tag: &LangTag) -> Self {
+        Self {
+            range: tag.as_str().to_ascii_lowercase(),
+        }
+    }
+}
+
+impl fmt::Display for LanguageRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.range)
+    }
+}
+
+impl TaggedStrings {
+    /// Pick the best choice for the given ranges, which should be ordered from most to least
+    /// preferred.
+    ///
+    /// See [crate::v2_0::language] for how choices are picked. Returns `None` only when there
+    /// are no choices.
+    pub fn resolve(&self, ranges: &[LanguageRange]) -> Option<(&LangTag, &str)> {
+        let find =
+            |matches: &dyn Fn(&str) -> bool| self.iter().find(|(tag, _)| matches(tag.as_str()));
+
+        let lookup = || {
+            ranges
+                .iter()
+                .flat_map(LanguageRange::fallbacks)
+                .find_map(|range| find(&|tag: &str| tag.eq_ignore_ascii_case(range)))
+        };
+        let filter = || {
+            ranges.iter().find_map(|range| {
+                if range.is_wildcard() {
+                    return self.iter().next();
+                }
+
+                range
+                    .fallbacks()
+                    .find_map(|prefix| find(&|tag: &str| is_prefix(prefix, tag)))
+            })
+        };
+        let default = || find(&|tag: &str| tag.eq_ignore_ascii_case(DEFAULT_LANGUAGE));
+
+        lookup()
+            .or_else(filter)
+            .or_else(default)
+            .or_else(|| self.iter().next())
+    }
+}
+
+impl StringWithAlternates<'_> {
+    /// Pick the string to show for the given ranges, which should be ordered from most to
+    /// least preferred.
+    ///
+    /// See [crate::v2_0::language] for how choices are picked.
+    pub fn resolve(&self, ranges: &[LanguageRange]) -> &str {
+        match self {
+            Self::Always(s) => s,
+            Self::Variants(variants) => variants.resolve(ranges).map_or("", |(_, s)| s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(header: &str) -> Vec<LanguageRange> {
+        LanguageRange::from_accept_language(header)
+    }
+
+    #[test]
+    fn test_accept_language() {
+        let parsed: Vec<_> = ranges("fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5")
+            .iter()
+            .map(LanguageRange::to_string)
+            .collect();
+        assert_eq!(parsed, vec!["fr-ch", "fr", "en", "de", "*"]);
+
+        let parsed: Vec<_> = ranges("de;q=0.5, en-US ,x;q=0, 12;q=1, es;q=bad, pt;Q=0.5")
+            .iter()
+            .map(LanguageRange::to_string)
+            .collect();
+        assert_eq!(parsed, vec!["en-us", "de", "pt"]);
+
+        assert!(ranges("").is_empty());
+        assert!("zh-Hant-CN".parse::<LanguageRange>().is_ok());
+        assert!("toolongsubtag".parse::<LanguageRange>().is_err());
+        assert!("en--us".parse::<LanguageRange>().is_err());
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let range: LanguageRange = "zh-Hant-CN-x-private1".parse().unwrap();
+        let fallbacks: Vec<_> = range.fallbacks().collect();
+        assert_eq!(
+            fallbacks,
+            vec!["zh-hant-cn-x-private1", "zh-hant-cn", "zh-hant", "zh"]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let title = StringWithAlternates::LANGUAGES;
+
+        assert_eq!(title.resolve(&ranges("pt-BR")), "Línguas");
+        assert_eq!(title.resolve(&ranges("it, de;q=0.5")), "Sprachen");
+        assert_eq!(title.resolve(&ranges("ja")), "Languages");
+        assert_eq!(title.resolve(&[]), "Languages");
+        assert_eq!(title.resolve(&ranges("*")), "Sprachen");
+
+        let json = r#"{"pt-PT": "Olá", "fr": "Bonjour", "es-MX": "Hola"}"#;
+        let greeting: StringWithAlternates<'_> = serde_json::from_str(json).unwrap();
+        assert_eq!(greeting.resolve(&ranges("pt-BR, es")), "Olá");
+        assert_eq!(greeting.resolve(&ranges("es-MX")), "Hola");
+        // With no match and no English, the first choice in the document is used.
+        assert_eq!(greeting.resolve(&ranges("de")), "Olá");
+
+        let plain = StringWithAlternates::from("Plain");
+        assert_eq!(plain.resolve(&ranges("fr")), "Plain");
+
+        let StringWithAlternates::Variants(variants) = greeting else {
+            panic!("expected variants");
+        };
+        let tags: Vec<_> = variants.iter().map(|(tag, _)| tag.as_str()).collect();
+        assert_eq!(tags, vec!["pt-PT", "fr", "es-MX"]);
+        assert_eq!(variants.get(langtag::langtag!("FR")), Some("Bonjour"));
+    }
+}
@@ -0,0 +1,344 @@
+//! Lenient parsing of feeds
+//!
+//! A single malformed value, such as an invalid language tag deep within one publication,
+//! normally makes the whole [Feed] fail to parse. [Feed::from_json_lenient] instead recovers
+//! from these problems by removing the smallest part of the document that it can: first just
+//! the offending field, and if that is not enough, the whole link, publication, facet or group
+//! that contains it. Each removal is reported as a [Warning].
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::*;
+use crate::v2_0::validate::escape;
+
+/// A part of a feed that was removed so that the rest of it could be parsed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Warning {
+    /// A JSON pointer to the removed value within the original document, such as
+    /// `/publications/3/metadata/language`.
+    pub pointer: String,
+
+    /// Why the value could not be parsed.
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: removed, {}", self.pointer, self.message)
+    }
+}
+
+/// A step within a JSON document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn pointer(path: &[Segment]) -> String {
+    path.iter()
+        .map(|segment| match segment {
+            Segment::Key(key) => format!("/{}", escape(key)),
+            Segment::Index(i) => format!("/{i}"),
+        })
+        .collect()
+}
+
+fn get<'v>(value: &'v Value, path: &[Segment]) -> Option<&'v Value> {
+    path.iter().try_fold(value, |value, segment| match segment {
+        Segment::Key(key) => value.get(key),
+        Segment::Index(i) => value.get(i),
+    })
+}
+
+/// Copy `value`, leaving out everything at the given paths.
+fn without(value: &Value, removed: &[Vec<Segment>]) -> Value {
+    let mut removed = removed.to_vec();
+
+    // Removing later array items first keeps the indices of earlier ones valid, and removing
+    // nested values first keeps the path to them valid.
+    removed.sort_by(|a, b| {
+        let order = a.iter().zip(b).find_map(|pair| match pair {
+            (Segment::Index(a), Segment::Index(b)) if a != b => Some(b.cmp(a)),
+            (Segment::Key(a), Segment::Key(b)) if a != b => Some(b.cmp(a)),
+            _ => None,
+        });
+
+        order.unwrap_or_else(|| b.len().cmp(&a.len()))
+    });
+
+    let mut value = value.clone();
+
+    for path in removed {
+        let Some((last, parent)) = path.split_last() else {
+            continue;
+        };
+
+        let parent = parent
+            .iter()
+            .try_fold(&mut value, |value, segment| match segment {
+                Segment::Key(key) => value.get_mut(key),
+                Segment::Index(i) => value.get_mut(i),
+            });
+
+        match (parent, last) {
+            (Some(Value::Object(map)), Segment::Key(key)) => {
+                map.remove(key);
+            }
+            (Some(Value::Array(items)), Segment::Index(i)) if *i < items.len() => {
+                items.remove(*i);
+            }
+            _ => {}
+        }
+    }
+
+    value
+}
+
+/// Find the value beneath `prefix` whose removal gets past `error`.
+///
+/// Fields are parsed in the order they appear, so removing a value that parses fine leaves
+/// the error unchanged, while removing the offending one either fixes the document or moves
+/// on to the next error. Once removing a value fixes the document, only a part of it that
+/// also fixes the document is preferred to it, so that a required field is not removed in
+/// place of the object containing it.
+fn culprit<F>(
+    value: &Value,
+    removed: &[Vec<Segment>],
+    check: &F,
+    error: &str,
+    prefix: Vec<Segment>,
+    fixes: bool,
+) -> Option<Vec<Segment>>
+where
+    F: Fn(&Value) -> Result<(), String>,
+{
+    let children: Vec<Segment> = match get(value, &prefix)? {
+        Value::Object(map) => map.keys().cloned().map(Segment::Key).collect(),
+        Value::Array(items) => (0..items.len()).map(Segment::Index).collect(),
+        _ => return None,
+    };
+
+    for child in children {
+        let mut path = prefix.clone();
+        path.push(child);
+
+        if removed.contains(&path) {
+            continue;
+        }
+
+        let mut trial = removed.to_vec();
+        trial.push(path.clone());
+
+        let fixed = match check(&without(value, &trial)) {
+            Ok(()) => true,
+            Err(e) if !fixes && e != error => false,
+            Err(_) => continue,
+        };
+
+        let deeper = culprit(value, removed, check, error, path.clone(), fixes || fixed);
+        return Some(deeper.unwrap_or(path));
+    }
+
+    None
+}
+
+/// Check whether a value parses as the given type.
+macro_rules! parses {
+    ($t: ident) => {
+        |value: &Value| $t::deserialize(value).map(drop).map_err(|e| e.to_string())
+    };
+}
+
+#[derive(Default)]
+struct Lenient {
+    warnings: Vec<Warning>,
+}
+
+impl Lenient {
+    /// Remove values from `value` until it passes `check`, returning `None` if it can't be
+    /// fixed by removing any of its contents.
+    fn repair<F>(&mut self, path: &str, value: &Value, check: F) -> Option<Value>
+    where
+        F: Fn(&Value) -> Result<(), String>,
+    {
+        let mut removed = vec![];
+        let mut warnings = vec![];
+
+        loop {
+            let current = without(value, &removed);
+
+            let Err(message) = check(&current) else {
+                self.warnings.extend(warnings);
+                return Some(current);
+            };
+
+            let Some(culprit) = culprit(value, &removed, &check, &message, vec![], false) else {
+                self.warnings.push(Warning {
+                    pointer: path.to_string(),
+                    message,
+                });
+                return None;
+            };
+
+            warnings.push(Warning {
+                pointer: format!("{path}{}", pointer(&culprit)),
+                message,
+            });
+            removed.push(culprit);
+        }
+    }
+
+    /// Repair each item of the array at `key`, dropping the ones that can't be fixed.
+    fn items(
+        &mut self,
+        path: &str,
+        object: &mut Value,
+        key: &str,
+        mut item: impl FnMut(&mut Self, &str, &Value) -> Option<Value>,
+    ) {
+        let Some(Value::Array(items)) = object.get_mut(key) else {
+            return;
+        };
+
+        *items = std::mem::take(items)
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| item(self, &format!("{path}/{key}/{i}"), value))
+            .collect();
+    }
+
+    fn link(&mut self, path: &str, value: &Value) -> Option<Value> {
+        self.repair(path, value, parses!(Link))
+    }
+
+    fn publication(&mut self, path: &str, value: &Value) -> Option<Value> {
+        let mut value = value.clone();
+        self.items(path, &mut value, "links", Self::link);
+        self.items(path, &mut value, "images", Self::link);
+        self.repair(path, &value, parses!(Publication))
+    }
+
+    fn facet(&mut self, path: &str, value: &Value) -> Option<Value> {
+        let mut value = value.clone();
+        self.items(path, &mut value, "links", Self::link);
+        self.repair(path, &value, parses!(Facet))
+    }
+
+    fn group(&mut self, path: &str, value: &Value) -> Option<Value> {
+        let mut value = value.clone();
+        self.items(path, &mut value, "links", Self::link);
+        self.items(path, &mut value, "navigation", Self::link);
+        self.items(path, &mut value, "publications", Self::publication);
+        self.repair(path, &value, parses!(FeedGroup))
+    }
+}
+
+impl<'a> Feed<'a> {
+    /// Parse a feed, removing any parts of it that are malformed.
+    ///
+    /// Only documents that are not valid JSON, or whose problems can't be fixed by removing
+    /// parts of the feed, such as a missing title, fail to parse.
+    ///
+    /// See [crate::v2_0::lenient] for more information.
+    pub fn from_json_lenient(json: &'a str) -> Result<(Self, Vec<Warning>), serde_json::Error> {
+        let mut value: Value = serde_json::from_str(json)?;
+        let mut lenient = Lenient::default();
+
+        lenient.items("", &mut value, "links", Lenient::link);
+        lenient.items("", &mut value, "navigation", Lenient::link);
+        lenient.items("", &mut value, "facets", Lenient::facet);
+        lenient.items("", &mut value, "publications", Lenient::publication);
+        lenient.items("", &mut value, "groups", Lenient::group);
+
+        let value = lenient.repair("", &value, parses!(Feed)).unwrap_or(value);
+        let feed = Feed::deserialize(value)?;
+
+        Ok((feed, lenient.warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pointers(warnings: &[Warning]) -> Vec<&str> {
+        warnings.iter().map(|w| w.pointer.as_str()).collect()
+    }
+
+    #[test]
+    fn test_lenient_recovery() {
+        let json = r#"{
+            "metadata": {"title": "Example", "identifier": "not a url"},
+            "links": [
+                {"rel": "self", "href": "https://example.com/feed.json"},
+                {"rel": "next", "href": 2}
+            ],
+            "publications": [{
+                "metadata": {"title": "Bad Language", "language": ["en", "$$$"]},
+                "links": [{
+                    "rel": "http://opds-spec.org/acquisition",
+                    "href": "https://example.com/1.epub",
+                    "properties": {"availability": {"state": "misplaced"}}
+                }]
+            }, {
+                "metadata": {"title": 5},
+                "links": []
+            }, {
+                "metadata": {"title": "Fine"},
+                "links": [{"rel": "http://opds-spec.org/acquisition", "href": "https://example.com/3.epub"}]
+            }],
+            "groups": [{
+                "metadata": {"title": "Group"},
+                "publications": [{"metadata": {"title": "Grouped", "identifier": 7}, "links": []}]
+            }]
+        }"#;
+
+        assert!(serde_json::from_str::<Feed<'_>>(json).is_err());
+
+        let (feed, warnings) = Feed::from_json_lenient(json).expect("can recover feed");
+
+        assert_eq!(
+            pointers(&warnings),
+            vec![
+                "/links/1/href",
+                "/publications/0/links/0/properties/availability",
+                "/publications/0/metadata/language/1",
+                "/publications/1",
+                "/groups/0/publications/0/metadata/identifier",
+                "/metadata/identifier",
+            ]
+        );
+
+        assert_eq!(feed.links.len(), 2);
+        assert!(feed.links[1].href.is_none());
+        assert!(feed.metadata.identifier.is_none());
+
+        let titles: Vec<_> = feed
+            .publications
+            .iter()
+            .map(|p| match &p.metadata.title {
+                StringWithAlternates::Always(title) => title.as_ref(),
+                StringWithAlternates::Variants(_) => "",
+            })
+            .collect();
+        assert_eq!(titles, vec!["Bad Language", "Fine"]);
+        assert_eq!(feed.publications[0].metadata.language.len(), 1);
+        assert!(
+            feed.publications[0].links[0]
+                .properties
+                .availability
+                .is_none()
+        );
+        assert_eq!(feed.groups[0].publications.len(), 1);
+    }
+
+    #[test]
+    fn test_lenient_failure() {
+        assert!(Feed::from_json_lenient("{").is_err());
+        assert!(Feed::from_json_lenient(r#"{"metadata": {}, "links": []}"#).is_err());
+    }
+}
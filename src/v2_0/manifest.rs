@@ -0,0 +1,521 @@
+//! Support for Readium Web Publication Manifests
+//!
+//! A [Manifest] describes a complete publication: its metadata, the resources that make up its
+//! content in reading order, and collections such as its table of contents. It is used for the
+//! `application/webpub+json` and `application/opds-publication+json` media types.
+//!
+//! See the [Readium Web Publication Manifest] specification for more information.
+//!
+//! [Readium Web Publication Manifest]: https://readium.org/webpub-manifest/
+use std::borrow::Cow;
+use std::hash::{Hash, Hasher};
+
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+
+use super::*;
+use crate::v2_0::extensions::Extensions;
+use crate::v2_0::owned::IntoOwned;
+
+/// A collection of links with an unrecognized role.
+///
+/// Collections can be written in either a compact form, which is just an array of links, or
+/// a full form, which is an object with its own metadata, links and nested subcollections.
+/// Collections without any metadata, subcollections or extensions are written out in the
+/// compact form.
+///
+/// See [Section 3: Collections] for more information.
+///
+/// [Section 3: Collections]: https://readium.org/webpub-manifest/#3-collections
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct Subcollection<'a> {
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    pub links: Vec<Link<'a>>,
+    pub subcollections: Subcollections<'a>,
+    pub extensions: Extensions,
+}
+
+impl<'a> Subcollection<'a> {
+    pub fn new(links: Vec<Link<'a>>) -> Self {
+        Self {
+            metadata: serde_json::Map::new(),
+            links,
+            subcollections: Subcollections::new(),
+            extensions: Extensions::new(),
+        }
+    }
+}
+
+/// Collections with unrecognized roles, keyed by the role's name.
+///
+/// Entries are kept in the order they appeared in the source document, like [Extensions],
+/// and two maps with the same entries in different orders are equal.
+#[derive(Clone, Debug, Default)]
+pub struct Subcollections<'a> {
+    entries: Vec<(String, Subcollection<'a>)>,
+}
+
+impl<'a> Subcollections<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, role: &str) -> Option<&Subcollection<'a>> {
+        self.entries.iter().find(|(r, _)| r == role).map(|(_, c)| c)
+    }
+
+    pub fn get_mut(&mut self, role: &str) -> Option<&mut Subcollection<'a>> {
+        self.entries
+            .iter_mut()
+            .find(|(r, _)| r == role)
+            .map(|(_, c)| c)
+    }
+
+    pub fn contains_key(&self, role: &str) -> bool {
+        self.get(role).is_some()
+    }
+
+    /// Set the collection for `role`, returning the previous collection if there was one.
+    ///
+    /// Replacing an existing collection keeps its position, while new roles are added at the
+    /// end.
+    pub fn insert(
+        &mut self,
+        role: impl Into<String>,
+        collection: Subcollection<'a>,
+    ) -> Option<Subcollection<'a>> {
+        let role = role.into();
+
+        match self.get_mut(&role) {
+            Some(old) => Some(std::mem::replace(old, collection)),
+            None => {
+                self.entries.push((role, collection));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, role: &str) -> Option<Subcollection<'a>> {
+        let i = self.entries.iter().position(|(r, _)| r == role)?;
+        Some(self.entries.remove(i).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Subcollection<'a>)> {
+        self.entries.iter().map(|(r, c)| (r.as_str(), c))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(r, _)| r.as_str())
+    }
+}
+
+impl PartialEq for Subcollections<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.entries.iter().all(|(r, c)| other.get(r) == Some(c))
+    }
+}
+
+impl Eq for Subcollections<'_> {}
+
+impl Hash for Subcollections<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.hash(state);
+    }
+}
+
+impl<'a, R: Into<String>> FromIterator<(R, Subcollection<'a>)> for Subcollections<'a> {
+    fn from_iter<I: IntoIterator<Item = (R, Subcollection<'a>)>>(iter: I) -> Self {
+        let mut subcollections = Self::new();
+
+        for (r, c) in iter {
+            subcollections.insert(r, c);
+        }
+
+        subcollections
+    }
+}
+
+impl<'a> IntoIterator for Subcollections<'a> {
+    type Item = (String, Subcollection<'a>);
+    type IntoIter = std::vec::IntoIter<(String, Subcollection<'a>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl Serialize for Subcollections<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+
+        for (role, collection) in self.entries.iter() {
+            map.serialize_entry(role, collection)?;
+        }
+
+        map.end()
+    }
+}
+
+/// A field that isn't modeled by this crate, which is a subcollection when its value is an
+/// object or an array of links, and an extension otherwise.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Unmodeled<'a> {
+    #[serde(borrow)]
+    Subcollection(Subcollection<'a>),
+    Extension(Value),
+}
+
+struct UnmodeledVisitor;
+
+impl<'de> Visitor<'de> for UnmodeledVisitor {
+    type Value = Vec<(String, Unmodeled<'de>)>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a map of subcollections and extension properties")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = vec![];
+
+        while let Some(field) = access.next_entry()? {
+            fields.push(field);
+        }
+
+        Ok(fields)
+    }
+}
+
+fn deserialize_subcollections<'de, D>(deserializer: D) -> Result<Subcollections<'de>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let fields = deserializer.deserialize_map(UnmodeledVisitor)?;
+
+    Ok(fields
+        .into_iter()
+        .filter_map(|(role, field)| match field {
+            Unmodeled::Subcollection(collection) => Some((role, collection)),
+            Unmodeled::Extension(_) => None,
+        })
+        .collect())
+}
+
+fn deserialize_extensions<'de, D>(deserializer: D) -> Result<Extensions, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let fields = deserializer.deserialize_map(UnmodeledVisitor)?;
+
+    Ok(fields
+        .into_iter()
+        .filter_map(|(key, field)| match field {
+            Unmodeled::Subcollection(_) => None,
+            Unmodeled::Extension(value) => Some((key, value)),
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct FullSubcollection<'a> {
+    #[serde(default)]
+    metadata: serde_json::Map<String, serde_json::Value>,
+
+    #[serde(borrow, default)]
+    links: Vec<Link<'a>>,
+
+    #[serde(borrow, flatten, deserialize_with = "deserialize_subcollections")]
+    subcollections: Subcollections<'a>,
+
+    #[serde(flatten, deserialize_with = "deserialize_extensions")]
+    extensions: Extensions,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SubcollectionForm<'a> {
+    #[serde(borrow)]
+    Compact(Vec<Link<'a>>),
+    Full(FullSubcollection<'a>),
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Subcollection<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match SubcollectionForm::deserialize(deserializer)? {
+            SubcollectionForm::Compact(links) => Ok(Self::new(links)),
+            SubcollectionForm::Full(full) => Ok(Self {
+                metadata: full.metadata,
+                links: full.links,
+                subcollections: full.subcollections,
+                extensions: full.extensions,
+            }),
+        }
+    }
+}
+
+impl Serialize for Subcollection<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.metadata.is_empty() && self.subcollections.is_empty() && self.extensions.is_empty()
+        {
+            return self.links.serialize(serializer);
+        }
+
+        let mut map = serializer.serialize_map(None)?;
+
+        if !self.metadata.is_empty() {
+            map.serialize_entry("metadata", &self.metadata)?;
+        }
+
+        if !self.links.is_empty() {
+            map.serialize_entry("links", &self.links)?;
+        }
+
+        for (role, collection) in self.subcollections.iter() {
+            map.serialize_entry(role, collection)?;
+        }
+
+        for (key, value) in self.extensions.iter() {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+/// A Readium Web Publication Manifest.
+///
+/// See the [Readium Web Publication Manifest] specification and the associated
+/// [JSON Schema] for more information.
+///
+/// [Readium Web Publication Manifest]: https://readium.org/webpub-manifest/
+/// [JSON Schema]: https://readium.org/webpub-manifest/schema/publication.schema.json
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Manifest<'a> {
+    /// The JSON-LD contexts that apply to this manifest, such as
+    /// `https://readium.org/webpub-manifest/context.jsonld`.
+    #[serde(
+        rename = "@context",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_flattened_vec",
+        deserialize_with = "deserialize_flattened_vec"
+    )]
+    pub context: Vec<Cow<'a, str>>,
+
+    #[serde(borrow)]
+    pub metadata: PublicationMetadata<'a>,
+
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link<'a>>,
+
+    /// The resources that make up the publication's content, in the order that they should be
+    /// read.
+    ///
+    /// See [Section 3.1: Core Collection Roles] for more information.
+    ///
+    /// [Section 3.1: Core Collection Roles]: https://readium.org/webpub-manifest/#31-core-collection-roles
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub reading_order: Vec<Link<'a>>,
+
+    /// Any other resources needed to render the publication, such as stylesheets and fonts.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<Link<'a>>,
+
+    /// The publication's table of contents, where nested entries are stored in
+    /// [Link::children].
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub toc: Vec<Link<'a>>,
+
+    /// Links to major structural components of the publication.
+    ///
+    /// See the [EPUB Extension] for more information on this and the other lists below.
+    ///
+    /// [EPUB Extension]: https://readium.org/webpub-manifest/profiles/epub.html#3-collection-roles
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub landmarks: Vec<Link<'a>>,
+
+    /// Links to the locations of print pages within the publication.
+    #[serde(
+        borrow,
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        rename = "page-list"
+    )]
+    pub page_list: Vec<Link<'a>>,
+
+    /// A list of audio clips.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub loa: Vec<Link<'a>>,
+
+    /// A list of illustrations.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub loi: Vec<Link<'a>>,
+
+    /// A list of tables.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub lot: Vec<Link<'a>>,
+
+    /// A list of videos.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub lov: Vec<Link<'a>>,
+
+    /// Regions of the resources in the reading order, such as the panels of a comic, in the
+    /// order that they should be shown when using guided navigation.
+    ///
+    /// See the [Divina Profile] for more information.
+    ///
+    /// [Divina Profile]: https://readium.org/webpub-manifest/profiles/divina.html
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub guided: Vec<Link<'a>>,
+
+    /// Links to preview images for the publication, as used within OPDS.
+    ///
+    /// See [Publication::images] for more information.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<Link<'a>>,
+
+    /// Collections with any other role, keyed by the role's name.
+    ///
+    /// Any other field whose value is an object or an array of links is treated as a
+    /// collection, while the rest are kept in [Manifest::extensions].
+    #[serde(borrow, flatten, deserialize_with = "deserialize_subcollections")]
+    pub subcollections: Subcollections<'a>,
+
+    /// Fields whose values aren't collections, such as vendor-specific settings.
+    #[serde(flatten, deserialize_with = "deserialize_extensions")]
+    pub extensions: Extensions,
+}
+
+impl<'a> Manifest<'a> {
+    pub fn new(metadata: PublicationMetadata<'a>) -> Self {
+        Self {
+            context: vec![],
+            metadata,
+            links: vec![],
+            reading_order: vec![],
+            resources: vec![],
+            toc: vec![],
+            landmarks: vec![],
+            page_list: vec![],
+            loa: vec![],
+            loi: vec![],
+            lot: vec![],
+            lov: vec![],
+            guided: vec![],
+            images: vec![],
+            subcollections: Subcollections::new(),
+            extensions: Extensions::new(),
+        }
+    }
+
+    pub fn with_context(mut self, context: impl Into<Cow<'a, str>>) -> Self {
+        self.context.push(context.into());
+        self
+    }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    pub fn with_reading_order(mut self, link: Link<'a>) -> Self {
+        self.reading_order.push(link);
+        self
+    }
+
+    pub fn with_resource(mut self, link: Link<'a>) -> Self {
+        self.resources.push(link);
+        self
+    }
+
+    pub fn with_toc(mut self, link: Link<'a>) -> Self {
+        self.toc.push(link);
+        self
+    }
+
+    pub fn with_image(mut self, link: Link<'a>) -> Self {
+        self.images.push(link);
+        self
+    }
+
+    pub fn with_subcollection(
+        mut self,
+        role: impl Into<String>,
+        subcollection: Subcollection<'a>,
+    ) -> Self {
+        self.subcollections.insert(role.into(), subcollection);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
+}
+
+/// Fields in [Publication::extensions] that [Manifest] models, such as `readingOrder` and
+/// `toc`, are read into their own fields, along with any subcollections. If any of them can't
+/// be read, the extensions are all kept in [Manifest::extensions] instead.
+impl<'a> From<Publication<'a>> for Manifest<'a> {
+    fn from(publication: Publication<'a>) -> Self {
+        let mut fields: serde_json::Map<String, Value> =
+            publication.extensions.into_iter().collect();
+
+        // The publication's metadata is moved over afterwards, rather than being copied into
+        // the object that is read.
+        fields.insert("metadata".into(), serde_json::json!({"title": ""}));
+        let fields = Value::Object(fields);
+
+        let mut manifest = match Manifest::deserialize(&fields) {
+            Ok(manifest) => manifest.into_owned(),
+            Err(_) => {
+                let mut manifest = Manifest::new(PublicationMetadata::new(""));
+                manifest.extensions = fields
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(k, _)| *k != "metadata")
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                manifest
+            }
+        };
+
+        manifest.metadata = publication.metadata;
+        manifest.links = publication.links;
+        manifest.images = publication.images;
+        manifest
+    }
+}
+
+into_owned!(Subcollection { links, subcollections; metadata, extensions });
+into_owned!(Manifest {
+    context, metadata, links, reading_order, resources, toc, landmarks, page_list, loa, loi,
+    lot, lov, guided, images, subcollections; extensions
+});
@@ -1,18 +1,21 @@
 //! Types used to represent the fields within the feed, link and publication metadata.
 use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 
-use serde::de::{Deserialize, Deserializer};
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
 use serde::ser::{Serialize, SerializeMap, Serializer};
 
 use super::*;
+use crate::mime::MediaType;
+use crate::v2_0::price::{Amount, Currency};
 
 /// A relationship between a resource and a link.
 ///
 /// See [Link Relations] for more information.
 ///
 /// [Link Relations]: https://readium.org/webpub-manifest/relationships.html
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum Relation {
@@ -70,6 +73,25 @@ pub enum Relation {
     #[serde(rename = "http://opds-spec.org/sort/popular")]
     SortPopular,
 
+    /// A link that a client can use to authenticate the user.
+    ///
+    /// See [Authentication for OPDS] for more information on this and the following relations.
+    ///
+    /// [Authentication for OPDS]: https://drafts.opds.io/authentication-for-opds-1.0.html
+    Authenticate,
+
+    /// A link that a client can use to refresh an expired access token.
+    Refresh,
+
+    /// A link to the logo of a catalog's provider.
+    Logo,
+
+    /// A link to where a user can register for an account.
+    Register,
+
+    /// A link to where a user can find help with authenticating.
+    Help,
+
     #[serde(untagged)]
     Acquisition(AcquisitionKind),
 
@@ -85,6 +107,69 @@ impl Relation {
             None
         }
     }
+
+    /// The value used for this relation in a link's `rel`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Myself => "self",
+            Self::Alternate => "alternate",
+            Self::Contents => "contents",
+            Self::Cover => "cover",
+            Self::Manifest => "manifest",
+            Self::Profile => "profile",
+            Self::First => "first",
+            Self::Previous => "previous",
+            Self::Next => "next",
+            Self::Last => "last",
+            Self::Current => "current",
+            Self::Search => "search",
+            Self::Subsection => "subsection",
+            Self::SortNew => "http://opds-spec.org/sort/new",
+            Self::SortPopular => "http://opds-spec.org/sort/popular",
+            Self::Authenticate => "authenticate",
+            Self::Refresh => "refresh",
+            Self::Logo => "logo",
+            Self::Register => "register",
+            Self::Help => "help",
+            Self::Acquisition(kind) => kind.as_str(),
+            Self::Custom(s) => s.as_str(),
+        }
+    }
+}
+
+impl std::str::FromStr for Relation {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rel = match s {
+            "self" => Self::Myself,
+            "alternate" => Self::Alternate,
+            "contents" => Self::Contents,
+            "cover" => Self::Cover,
+            "manifest" => Self::Manifest,
+            "profile" => Self::Profile,
+            "first" => Self::First,
+            "previous" => Self::Previous,
+            "next" => Self::Next,
+            "last" => Self::Last,
+            "current" => Self::Current,
+            "search" => Self::Search,
+            "subsection" => Self::Subsection,
+            "http://opds-spec.org/sort/new" => Self::SortNew,
+            "http://opds-spec.org/sort/popular" => Self::SortPopular,
+            "authenticate" => Self::Authenticate,
+            "refresh" => Self::Refresh,
+            "logo" => Self::Logo,
+            "register" => Self::Register,
+            "help" => Self::Help,
+            s => match s.parse::<AcquisitionKind>() {
+                Ok(kind) => Self::Acquisition(kind),
+                Err(()) => Self::Custom(s.to_string()),
+            },
+        };
+
+        Ok(rel)
+    }
 }
 
 impl From<String> for Relation {
@@ -93,7 +178,7 @@ impl From<String> for Relation {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum AcquisitionKind {
@@ -126,7 +211,37 @@ pub enum AcquisitionKind {
     Preview,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+impl AcquisitionKind {
+    /// The value used for this kind of acquisition in a link's `rel`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fallback => "http://opds-spec.org/acquisition",
+            Self::OpenAccess => "http://opds-spec.org/acquisition/open-access",
+            Self::Buy => "http://opds-spec.org/acquisition/buy",
+            Self::Sample => "http://opds-spec.org/acquisition/sample",
+            Self::Subscribe => "http://opds-spec.org/acquisition/subscribe",
+            Self::Preview => "preview",
+        }
+    }
+}
+
+impl std::str::FromStr for AcquisitionKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "http://opds-spec.org/acquisition" => Ok(Self::Fallback),
+            "http://opds-spec.org/acquisition/open-access" => Ok(Self::OpenAccess),
+            "http://opds-spec.org/acquisition/buy" => Ok(Self::Buy),
+            "http://opds-spec.org/acquisition/sample" => Ok(Self::Sample),
+            "http://opds-spec.org/acquisition/subscribe" => Ok(Self::Subscribe),
+            "preview" => Ok(Self::Preview),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum PageDisplay {
     Left,
@@ -135,7 +250,7 @@ pub enum PageDisplay {
 }
 
 /// Hints for how the layout of the publication should be presented.
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum PublicationLayout {
     Fixed,
@@ -143,7 +258,7 @@ pub enum PublicationLayout {
     Scrolled,
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ReadingProgression {
     #[serde(rename = "rtl")]
@@ -158,13 +273,13 @@ pub enum ReadingProgression {
 /// See [Section 5.3: Acquisition Links].
 ///
 /// [Section 5.3: Acquisition Links]: https://drafts.opds.io/opds-2.0.html#53-acquisition-links
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Price {
     /// The number price for an acquisition.
-    pub value: f32,
+    pub value: Amount,
     /// The unit of currency for the price value.
-    pub currency: String,
+    pub currency: Currency,
 }
 
 /// An OPDS acquisition object.
@@ -172,7 +287,7 @@ pub struct Price {
 /// See [Section 5.3: Acquisition Links].
 ///
 /// [Section 5.3: Acquisition Links]: https://drafts.opds.io/opds-2.0.html#53-acquisition-links
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Acquisition<'a> {
     /// The MIME type that will be acquired.
@@ -185,8 +300,27 @@ pub struct Acquisition<'a> {
     pub child: Vec<Acquisition<'a>>,
 }
 
+impl<'a> Acquisition<'a> {
+    pub fn new(mime: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            mime: mime.into(),
+            child: vec![],
+        }
+    }
+
+    pub fn with_child(mut self, child: Acquisition<'a>) -> Self {
+        self.child.push(child);
+        self
+    }
+
+    /// The parsed [MediaType] of what will be acquired, if it is valid.
+    pub fn media_type(&self) -> Option<MediaType<'_>> {
+        MediaType::parse(&self.mime).ok()
+    }
+}
+
 /// Information about a library's holds for a publication.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Holds {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -197,7 +331,7 @@ pub struct Holds {
 }
 
 /// Information about a library's number of copies for a publication.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Copies {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -207,8 +341,32 @@ pub struct Copies {
     pub available: Option<usize>,
 }
 
+impl Holds {
+    pub fn with_total(mut self, total: usize) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    pub fn with_position(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+impl Copies {
+    pub fn with_total(mut self, total: usize) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    pub fn with_available(mut self, available: usize) -> Self {
+        self.available = Some(available);
+        self
+    }
+}
+
 /// A resource's current availability state.
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum AvailabilityState {
@@ -219,25 +377,45 @@ pub enum AvailabilityState {
 }
 
 /// A resource's availability.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Availability<'a> {
+pub struct Availability {
     /// The current state of the resource.
     pub state: AvailabilityState,
 
     /// Timestamp for when the state change occurred.
-    #[serde(borrow, skip_serializing_if = "Option::is_none")]
-    pub since: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<Timestamp>,
 
     /// Timestamp for when the next state change will occur.
-    #[serde(borrow, skip_serializing_if = "Option::is_none")]
-    pub until: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<Timestamp>,
+}
+
+impl Availability {
+    pub fn new(state: AvailabilityState) -> Self {
+        Self {
+            state,
+            since: None,
+            until: None,
+        }
+    }
+
+    pub fn with_since(mut self, since: Timestamp) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn with_until(mut self, until: Timestamp) -> Self {
+        self.until = Some(until);
+        self
+    }
 }
 
 /// An identifier for a resource.
 ///
 /// This is either a URL or a URN.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum Identifier {
     Url(url::Url),
@@ -249,7 +427,7 @@ pub enum Identifier {
 /// See the [JSON Schema] for more details.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/altIdentifier.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AltIdentifier<'a> {
     pub value: Cow<'a, str>,
@@ -265,6 +443,11 @@ impl<'a> AltIdentifier<'a> {
             scheme: None,
         }
     }
+
+    pub fn with_scheme(mut self, scheme: url::Url) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
 }
 
 impl<'a> From<String> for AltIdentifier<'a> {
@@ -279,7 +462,7 @@ impl<'a> From<Cow<'a, str>> for AltIdentifier<'a> {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum AccessMode {
     Auditory,
@@ -295,7 +478,7 @@ pub enum AccessMode {
     Visual,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub enum AccessibilityExemption {
@@ -304,7 +487,7 @@ pub enum AccessibilityExemption {
     EaaMicroenterprise,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[non_exhaustive]
 pub enum AccessibilityFeature {
     #[serde(rename = "annotations")]
@@ -397,7 +580,7 @@ pub enum AccessibilityFeature {
     Unknown,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[non_exhaustive]
 pub enum AccessibilityHazard {
     #[serde(rename = "flashing")]
@@ -424,7 +607,7 @@ pub enum AccessibilityHazard {
     UnknownSoundHazard,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccessbilityCertification<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
@@ -437,6 +620,23 @@ pub struct AccessbilityCertification<'a> {
     pub report: Option<Cow<'a, str>>,
 }
 
+impl<'a> AccessbilityCertification<'a> {
+    pub fn with_certified_by(mut self, certified_by: impl Into<Cow<'a, str>>) -> Self {
+        self.certified_by = Some(certified_by.into());
+        self
+    }
+
+    pub fn with_credential(mut self, credential: impl Into<Cow<'a, str>>) -> Self {
+        self.credential = Some(credential.into());
+        self
+    }
+
+    pub fn with_report(mut self, report: impl Into<Cow<'a, str>>) -> Self {
+        self.report = Some(report.into());
+        self
+    }
+}
+
 /// Accessibility metadata for a publication.
 ///
 /// See [Default Context: Accessibility Metadata] and the associated [JSON Schema] for more
@@ -444,7 +644,7 @@ pub struct AccessbilityCertification<'a> {
 ///
 /// [Default context: Accessibility Metadata]: https://readium.org/webpub-manifest/contexts/default/#accessibility-metadata
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/a11y.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccessibilityMetadata<'a> {
     #[serde(
@@ -473,8 +673,45 @@ pub struct AccessibilityMetadata<'a> {
     pub summary: Option<Cow<'a, str>>,
 }
 
+impl<'a> AccessibilityMetadata<'a> {
+    pub fn with_conforms_to(mut self, conforms_to: url::Url) -> Self {
+        self.conforms_to.push(conforms_to);
+        self
+    }
+
+    pub fn with_exemption(mut self, exemption: AccessibilityExemption) -> Self {
+        self.exemption = Some(exemption);
+        self
+    }
+
+    pub fn with_access_mode(mut self, access_mode: AccessMode) -> Self {
+        self.access_mode.push(access_mode);
+        self
+    }
+
+    pub fn with_feature(mut self, feature: AccessibilityFeature) -> Self {
+        self.feature.push(feature);
+        self
+    }
+
+    pub fn with_hazard(mut self, hazard: AccessibilityHazard) -> Self {
+        self.hazard.push(hazard);
+        self
+    }
+
+    pub fn with_certification(mut self, certification: AccessbilityCertification<'a>) -> Self {
+        self.certification = Some(certification);
+        self
+    }
+
+    pub fn with_summary(mut self, summary: impl Into<Cow<'a, str>>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+}
+
 /// A "belongs to" relationship.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub struct BelongsTo<'a> {
@@ -551,7 +788,54 @@ pub struct BelongsTo<'a> {
     pub volume: Vec<Volume<'a>>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+impl<'a> BelongsTo<'a> {
+    pub fn with_collection(mut self, collection: Collection<'a>) -> Self {
+        self.collection.push(collection);
+        self
+    }
+
+    pub fn with_journal(mut self, journal: Periodical<'a>) -> Self {
+        self.journal.push(journal);
+        self
+    }
+
+    pub fn with_magazine(mut self, magazine: Periodical<'a>) -> Self {
+        self.magazine.push(magazine);
+        self
+    }
+
+    pub fn with_newspaper(mut self, newspaper: Periodical<'a>) -> Self {
+        self.newspaper.push(newspaper);
+        self
+    }
+
+    pub fn with_periodical(mut self, periodical: Periodical<'a>) -> Self {
+        self.periodical.push(periodical);
+        self
+    }
+
+    pub fn with_season(mut self, season: Season<'a>) -> Self {
+        self.season.push(season);
+        self
+    }
+
+    pub fn with_series(mut self, series: Series<'a>) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    pub fn with_story_arc(mut self, story_arc: StoryArc<'a>) -> Self {
+        self.story_arc.push(story_arc);
+        self
+    }
+
+    pub fn with_volume(mut self, volume: Volume<'a>) -> Self {
+        self.volume.push(volume);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Collection<'a> {
     #[serde(borrow)]
@@ -615,7 +899,7 @@ impl<'a> From<Cow<'a, str>> for Collection<'a> {
 /// See the associate [JSON Schema].
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/periodical.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Periodical<'a> {
     #[serde(borrow)]
@@ -692,7 +976,7 @@ impl<'a> From<Cow<'a, str>> for Periodical<'a> {
 /// See the associated [JSON Schema] for more information.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/episode.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Episode<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
@@ -743,7 +1027,7 @@ impl<'a> From<usize> for Episode<'a> {
 /// See the associated [JSON Schema] for more information.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/season.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Season<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
@@ -812,7 +1096,7 @@ impl<'a> From<usize> for Season<'a> {
 /// See the associated [JSON Schema] for more information.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/storyArc.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StoryArc<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
@@ -890,7 +1174,7 @@ impl<'a> From<usize> for StoryArc<'a> {
 /// See the associated [JSON Schema] for more information.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/issue.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Issue<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
@@ -954,7 +1238,7 @@ impl<'a> From<usize> for Issue<'a> {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Chapter<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
@@ -1014,7 +1298,7 @@ impl<'a> From<usize> for Chapter<'a> {
 /// See the associated [JSON Schema] for comparison.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/article.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Article<'a> {
     #[serde(borrow)]
@@ -1136,7 +1420,7 @@ impl<'a> From<Cow<'a, str>> for Article<'a> {
 /// See the associated [JSON Schema] for comparison.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/series.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Series<'a> {
     #[serde(borrow)]
@@ -1249,7 +1533,7 @@ impl<'a> From<Cow<'a, str>> for Series<'a> {
 /// See the associated [JSON Schema] for more information.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/volume.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Volume<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
@@ -1300,7 +1584,7 @@ pub struct Volume<'a> {
 }
 
 impl<'a> Volume<'a> {
-    fn new(position: usize) -> Self {
+    pub fn new(position: usize) -> Self {
         Self {
             position,
 
@@ -1323,7 +1607,7 @@ impl<'a> From<usize> for Volume<'a> {
 }
 
 /// A "contains" relationship.
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub struct Contains<'a> {
@@ -1392,13 +1676,55 @@ pub struct Contains<'a> {
     pub volume: Vec<Volume<'a>>,
 }
 
+impl<'a> Contains<'a> {
+    pub fn with_article(mut self, article: Article<'a>) -> Self {
+        self.article.push(article);
+        self
+    }
+
+    pub fn with_chapter(mut self, chapter: Chapter<'a>) -> Self {
+        self.chapter.push(chapter);
+        self
+    }
+
+    pub fn with_episode(mut self, episode: Episode<'a>) -> Self {
+        self.episode.push(episode);
+        self
+    }
+
+    pub fn with_issue(mut self, issue: Issue<'a>) -> Self {
+        self.issue.push(issue);
+        self
+    }
+
+    pub fn with_season(mut self, season: Season<'a>) -> Self {
+        self.season.push(season);
+        self
+    }
+
+    pub fn with_series(mut self, series: Series<'a>) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    pub fn with_story_arc(mut self, story_arc: StoryArc<'a>) -> Self {
+        self.story_arc.push(story_arc);
+        self
+    }
+
+    pub fn with_volume(mut self, volume: Volume<'a>) -> Self {
+        self.volume.push(volume);
+        self
+    }
+}
+
 /// The subject matter of a publication.
 ///
 /// See [Default Context: Subjects] or the associated [JSON Schema] for more information.
 ///
 /// [Default Context: Subjects]: https://readium.org/webpub-manifest/contexts/default/#subjects
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/subject.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Subject<'a> {
     #[serde(borrow)]
@@ -1429,6 +1755,26 @@ impl<'a> Subject<'a> {
             links: Vec::new(),
         }
     }
+
+    pub fn with_sort_as(mut self, sort_as: impl Into<StringWithAlternates<'a>>) -> Self {
+        self.sort_as = Some(sort_as.into());
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<Cow<'a, str>>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_scheme(mut self, scheme: url::Url) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
 }
 
 impl<'a> From<String> for Subject<'a> {
@@ -1448,7 +1794,7 @@ impl<'a> From<Cow<'a, str>> for Subject<'a> {
 /// See [Default Context: Text and Data Mining] for more information.
 ///
 /// [Default Context: Text and Data Mining]: https://readium.org/webpub-manifest/contexts/default/#text-and-data-mining
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DataMining {
     pub reservation: Reservation,
@@ -1457,7 +1803,21 @@ pub struct DataMining {
     pub policy: Option<url::Url>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+impl DataMining {
+    pub fn new(reservation: Reservation) -> Self {
+        Self {
+            reservation,
+            policy: None,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: url::Url) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum Reservation {
@@ -1470,6 +1830,9 @@ pub enum Reservation {
 /// See the [JSON Schema] for more details.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/language-map.schema.json
+///
+/// Each language has at most one choice, and two maps with the same choices in different
+/// orders are equal.
 #[derive(Clone, Debug)]
 pub struct TaggedStrings {
     choices: Cow<'static, [(Cow<'static, langtag::LangTag>, Cow<'static, str>)]>,
@@ -1483,6 +1846,77 @@ impl TaggedStrings {
             choices: Cow::Borrowed(choices),
         }
     }
+
+    /// Create a set of choices from pairs of language tags and strings.
+    ///
+    /// When a language appears more than once, the last string given for it is kept, in the
+    /// place where the language first appeared.
+    pub fn new(
+        choices: impl IntoIterator<Item = (Cow<'static, langtag::LangTag>, Cow<'static, str>)>,
+    ) -> Self {
+        let mut unique: Vec<(Cow<'static, langtag::LangTag>, Cow<'static, str>)> = vec![];
+
+        for (tag, s) in choices {
+            match unique.iter_mut().find(|(t, _)| *t == tag) {
+                Some((_, existing)) => *existing = s,
+                None => unique.push((tag, s)),
+            }
+        }
+
+        Self {
+            choices: unique.into(),
+        }
+    }
+
+    pub(crate) fn choices(&self) -> &[(Cow<'static, langtag::LangTag>, Cow<'static, str>)] {
+        &self.choices
+    }
+
+    pub fn len(&self) -> usize {
+        self.choices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.choices.is_empty()
+    }
+
+    /// The choice for exactly the given language, ignoring case.
+    pub fn get(&self, language: &langtag::LangTag) -> Option<&str> {
+        self.iter()
+            .find(|(tag, _)| *tag == language)
+            .map(|(_, s)| s)
+    }
+
+    /// Iterate over each language tag and its string, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&langtag::LangTag, &str)> {
+        self.choices
+            .iter()
+            .map(|(tag, s)| (tag.as_ref(), s.as_ref()))
+    }
+}
+
+impl PartialEq for TaggedStrings {
+    fn eq(&self, other: &Self) -> bool {
+        // Both directions are checked, since maps built with from_static could repeat a tag.
+        self.iter().all(|(tag, s)| other.get(tag) == Some(s))
+            && other.iter().all(|(tag, s)| self.get(tag) == Some(s))
+    }
+}
+
+impl Eq for TaggedStrings {}
+
+impl Hash for TaggedStrings {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Hash the choices that get() would find, in the same order regardless of how they
+        // were written.
+        let choices: BTreeMap<_, _> = self
+            .choices
+            .iter()
+            .rev()
+            .map(|(tag, s)| (tag.as_ref(), s.as_ref()))
+            .collect();
+        choices.hash(state);
+    }
 }
 
 macro_rules! tagged_strings {
@@ -1517,12 +1951,28 @@ impl<'de> Deserialize<'de> for TaggedStrings {
     where
         D: Deserializer<'de>,
     {
-        let map = <BTreeMap<langtag::LangTagBuf, String>>::deserialize(deserializer)?;
-        let choices = map
-            .into_iter()
-            .map(|(tag, s)| (Cow::Owned(tag), Cow::Owned(s)))
-            .collect();
-        Ok(Self { choices })
+        struct TaggedStringsVisitor;
+
+        impl<'de> Visitor<'de> for TaggedStringsVisitor {
+            type Value = TaggedStrings;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a map of language tags to strings")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut choices = vec![];
+
+                while let Some((tag, s)) = map.next_entry::<langtag::LangTagBuf, String>()? {
+                    choices.push((Cow::Owned(tag), Cow::Owned(s)));
+                }
+
+                // Keeps the choices in the order they were written.
+                Ok(TaggedStrings::new(choices))
+            }
+        }
+
+        deserializer.deserialize_map(TaggedStringsVisitor)
     }
 }
 
@@ -1531,7 +1981,7 @@ impl<'de> Deserialize<'de> for TaggedStrings {
 /// See the [JSON Schema] for more details.
 ///
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/language-map.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum StringWithAlternates<'a> {
     #[serde(borrow)]
@@ -1621,7 +2071,7 @@ impl<'a> From<Cow<'a, str>> for StringWithAlternates<'a> {
 ///
 /// [Default Context: Contributors]: https://readium.org/webpub-manifest/contexts/default/#contributors
 /// [JSON Schema]: https://readium.org/webpub-manifest/schema/contributor.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Contributor<'a> {
     pub name: StringWithAlternates<'a>,
@@ -1658,6 +2108,11 @@ impl<'a> Contributor<'a> {
             links: Vec::new(),
         }
     }
+
+    pub fn with_role(mut self, role: impl Into<Cow<'a, str>>) -> Self {
+        self.role.push(role.into());
+        self
+    }
 }
 
 impl<'a> From<String> for Contributor<'a> {
@@ -1677,7 +2132,7 @@ impl<'a> From<Cow<'a, str>> for Contributor<'a> {
 /// See the [JSON Schema] for more information.
 ///
 /// [JSON Schema]: https://drafts.opds.io/schema/feed-metadata.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FeedMetadata<'a> {
     #[serde(borrow)]
@@ -1700,8 +2155,8 @@ pub struct FeedMetadata<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none", rename = "@type")]
     pub schema: Option<Cow<'a, str>>,
 
-    #[serde(borrow, skip_serializing_if = "Option::is_none")]
-    pub modified: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<Timestamp>,
 
     #[serde(borrow, skip_serializing_if = "Option::is_none")]
     pub description: Option<Cow<'a, str>>,
@@ -1714,6 +2169,10 @@ pub struct FeedMetadata<'a> {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub number_of_items: Option<usize>,
+
+    /// Catalog-specific metadata, such as the terms of a custom search API.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 impl<'a> FeedMetadata<'a> {
@@ -1730,8 +2189,54 @@ impl<'a> FeedMetadata<'a> {
             items_per_page: None,
             current_page: None,
             number_of_items: None,
+            extensions: Extensions::new(),
         }
     }
+
+    pub fn with_subtitle(mut self, subtitle: impl Into<StringWithAlternates<'a>>) -> Self {
+        self.subtitle.push(subtitle.into());
+        self
+    }
+
+    pub fn with_identifier(mut self, identifier: url::Url) -> Self {
+        self.identifier = Some(identifier);
+        self
+    }
+
+    pub fn with_schema(mut self, schema: impl Into<Cow<'a, str>>) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    pub fn with_modified(mut self, modified: Timestamp) -> Self {
+        self.modified = Some(modified);
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_items_per_page(mut self, items_per_page: usize) -> Self {
+        self.items_per_page = Some(items_per_page);
+        self
+    }
+
+    pub fn with_current_page(mut self, current_page: usize) -> Self {
+        self.current_page = Some(current_page);
+        self
+    }
+
+    pub fn with_number_of_items(mut self, number_of_items: usize) -> Self {
+        self.number_of_items = Some(number_of_items);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
 }
 
 /// Metadata for an OPDS Publication.
@@ -1755,7 +2260,11 @@ pub struct PublicationMetadata<'a> {
     #[serde(borrow, skip_serializing_if = "Option::is_none", rename = "@type")]
     pub schema: Option<Cow<'a, str>>,
 
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_flattened_vec"
+    )]
     pub conforms_to: Vec<url::Url>,
 
     /// The title of a publication.
@@ -1815,16 +2324,16 @@ pub struct PublicationMetadata<'a> {
     pub accessibility: Option<AccessibilityMetadata<'a>>,
 
     /// When this publication was last modified.
-    #[serde(borrow, skip_serializing_if = "Option::is_none")]
-    pub modified: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<Timestamp>,
 
     /// When this publication was published.
     ///
     /// See [Default Context: Publication Date] for more information.
     ///
     /// [Default Context: Publication Date]: https://readium.org/webpub-manifest/contexts/default/#publication-date
-    #[serde(borrow, skip_serializing_if = "Option::is_none")]
-    pub published: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<Timestamp>,
 
     /// Expected language of the linked resource.
     ///
@@ -1864,7 +2373,7 @@ pub struct PublicationMetadata<'a> {
 
     /// The duration in seconds of this publication.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub duration: Option<usize>,
+    pub duration: Option<f64>,
 
     /// Whether or not this is an abridged edition of this publication.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -2014,6 +2523,96 @@ pub struct PublicationMetadata<'a> {
         deserialize_with = "deserialize_flattened_vec_stringy"
     )]
     pub imprint: Vec<Contributor<'a>>,
+
+    /// Metadata from other vocabularies, like `http://palaceproject.io/terms/timeTracking`.
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+/// Metadata is compared with its `duration` taken bit for bit, so that all metadata is equal to
+/// itself, as [Eq] and [Hash] require.
+impl PartialEq for PublicationMetadata<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let bits = |f: Option<f64>| f.map(f64::to_bits);
+
+        self.schema == other.schema
+            && self.conforms_to == other.conforms_to
+            && self.title == other.title
+            && self.sort_as == other.sort_as
+            && self.subtitle == other.subtitle
+            && self.author == other.author
+            && self.description == other.description
+            && self.identifier == other.identifier
+            && self.alt_identifier == other.alt_identifier
+            && self.accessibility == other.accessibility
+            && self.modified == other.modified
+            && self.published == other.published
+            && self.language == other.language
+            && self.subject == other.subject
+            && self.layout == other.layout
+            && self.reading_progression == other.reading_progression
+            && bits(self.duration) == bits(other.duration)
+            && self.abridged == other.abridged
+            && self.number_of_pages == other.number_of_pages
+            && self.belongs_to == other.belongs_to
+            && self.contains == other.contains
+            && self.tdm == other.tdm
+            && self.translator == other.translator
+            && self.editor == other.editor
+            && self.artist == other.artist
+            && self.illustrator == other.illustrator
+            && self.letterer == other.letterer
+            && self.penciler == other.penciler
+            && self.colorist == other.colorist
+            && self.inker == other.inker
+            && self.narrator == other.narrator
+            && self.contributor == other.contributor
+            && self.publisher == other.publisher
+            && self.imprint == other.imprint
+            && self.extensions == other.extensions
+    }
+}
+
+impl Eq for PublicationMetadata<'_> {}
+
+impl Hash for PublicationMetadata<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.schema.hash(state);
+        self.conforms_to.hash(state);
+        self.title.hash(state);
+        self.sort_as.hash(state);
+        self.subtitle.hash(state);
+        self.author.hash(state);
+        self.description.hash(state);
+        self.identifier.hash(state);
+        self.alt_identifier.hash(state);
+        self.accessibility.hash(state);
+        self.modified.hash(state);
+        self.published.hash(state);
+        self.language.hash(state);
+        self.subject.hash(state);
+        self.layout.hash(state);
+        self.reading_progression.hash(state);
+        self.duration.map(f64::to_bits).hash(state);
+        self.abridged.hash(state);
+        self.number_of_pages.hash(state);
+        self.belongs_to.hash(state);
+        self.contains.hash(state);
+        self.tdm.hash(state);
+        self.translator.hash(state);
+        self.editor.hash(state);
+        self.artist.hash(state);
+        self.illustrator.hash(state);
+        self.letterer.hash(state);
+        self.penciler.hash(state);
+        self.colorist.hash(state);
+        self.inker.hash(state);
+        self.narrator.hash(state);
+        self.contributor.hash(state);
+        self.publisher.hash(state);
+        self.imprint.hash(state);
+        self.extensions.hash(state);
+    }
 }
 
 impl<'a> PublicationMetadata<'a> {
@@ -2056,8 +2655,179 @@ impl<'a> PublicationMetadata<'a> {
             belongs_to: None,
             contains: None,
             tdm: None,
+            extensions: Extensions::new(),
         }
     }
+
+    pub fn with_schema(mut self, schema: impl Into<Cow<'a, str>>) -> Self {
+        self.schema = Some(schema.into());
+        self
+    }
+
+    pub fn with_conforms_to(mut self, conforms_to: url::Url) -> Self {
+        self.conforms_to.push(conforms_to);
+        self
+    }
+
+    pub fn with_sort_as(mut self, sort_as: impl Into<StringWithAlternates<'a>>) -> Self {
+        self.sort_as = Some(sort_as.into());
+        self
+    }
+
+    pub fn with_subtitle(mut self, subtitle: impl Into<StringWithAlternates<'a>>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<Cow<'a, str>>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_identifier(mut self, identifier: url::Url) -> Self {
+        self.identifier = Some(identifier);
+        self
+    }
+
+    pub fn with_alt_identifier(mut self, alt_identifier: AltIdentifier<'a>) -> Self {
+        self.alt_identifier.push(alt_identifier);
+        self
+    }
+
+    pub fn with_accessibility(mut self, accessibility: AccessibilityMetadata<'a>) -> Self {
+        self.accessibility = Some(accessibility);
+        self
+    }
+
+    pub fn with_modified(mut self, modified: Timestamp) -> Self {
+        self.modified = Some(modified);
+        self
+    }
+
+    pub fn with_published(mut self, published: Timestamp) -> Self {
+        self.published = Some(published);
+        self
+    }
+
+    pub fn with_language(mut self, language: Cow<'a, langtag::LangTag>) -> Self {
+        self.language.push(language);
+        self
+    }
+
+    pub fn with_subject(mut self, subject: Subject<'a>) -> Self {
+        self.subject.push(subject);
+        self
+    }
+
+    pub fn with_layout(mut self, layout: PublicationLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    pub fn with_reading_progression(mut self, reading_progression: ReadingProgression) -> Self {
+        self.reading_progression = Some(reading_progression);
+        self
+    }
+
+    pub fn with_duration(mut self, duration: f64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn with_abridged(mut self, abridged: bool) -> Self {
+        self.abridged = Some(abridged);
+        self
+    }
+
+    pub fn with_number_of_pages(mut self, number_of_pages: usize) -> Self {
+        self.number_of_pages = Some(number_of_pages);
+        self
+    }
+
+    pub fn with_belongs_to(mut self, belongs_to: BelongsTo<'a>) -> Self {
+        self.belongs_to = Some(belongs_to);
+        self
+    }
+
+    pub fn with_contains(mut self, contains: Contains<'a>) -> Self {
+        self.contains = Some(contains);
+        self
+    }
+
+    pub fn with_tdm(mut self, tdm: DataMining) -> Self {
+        self.tdm = Some(tdm);
+        self
+    }
+
+    pub fn with_author(mut self, author: Contributor<'a>) -> Self {
+        self.author.push(author);
+        self
+    }
+
+    pub fn with_translator(mut self, translator: Contributor<'a>) -> Self {
+        self.translator.push(translator);
+        self
+    }
+
+    pub fn with_editor(mut self, editor: Contributor<'a>) -> Self {
+        self.editor.push(editor);
+        self
+    }
+
+    pub fn with_artist(mut self, artist: Contributor<'a>) -> Self {
+        self.artist.push(artist);
+        self
+    }
+
+    pub fn with_illustrator(mut self, illustrator: Contributor<'a>) -> Self {
+        self.illustrator.push(illustrator);
+        self
+    }
+
+    pub fn with_letterer(mut self, letterer: Contributor<'a>) -> Self {
+        self.letterer.push(letterer);
+        self
+    }
+
+    pub fn with_penciler(mut self, penciler: Contributor<'a>) -> Self {
+        self.penciler.push(penciler);
+        self
+    }
+
+    pub fn with_colorist(mut self, colorist: Contributor<'a>) -> Self {
+        self.colorist.push(colorist);
+        self
+    }
+
+    pub fn with_inker(mut self, inker: Contributor<'a>) -> Self {
+        self.inker.push(inker);
+        self
+    }
+
+    pub fn with_narrator(mut self, narrator: Contributor<'a>) -> Self {
+        self.narrator.push(narrator);
+        self
+    }
+
+    pub fn with_contributor(mut self, contributor: Contributor<'a>) -> Self {
+        self.contributor.push(contributor);
+        self
+    }
+
+    pub fn with_publisher(mut self, publisher: Contributor<'a>) -> Self {
+        self.publisher.push(publisher);
+        self
+    }
+
+    pub fn with_imprint(mut self, imprint: Contributor<'a>) -> Self {
+        self.imprint.push(imprint);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
 }
 
 /// Properties of an OPDS link object.
@@ -2065,7 +2835,7 @@ impl<'a> PublicationMetadata<'a> {
 /// See [JSON Schema].
 ///
 /// [JSON Schema]: https://drafts.opds.io/schema/properties.schema.json
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub struct LinkProperties<'a> {
@@ -2079,8 +2849,8 @@ pub struct LinkProperties<'a> {
     pub page: Option<PageDisplay>,
 
     /// Indicates the availability of a given resource.
-    #[serde(borrow, skip_serializing_if = "Option::is_none")]
-    pub availability: Option<Availability<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability: Option<Availability>,
 
     /// The price of the publication (tied to its acquisition link).
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -2097,6 +2867,10 @@ pub struct LinkProperties<'a> {
     /// Library-specific feature that contains information about the copies that a library has acquired.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub copies: Option<Copies>,
+
+    /// Properties defined elsewhere, such as Readium LCP's `lcp_hashed_passphrase`.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 impl<'a> LinkProperties<'a> {
@@ -2109,6 +2883,158 @@ impl<'a> LinkProperties<'a> {
             holds: None,
             copies: None,
             availability: None,
-        } if indirect_acquisition.is_empty())
+            extensions,
+        } if indirect_acquisition.is_empty() && extensions.is_empty())
+    }
+
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn with_page(mut self, page: PageDisplay) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    pub fn with_availability(mut self, availability: Availability) -> Self {
+        self.availability = Some(availability);
+        self
+    }
+
+    pub fn with_price(mut self, price: Price) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn with_indirect_acquisition(mut self, indirect_acquisition: Acquisition<'a>) -> Self {
+        self.indirect_acquisition.push(indirect_acquisition);
+        self
+    }
+
+    pub fn with_holds(mut self, holds: Holds) -> Self {
+        self.holds = Some(holds);
+        self
+    }
+
+    pub fn with_copies(mut self, copies: Copies) -> Self {
+        self.copies = Some(copies);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extensions.insert(key, value);
+        self
     }
 }
+
+/// Implement the `with_*` methods shared by named and identified objects, such as [Collection]
+/// and [Contributor], along with either `with_position` or `with_name` for the objects where
+/// those are optional.
+macro_rules! identified {
+    ($($ty: ident),*) => {$(
+        impl<'a> $ty<'a> {
+            pub fn with_sort_as(mut self, sort_as: impl Into<StringWithAlternates<'a>>) -> Self {
+                self.sort_as = Some(sort_as.into());
+                self
+            }
+
+            pub fn with_identifier(mut self, identifier: Identifier) -> Self {
+                self.identifier = Some(identifier);
+                self
+            }
+
+            pub fn with_alt_identifier(mut self, alt_identifier: AltIdentifier<'a>) -> Self {
+                self.alt_identifier.push(alt_identifier);
+                self
+            }
+
+            pub fn with_link(mut self, link: Link<'a>) -> Self {
+                self.links.push(link);
+                self
+            }
+        }
+    )*};
+    ($($ty: ident),*; with_position) => {$(
+        identified!($ty);
+
+        impl<'a> $ty<'a> {
+            pub fn with_position(mut self, position: usize) -> Self {
+                self.position = Some(position);
+                self
+            }
+        }
+    )*};
+    ($($ty: ident),*; with_name) => {$(
+        identified!($ty);
+
+        impl<'a> $ty<'a> {
+            pub fn with_name(mut self, name: impl Into<StringWithAlternates<'a>>) -> Self {
+                self.name = Some(name.into());
+                self
+            }
+        }
+    )*};
+}
+
+identified!(Contributor);
+identified!(Article, Collection, Periodical, Series; with_position);
+identified!(Chapter, Episode, Issue, Season, StoryArc, Volume; with_name);
+
+into_owned!(Acquisition { mime, child; });
+into_owned!(AltIdentifier { value; scheme });
+into_owned!(AccessbilityCertification { certified_by, credential, report; });
+into_owned!(AccessibilityMetadata {
+    certification, summary;
+    conforms_to, exemption, access_mode, feature, hazard
+});
+into_owned!(BelongsTo {
+    collection, journal, magazine, newspaper, periodical, season, series, story_arc, volume;
+});
+into_owned!(Collection { name, sort_as, alt_identifier, links; identifier, position });
+into_owned!(Periodical {
+    name, sort_as, alt_identifier, links, issue, volume;
+    identifier, position
+});
+into_owned!(Episode { name, sort_as, alt_identifier, links; identifier, position });
+into_owned!(Season {
+    name, sort_as, alt_identifier, links, article, chapter;
+    identifier, position
+});
+into_owned!(StoryArc {
+    name, sort_as, alt_identifier, links, chapter, issue, episode;
+    identifier, position
+});
+into_owned!(Issue { name, sort_as, alt_identifier, links, article, chapter; identifier, position });
+into_owned!(Chapter { name, sort_as, alt_identifier, links, series; identifier, position });
+into_owned!(Article {
+    name, sort_as, alt_identifier, author, translator, editor, artist, illustrator, contributor,
+    description, links;
+    identifier, number_of_pages, position
+});
+into_owned!(Series {
+    name, sort_as, alt_identifier, links, chapter, episode, issue, season, story_arc, volume;
+    identifier, position
+});
+into_owned!(Volume {
+    name, sort_as, alt_identifier, links, chapter, issue, story_arc;
+    identifier, position
+});
+into_owned!(Contains { article, chapter, episode, issue, season, series, story_arc, volume; });
+into_owned!(Subject { name, sort_as, code, links; scheme });
+into_owned!(Contributor { name, sort_as, alt_identifier, role, links; identifier });
+into_owned!(FeedMetadata {
+    title, subtitle, schema, description;
+    identifier, modified, items_per_page, current_page, number_of_items, extensions
+});
+into_owned!(PublicationMetadata {
+    schema, title, sort_as, subtitle, author, description, alt_identifier, accessibility,
+    language, subject, belongs_to, contains, translator, editor, artist, illustrator, letterer,
+    penciler, colorist, inker, narrator, contributor, publisher, imprint;
+    conforms_to, identifier, modified, published, layout, reading_progression, duration,
+    abridged, number_of_pages, tdm, extensions
+});
+into_owned!(LinkProperties {
+    indirect_acquisition;
+    count, page, availability, price, holds, copies, extensions
+});
@@ -23,6 +23,53 @@
 //!   links and publications meant to make reading a feed easier. They are
 //!   represented by the [FeedGroup] type, and stored in [Feed::groups].
 //!
+//! Objects are created with a constructor that takes their required fields, such as
+//! [PublicationMetadata::new], and filled in with `with_*` methods:
+//!
+//! ```
+//! use std::borrow::Cow;
+//! use opds::v2_0::{Feed, Link, Publication};
+//! use opds::v2_0::metadata::{Contributor, PublicationMetadata, Relation};
+//!
+//! let metadata = PublicationMetadata::new("Moby Dick")
+//!     .with_author(Contributor::new("Herman Melville"));
+//! let publication = Publication::new(metadata)
+//!     .with_link(Link::new(Cow::Borrowed("/books/1.epub"), None));
+//! let feed = Feed::new("Example")
+//!     .with_link(Link::new(Cow::Borrowed("/"), None).with_rel(Relation::Myself))
+//!     .with_publication(publication);
+//! ```
+//!
+//! Complete publication manifests, which also describe the publication's content, are
+//! represented by the [manifest::Manifest] type. Manifests that follow the audiobook profile
+//! can be checked and worked with using [audiobook::Audiobook], and likewise for comics and
+//! other visual narratives using [divina::Divina].
+//!
+//! Catalogs that require the user to sign in describe how to do so with an
+//! [authentication::AuthenticationDocument].
+//!
+//! Fields that are not modeled by this crate are kept in the `extensions` map of the object
+//! they appear in, so that they are not lost when re-serializing a feed. See [extensions] for
+//! more information.
+//!
+//! Feeds can be checked against the rules of the specification that go beyond the shape of
+//! the JSON using [Feed::validate]. See [validate] for more information.
+//!
+//! Feeds gathered from many different catalogs can be parsed with [Feed::from_json_lenient],
+//! which removes malformed parts of the feed instead of failing. See [lenient] for more
+//! information.
+//!
+//! Titles and other strings given in several languages can be shown in the reader's language
+//! using [StringWithAlternates::resolve]. See [language] for more information.
+//! Translations of common navigation and facet labels are provided by [catalog::Catalog].
+//!
+//! Parsed objects borrow from the JSON they were parsed from, and can be detached from it with
+//! [owned::IntoOwned].
+//!
+//! Objects can be compared with `==` and stored in hash sets. Feeds that differ only in how
+//! they are written, such as the order of their links, can be compared with
+//! [normalize::NormalizedEq].
+//!
 //! [serde_json]: https://docs.rs/serde_json/latest/serde_json/
 //! [Section 2: Collections]: https://drafts.opds.io/opds-2.0.html#2-collections
 //! [opds-spec-navigation]: https://drafts.opds.io/opds-2.0.html#21-navigation
@@ -35,9 +82,28 @@ use std::borrow::Cow;
 use serde::{Deserialize, Serialize};
 
 use crate::helpers::*;
+use crate::mime::MediaType;
+use crate::template::{self, UriTemplate, Variables};
+use crate::v2_0::extensions::Extensions;
 use crate::v2_0::metadata::*;
-
+use crate::v2_0::owned::into_owned;
+use crate::v2_0::timestamp::Timestamp;
+
+pub mod audiobook;
+pub mod authentication;
+pub mod catalog;
+pub mod divina;
+pub mod extensions;
+pub mod identifier;
+pub mod language;
+pub mod lenient;
+pub mod manifest;
 pub mod metadata;
+pub mod normalize;
+pub mod owned;
+pub mod price;
+pub mod timestamp;
+pub mod validate;
 
 /// An OPDS link object.
 ///
@@ -119,6 +185,10 @@ pub struct Link<'a> {
     /// given collection role.
     #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<Link<'a>>,
+
+    /// Link attributes from other specifications or vendors.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 impl<'a> Link<'a> {
@@ -139,32 +209,160 @@ impl<'a> Link<'a> {
             language: vec![],
             alternate: vec![],
             children: vec![],
+            extensions: Extensions::new(),
         }
     }
 
     pub fn template(href: Cow<'a, str>, mime: Option<Cow<'a, str>>) -> Self {
         Link {
-            href: Some(href),
             templated: true,
-            mime,
-
-            title: None,
-            rel: vec![],
-            properties: LinkProperties::default(),
-            height: None,
-            width: None,
-            size: None,
-            bitrate: None,
-            duration: None,
-            language: vec![],
-            alternate: vec![],
-            children: vec![],
+            ..Link::new(href, mime)
         }
     }
 
+    pub fn with_title(mut self, title: impl Into<Cow<'a, str>>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_rel(mut self, rel: Relation) -> Self {
+        self.rel.push(rel);
+        self
+    }
+
+    pub fn with_properties(mut self, properties: LinkProperties<'a>) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    pub fn with_height(mut self, height: usize) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn with_size(mut self, size: usize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn with_bitrate(mut self, bitrate: f64) -> Self {
+        self.bitrate = Some(bitrate);
+        self
+    }
+
+    pub fn with_duration(mut self, duration: f64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn with_language(mut self, language: Cow<'a, langtag::LangTag>) -> Self {
+        self.language.push(language);
+        self
+    }
+
+    pub fn with_alternate(mut self, link: Link<'a>) -> Self {
+        self.alternate.push(link);
+        self
+    }
+
+    pub fn with_child(mut self, link: Link<'a>) -> Self {
+        self.children.push(link);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
+
     pub fn get_acquisition(&self) -> Option<AcquisitionKind> {
         self.rel.iter().flat_map(|rel| rel.as_acquisition()).next()
     }
+
+    /// The parsed [MediaType] of the linked resource, if it has a valid one.
+    pub fn media_type(&self) -> Option<MediaType<'_>> {
+        self.mime
+            .as_deref()
+            .and_then(|mime| MediaType::parse(mime).ok())
+    }
+
+    /// The names of the variables used by this link's URI template.
+    ///
+    /// Links that are not [templated][Link::templated] have no variables.
+    pub fn template_variables(&self) -> Result<Vec<&str>, template::Error> {
+        match self.href.as_deref() {
+            Some(href) if self.templated => Ok(UriTemplate::parse(href)?.variables()),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Expand this link's URI template using the given variables, producing a link that is
+    /// no longer templated.
+    ///
+    /// Links that are not [templated][Link::templated] are returned unchanged. See
+    /// [crate::template] for more information.
+    pub fn expand(&self, variables: &Variables) -> Result<Link<'a>, template::Error> {
+        let mut link = self.clone();
+
+        if let Some(href) = self.href.as_deref().filter(|_| self.templated) {
+            let href = UriTemplate::parse(href)?.expand(variables);
+            link.href = Some(Cow::Owned(href));
+            link.templated = false;
+        }
+
+        Ok(link)
+    }
+}
+
+/// Links are compared with their `bitrate` and `duration` taken bit for bit, so that every
+/// link is equal to itself, as [Eq] and [Hash] require.
+impl PartialEq for Link<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let bits = |f: Option<f64>| f.map(f64::to_bits);
+
+        self.title == other.title
+            && self.href == other.href
+            && self.templated == other.templated
+            && self.mime == other.mime
+            && self.rel == other.rel
+            && self.properties == other.properties
+            && self.height == other.height
+            && self.width == other.width
+            && self.size == other.size
+            && bits(self.bitrate) == bits(other.bitrate)
+            && bits(self.duration) == bits(other.duration)
+            && self.language == other.language
+            && self.alternate == other.alternate
+            && self.children == other.children
+            && self.extensions == other.extensions
+    }
+}
+
+impl Eq for Link<'_> {}
+
+impl std::hash::Hash for Link<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.title.hash(state);
+        self.href.hash(state);
+        self.templated.hash(state);
+        self.mime.hash(state);
+        self.rel.hash(state);
+        self.properties.hash(state);
+        self.height.hash(state);
+        self.width.hash(state);
+        self.size.hash(state);
+        self.bitrate.map(f64::to_bits).hash(state);
+        self.duration.map(f64::to_bits).hash(state);
+        self.language.hash(state);
+        self.alternate.hash(state);
+        self.children.hash(state);
+        self.extensions.hash(state);
+    }
 }
 
 /// An OPDS facet for helping to navigate a collection by viewing a subset or by providing
@@ -173,7 +371,7 @@ impl<'a> Link<'a> {
 /// See [Section 2.4: Facets][opds-spec-facets] for more information.
 ///
 /// [opds-spec-facets]: https://drafts.opds.io/opds-2.0.html#24-facets
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct Facet<'a> {
     #[serde(borrow)]
@@ -191,6 +389,11 @@ impl<'a> Facet<'a> {
             links: vec![],
         }
     }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
 }
 
 /// An OPDS Publication object.
@@ -199,7 +402,7 @@ impl<'a> Facet<'a> {
 ///
 /// [Section 5: Publications]: https://drafts.opds.io/opds-2.0#5-publications
 /// [JSON Schema]: https://drafts.opds.io/schema/publication.schema.json
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub struct Publication<'a> {
@@ -215,10 +418,8 @@ pub struct Publication<'a> {
     /// to allow clients to support a standard subset of image types:
     ///
     /// - `image/jpeg`
-    /// - `image/webp`
     /// - `image/avif`
     /// - `image/png`
-    /// - `image/jxl`
     /// - `image/gif`
     ///
     /// See [Section 2.3: Images][opds-spec-images] for more information.
@@ -226,6 +427,36 @@ pub struct Publication<'a> {
     /// [opds-spec-images]: https://drafts.opds.io/opds-2.0#23-images
     #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
     pub images: Vec<Link<'a>>,
+
+    /// Vendor-specific fields, and collections with roles that aren't modeled here.
+    #[serde(flatten)]
+    pub extensions: Extensions,
+}
+
+impl<'a> Publication<'a> {
+    pub fn new(metadata: PublicationMetadata<'a>) -> Self {
+        Self {
+            metadata,
+            links: vec![],
+            images: vec![],
+            extensions: Extensions::new(),
+        }
+    }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    pub fn with_image(mut self, link: Link<'a>) -> Self {
+        self.images.push(link);
+        self
+    }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
 }
 
 /// A group within an OPDS feed.
@@ -233,7 +464,7 @@ pub struct Publication<'a> {
 /// See [Section 2.5: Groups][opds-spec-groups] for more information.
 ///
 /// [opds-spec-groups]: https://drafts.opds.io/opds-2.0.html#25-groups
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct FeedGroup<'a> {
     #[serde(borrow)]
@@ -269,10 +500,25 @@ impl<'a> FeedGroup<'a> {
             publications: vec![],
         }
     }
+
+    pub fn with_link(mut self, link: Link<'a>) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    pub fn with_navigation(mut self, link: Link<'a>) -> Self {
+        self.navigation.push(link);
+        self
+    }
+
+    pub fn with_publication(mut self, publication: Publication<'a>) -> Self {
+        self.publications.push(publication);
+        self
+    }
 }
 
 /// The main OPDS feed.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct Feed<'a> {
     #[serde(borrow)]
@@ -313,6 +559,10 @@ pub struct Feed<'a> {
     /// [opds-spec-groups]: https://drafts.opds.io/opds-2.0.html#25-groups
     #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
     pub groups: Vec<FeedGroup<'a>>,
+
+    /// Fields and collections beyond those defined by OPDS 2.0.
+    #[serde(flatten)]
+    pub extensions: Extensions,
 }
 
 impl<'a> Feed<'a> {
@@ -326,6 +576,7 @@ impl<'a> Feed<'a> {
             facets: vec![],
             publications: vec![],
             groups: vec![],
+            extensions: Extensions::new(),
         }
     }
 
@@ -353,8 +604,22 @@ impl<'a> Feed<'a> {
         self.groups.push(group);
         self
     }
+
+    pub fn with_extension(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extensions.insert(key, value);
+        self
+    }
 }
 
+into_owned!(Link {
+    title, href, mime, properties, language, alternate, children;
+    templated, rel, height, width, size, bitrate, duration, extensions
+});
+into_owned!(Facet { metadata, links; });
+into_owned!(Publication { metadata, links, images; extensions });
+into_owned!(FeedGroup { metadata, links, navigation, publications; });
+into_owned!(Feed { metadata, links, navigation, facets, publications, groups; extensions });
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -418,4 +683,215 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_builders() {
+        use crate::v2_0::price::{Amount, Currency};
+
+        let acquisition = Link::new(
+            Cow::Borrowed("/books/1.epub"),
+            Some(crate::mime::APPLICATION_EPUB_ZIP.into()),
+        )
+        .with_rel(Relation::Acquisition(AcquisitionKind::Buy))
+        .with_properties(
+            LinkProperties::default()
+                .with_price(Price::new(Amount::new(1099, 2), Currency::USD))
+                .with_availability(Availability::new(AvailabilityState::Available))
+                .with_copies(Copies::default().with_total(3).with_available(1)),
+        );
+
+        let metadata = PublicationMetadata::new("Moby Dick")
+            .with_author(Contributor::new("Herman Melville").with_sort_as("Melville, Herman"))
+            .with_language(Cow::Borrowed(langtag::langtag!("en")))
+            .with_belongs_to(
+                BelongsTo::default().with_series(Series::new("Classics").with_position(4)),
+            )
+            .with_number_of_pages(635);
+
+        let publication = Publication::new(metadata)
+            .with_link(acquisition)
+            .with_image(Link::new(Cow::Borrowed("/covers/1.jpg"), None).with_width(600));
+
+        let feed = Feed::new("Example")
+            .with_link(Link::new(Cow::Borrowed("/"), None).with_rel(Relation::Myself))
+            .with_publication(publication);
+
+        assert_eq!(
+            serde_json::to_value(&feed).unwrap(),
+            serde_json::json!({
+                "metadata": {"title": "Example"},
+                "links": [{"href": "/", "rel": "self"}],
+                "publications": [{
+                    "metadata": {
+                        "title": "Moby Dick",
+                        "author": [{"name": "Herman Melville", "sortAs": "Melville, Herman"}],
+                        "language": ["en"],
+                        "belongsTo": {"series": [{"name": "Classics", "position": 4}]},
+                        "numberOfPages": 635,
+                    },
+                    "links": [{
+                        "href": "/books/1.epub",
+                        "type": "application/epub+zip",
+                        "rel": "http://opds-spec.org/acquisition/buy",
+                        "properties": {
+                            "price": {"value": 10.99, "currency": "USD"},
+                            "availability": {"state": "available"},
+                            "copies": {"total": 3, "available": 1},
+                        },
+                    }],
+                    "images": [{"href": "/covers/1.jpg", "width": 600}],
+                }],
+            })
+        );
+
+        let template = Link::template(Cow::Borrowed("/search{?query}"), None);
+        assert!(template.templated);
+        assert_eq!(template.href.as_deref(), Some("/search{?query}"));
+    }
+
+    #[test]
+    fn test_feed_equality() {
+        for prefix in get_prefixes("test-feed") {
+            let (json_in, json_exp) = read_files(&prefix);
+
+            let feed_in: Feed<'_> = serde_json::from_str(&json_in).expect("can parse input");
+            let feed_exp: Feed<'_> = serde_json::from_str(&json_exp).expect("can parse output");
+            assert_eq!(
+                feed_in, feed_exp,
+                "{prefix} input equals its expected output"
+            );
+
+            let links: std::collections::HashSet<_> =
+                feed_in.links.iter().chain(&feed_exp.links).collect();
+            assert_eq!(
+                links.len(),
+                feed_in.links.len(),
+                "{prefix} links are deduplicated"
+            );
+        }
+
+        let a: Link<'_> = serde_json::from_str(r#"{"href": "/a", "x": 1, "y": 2}"#).unwrap();
+        let b: Link<'_> = serde_json::from_str(r#"{"y": 2, "href": "/a", "x": 1}"#).unwrap();
+        let c: Link<'_> = serde_json::from_str(r#"{"href": "/a", "bitrate": 64.0}"#).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let a: StringWithAlternates<'_> =
+            serde_json::from_str(r#"{"en": "A", "fr": "B"}"#).unwrap();
+        let b = StringWithAlternates::Variants(TaggedStrings::new([
+            (Cow::Borrowed(langtag::langtag!("fr")), Cow::Borrowed("B")),
+            (Cow::Borrowed(langtag::langtag!("EN")), Cow::Borrowed("A")),
+        ]));
+        let titles: std::collections::HashSet<_> = [&a, &b].into_iter().collect();
+        assert_eq!(a, b);
+        assert_eq!(titles.len(), 1);
+
+        // Repeated languages keep their last string.
+        let en = || Cow::Borrowed(langtag::langtag!("en"));
+        let fr = || Cow::Borrowed(langtag::langtag!("fr"));
+        let x = TaggedStrings::new([(en(), Cow::Borrowed("a")), (en(), Cow::Borrowed("a"))]);
+        let y = TaggedStrings::new([(en(), Cow::Borrowed("a")), (fr(), Cow::Borrowed("c"))]);
+        let z = TaggedStrings::new([(en(), Cow::Borrowed("b")), (en(), Cow::Borrowed("a"))]);
+        assert_eq!(x.len(), 1);
+        assert_ne!(x, y);
+        assert_ne!(y, x);
+        assert_eq!(x, z);
+        assert_eq!(z.get(langtag::langtag!("en")), Some("a"));
+
+        let titles: std::collections::HashSet<_> = [&x, &y, &z].into_iter().collect();
+        assert_eq!(titles.len(), 2);
+    }
+
+    #[test]
+    fn test_manifest() {
+        for prefix in get_prefixes("test-manifest") {
+            let (json_in, json_exp) = read_files(&prefix);
+
+            let manifest: manifest::Manifest<'_> = serde_json::from_str(&json_in)
+                .with_context(|| format!("can parse {prefix:?} input as a Manifest"))
+                .expect("can parse manifest");
+
+            let json_out = serde_json::to_string_pretty(&manifest)
+                .with_context(|| format!("can serialize {prefix:?} input as a Manifest"))
+                .expect("can serialize manifest");
+
+            pretty_assertions::assert_eq!(
+                json_out.trim_end(),
+                json_exp.trim_end(),
+                "{prefix} input matches expected output file after parsing"
+            );
+        }
+    }
+
+    #[test]
+    fn test_manifest_from_publication() {
+        let json = r#"{
+            "metadata": {"title": "Moby-Dick"},
+            "links": [{"rel": "self", "href": "https://example.com/manifest.json"}],
+            "readingOrder": [{"href": "https://example.com/c001.html", "type": "text/html"}],
+            "toc": [{"href": "https://example.com/c001.html", "title": "Chapter 1"}],
+            "x-extras": [{"href": "https://example.com/map.jpg"}],
+            "x-vendor": "bar"
+        }"#;
+        let publication: Publication<'_> = serde_json::from_str(json).expect("can parse");
+        let manifest = manifest::Manifest::from(publication);
+
+        assert_eq!(
+            manifest.metadata.title,
+            StringWithAlternates::from("Moby-Dick")
+        );
+        assert_eq!(manifest.links.len(), 1);
+        assert_eq!(manifest.reading_order.len(), 1);
+        assert_eq!(manifest.toc.len(), 1);
+        assert_eq!(
+            manifest.subcollections.keys().collect::<Vec<_>>(),
+            ["x-extras"]
+        );
+        assert_eq!(manifest.extensions.keys().collect::<Vec<_>>(), ["x-vendor"]);
+
+        let json = r#"{"metadata": {"title": "Moby-Dick"}, "links": [], "toc": 5}"#;
+        let publication: Publication<'_> = serde_json::from_str(json).expect("can parse");
+        let manifest = manifest::Manifest::from(publication);
+        assert!(manifest.toc.is_empty());
+        assert_eq!(manifest.extensions.get("toc"), Some(&serde_json::json!(5)));
+    }
+
+    #[test]
+    fn test_fractional_duration() {
+        let json = r#"{"metadata":{"title":"Moby Dick","duration":1371.205},"links":[],"readingOrder":[]}"#;
+        let manifest: manifest::Manifest<'_> =
+            serde_json::from_str(json).expect("can parse manifest");
+        assert_eq!(manifest.metadata.duration, Some(1371.205));
+
+        let json_out = serde_json::to_string(&manifest).expect("can serialize manifest");
+        assert!(json_out.contains(r#""duration":1371.205"#));
+    }
+
+    #[test]
+    fn test_authentication() {
+        for prefix in get_prefixes("test-auth") {
+            let (json_in, json_exp) = read_files(&prefix);
+
+            let doc: authentication::AuthenticationDocument<'_> = serde_json::from_str(&json_in)
+                .with_context(|| format!("can parse {prefix:?} input as an AuthenticationDocument"))
+                .expect("can parse authentication document");
+
+            let json_out = serde_json::to_string_pretty(&doc)
+                .with_context(|| {
+                    format!("can serialize {prefix:?} input as an AuthenticationDocument")
+                })
+                .expect("can serialize authentication document");
+
+            pretty_assertions::assert_eq!(
+                json_out.trim_end(),
+                json_exp.trim_end(),
+                "{prefix} input matches expected output file after parsing"
+            );
+
+            // The generated document should parse back into the same document.
+            let reparsed: authentication::AuthenticationDocument<'_> =
+                serde_json::from_str(&json_out).expect("can parse generated document");
+            assert_eq!(serde_json::to_string_pretty(&reparsed).unwrap(), json_out);
+        }
+    }
 }
@@ -0,0 +1,127 @@
+//! Support for comparing objects while ignoring differences that don't change their meaning
+//!
+//! The [PartialEq] implementations of the types in this crate compare every field exactly,
+//! apart from treating [Extensions] and [TaggedStrings] as unordered maps. [NormalizedEq] is
+//! a looser comparison for checking whether two feeds say the same thing, which also ignores:
+//!
+//! - Whether a value is written as a single value or an array with just that value, such as
+//!   `"rel": "next"` and `"rel": ["next"]`, including within [Extensions].
+//! - The order of [Link]s within each array of links, such as [Feed::links] or
+//!   [Publication::images].
+use serde::Serialize;
+use serde_json::Value;
+
+use super::*;
+use crate::v2_0::authentication::AuthenticationDocument;
+use crate::v2_0::manifest::Manifest;
+
+/// Comparison of two objects after normalizing their JSON representation.
+///
+/// See [crate::v2_0::normalize] for the differences that are ignored.
+pub trait NormalizedEq: Serialize {
+    fn normalized_eq(&self, other: &Self) -> bool {
+        match (serde_json::to_value(self), serde_json::to_value(other)) {
+            (Ok(a), Ok(b)) => normalize(a) == normalize(b),
+            _ => false,
+        }
+    }
+}
+
+impl NormalizedEq for AuthenticationDocument<'_> {}
+impl NormalizedEq for Contributor<'_> {}
+impl NormalizedEq for Facet<'_> {}
+impl NormalizedEq for Feed<'_> {}
+impl NormalizedEq for FeedGroup<'_> {}
+impl NormalizedEq for FeedMetadata<'_> {}
+impl NormalizedEq for Link<'_> {}
+impl NormalizedEq for Manifest<'_> {}
+impl NormalizedEq for Publication<'_> {}
+impl NormalizedEq for PublicationMetadata<'_> {}
+
+/// Whether `value` looks like a serialized [Link].
+fn is_link(value: &Value) -> bool {
+    value.as_object().is_some_and(|o| o.contains_key("href"))
+}
+
+fn normalize(value: Value) -> Value {
+    match value {
+        Value::Array(items) => {
+            let mut items: Vec<Value> = items.into_iter().map(normalize).collect();
+
+            if items.len() == 1 {
+                return items.remove(0);
+            }
+
+            if items.iter().all(is_link) {
+                // Objects have their keys sorted below, which gives every link a canonical
+                // form to order by.
+                items.sort_by_cached_key(Value::to_string);
+            }
+
+            Value::Array(items)
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.into_iter().map(|(k, v)| (k, normalize(v))).collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(entries.into_iter().collect())
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Feed<'_> {
+        serde_json::from_str(json).expect("can parse feed")
+    }
+
+    #[test]
+    fn test_normalized_eq() {
+        let a = parse(
+            r#"{
+                "metadata": {"title": "Example", "http://example.com/tags": ["new"]},
+                "links": [
+                    {"href": "/self", "rel": "self", "x-a": 1, "x-b": 2},
+                    {"href": "/next", "rel": ["next", "http://example.com/rel"]}
+                ],
+                "navigation": [{"href": "/a", "title": "A"}, {"href": "/b", "title": "B"}]
+            }"#,
+        );
+        let b = parse(
+            r#"{
+                "metadata": {"title": "Example", "http://example.com/tags": "new"},
+                "links": [
+                    {"href": "/next", "rel": ["next", "http://example.com/rel"]},
+                    {"rel": ["self"], "href": "/self", "x-b": 2, "x-a": 1}
+                ],
+                "navigation": [{"href": "/b", "title": "B"}, {"href": "/a", "title": "A"}]
+            }"#,
+        );
+        assert_ne!(a, b);
+        assert!(a.normalized_eq(&b));
+
+        // The order of values other than links still matters.
+        let c = parse(
+            r#"{
+                "metadata": {"title": "Example", "http://example.com/tags": ["new"]},
+                "links": [
+                    {"href": "/self", "rel": "self"},
+                    {"href": "/next", "rel": ["http://example.com/rel", "next"]}
+                ],
+                "navigation": [{"href": "/a", "title": "A"}, {"href": "/b", "title": "B"}]
+            }"#,
+        );
+        assert!(!a.normalized_eq(&c));
+
+        let d = parse(
+            r#"{
+                "metadata": {"title": "Example", "http://example.com/tags": ["new"]},
+                "links": [{"href": "/self", "rel": "self"}],
+                "navigation": [{"href": "/a", "title": "A"}, {"href": "/b", "title": "B"}]
+            }"#,
+        );
+        assert!(!a.normalized_eq(&d));
+    }
+}
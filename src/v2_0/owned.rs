@@ -0,0 +1,153 @@
+//! Support for detaching parsed objects from the buffer they were parsed from
+//!
+//! Objects parsed with [serde_json::from_str] borrow their strings from the input wherever
+//! possible, which ties them to the lifetime of the input. [IntoOwned] converts them into
+//! copies with a `'static` lifetime that own all of their data, so that they can be cached or
+//! sent to other threads once the input is dropped:
+//!
+//! ```
+//! use opds::v2_0::Feed;
+//! use opds::v2_0::owned::IntoOwned;
+//!
+//! fn parse(json: String) -> Feed<'static> {
+//!     let feed: Feed<'_> = serde_json::from_str(&json).unwrap();
+//!     feed.into_owned()
+//! }
+//!
+//! let feed = parse(r#"{"metadata": {"title": "Example"}, "links": []}"#.to_string());
+//! std::thread::spawn(move || println!("{:?}", feed.metadata.title)).join().unwrap();
+//! ```
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use super::*;
+
+/// Conversion into a copy that owns all of its data.
+pub trait IntoOwned {
+    /// The owned version of this type, usually the same type with a `'static` lifetime.
+    type Owned: 'static;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<B> IntoOwned for Cow<'_, B>
+where
+    B: ToOwned + ?Sized + 'static,
+{
+    type Owned = Cow<'static, B>;
+
+    fn into_owned(self) -> Cow<'static, B> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+impl<K: Ord + 'static, V: IntoOwned> IntoOwned for BTreeMap<K, V> {
+    type Owned = BTreeMap<K, V::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(|(k, v)| (k, v.into_owned())).collect()
+    }
+}
+
+impl IntoOwned for manifest::Subcollections<'_> {
+    type Owned = manifest::Subcollections<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(|(r, c)| (r, c.into_owned())).collect()
+    }
+}
+
+impl IntoOwned for StringWithAlternates<'_> {
+    type Owned = StringWithAlternates<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Always(s) => StringWithAlternates::Always(IntoOwned::into_owned(s)),
+            Self::Variants(variants) => StringWithAlternates::Variants(variants),
+        }
+    }
+}
+
+/// Implement [IntoOwned] for a struct with a single lifetime parameter.
+///
+/// The fields that borrow from the input are listed first, followed by a semicolon and then
+/// the fields that are moved over as they are. Every field must be listed, so that adding a
+/// field without updating the implementation fails to compile.
+macro_rules! into_owned {
+    ($ty: ident { $($borrowed: ident),* ; $($owned: ident),* }) => {
+        impl $crate::v2_0::owned::IntoOwned for $ty<'_> {
+            type Owned = $ty<'static>;
+
+            fn into_owned(self) -> Self::Owned {
+                let $ty { $($borrowed,)* $($owned,)* } = self;
+
+                $ty {
+                    $($borrowed: $crate::v2_0::owned::IntoOwned::into_owned($borrowed),)*
+                    $($owned,)*
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use into_owned;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::v2_0::authentication::AuthenticationDocument;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn read_input(name: &str) -> String {
+        std::fs::read_to_string(format!("{CRATE_DIR}/tests/{name}.in.json"))
+            .expect("valid file input")
+    }
+
+    fn assert_owned<T: Send + 'static>(_: &T) {}
+
+    #[test]
+    fn test_feed_into_owned() {
+        let json = read_input("test-feed-opds-io");
+        let feed: Feed<'_> = serde_json::from_str(&json).expect("can parse feed");
+        let expected = serde_json::to_value(&feed).unwrap();
+        let owned = feed.into_owned();
+        drop(json);
+
+        assert_owned(&owned);
+        assert_eq!(serde_json::to_value(&owned).unwrap(), expected);
+
+        let title = StringWithAlternates::from("Borrowed").into_owned();
+        assert!(matches!(title, StringWithAlternates::Always(Cow::Owned(_))));
+    }
+
+    #[test]
+    fn test_document_into_owned() {
+        let json = read_input("test-auth-opds-spec");
+        let doc: AuthenticationDocument<'_> =
+            serde_json::from_str(&json).expect("can parse document");
+        let expected = serde_json::to_value(&doc).unwrap();
+        let owned = doc.into_owned();
+        drop(json);
+
+        assert_owned(&owned);
+        assert_eq!(serde_json::to_value(&owned).unwrap(), expected);
+    }
+}
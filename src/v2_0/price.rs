@@ -0,0 +1,887 @@
+//! Support for the prices attached to acquisition links
+//!
+//! A [Price] is made of an exact decimal [Amount] and an ISO 4217 [Currency]. Amounts are
+//! read from the JSON numbers that feeds use, but are kept as decimals so that values like
+//! `10.99` don't pick up rounding errors, and currencies are checked against the list of
+//! active ISO 4217 codes, which also records how many minor units (such as cents) each
+//! currency has.
+//!
+//! JSON numbers with a fractional part are read as the shortest decimal that has the same
+//! floating point value, so any digits beyond the 15 to 17 significant digits that a float
+//! holds are lost: `12345678901234567.89` is read as `12345678901234568`. Prices that need
+//! more precision than that can be written as strings, like `"12345678901234567.89"`, which
+//! are read exactly.
+//!
+//! Amounts are written exactly by serializers that write JSON text, such as
+//! [serde_json::to_string] and [serde_json::to_writer]. A [serde_json::Value] can't hold more
+//! digits than a float, so [serde_json::to_value] rounds them like it does when reading, as do
+//! [NormalizedEq][crate::v2_0::normalize::NormalizedEq] and
+//! [Feed::from_json_strict][crate::v2_0::Feed::from_json_strict], which compare documents
+//! through it.
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use langtag::LangTag;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+use serde_json::value::RawValue;
+
+use super::*;
+
+/// The largest number of digits an [Amount] can hold.
+const MAX_DIGITS: usize = 38;
+
+/// An error encountered while parsing an [Amount] or [Currency].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The value is not a decimal number, or has too many digits.
+    InvalidAmount(String),
+
+    /// The value is not an active ISO 4217 currency code.
+    UnknownCurrency(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAmount(value) => write!(f, "invalid amount {value:?}"),
+            Self::UnknownCurrency(code) => write!(f, "unknown currency code {code:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// An exact decimal number.
+///
+/// Amounts are stored as an integer and the number of digits after the decimal point, so
+/// `10.99` is `1099` with a scale of `2`. Amounts that differ only in trailing zeros, like
+/// `10.5` and `10.50`, are equal.
+#[derive(Clone, Copy, Debug)]
+pub struct Amount {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Amount {
+    /// Create the amount `mantissa × 10^-scale`.
+    pub const fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// The number of digits after the decimal point.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// An approximation of this amount as a floating point number.
+    pub fn to_f64(&self) -> f64 {
+        // Going through the decimal string gives the closest float.
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// Round this amount to `scale` digits after the decimal point, with halves rounded away
+    /// from zero. Amounts that already have `scale` digits or fewer are padded with zeros.
+    ///
+    /// Returns `None` if the result is too large to represent.
+    pub fn round(&self, scale: u32) -> Option<Self> {
+        match self.scale.cmp(&scale) {
+            Ordering::Equal => Some(*self),
+            Ordering::Less => {
+                let factor = 10i128.checked_pow(scale - self.scale)?;
+                let mantissa = self.mantissa.checked_mul(factor)?;
+                Some(Self::new(mantissa, scale))
+            }
+            Ordering::Greater => {
+                let Some(factor) = 10i128.checked_pow(self.scale - scale) else {
+                    // Every digit is being removed, and they're all less than half.
+                    return Some(Self::new(0, scale));
+                };
+                let quotient = self.mantissa / factor;
+                let remainder = self.mantissa % factor;
+                let mantissa = if remainder.unsigned_abs() * 2 >= factor.unsigned_abs() {
+                    quotient + self.mantissa.signum()
+                } else {
+                    quotient
+                };
+                Some(Self::new(mantissa, scale))
+            }
+        }
+    }
+
+    /// Remove any trailing zeros after the decimal point.
+    fn normalize(&self) -> Self {
+        let mut amount = *self;
+
+        while amount.scale > 0 && amount.mantissa % 10 == 0 {
+            amount.mantissa /= 10;
+            amount.scale -= 1;
+        }
+
+        amount
+    }
+}
+
+impl PartialEq for Amount {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Amount {}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Amount {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.normalize(), other.normalize());
+
+        match a.scale.cmp(&b.scale) {
+            Ordering::Equal => a.mantissa.cmp(&b.mantissa),
+            Ordering::Less => match a.round(b.scale) {
+                Some(a) => a.mantissa.cmp(&b.mantissa),
+                // Too large to scale up, so it's further from zero than the other amount.
+                None => a.mantissa.cmp(&0),
+            },
+            Ordering::Greater => other.cmp(self).reverse(),
+        }
+    }
+}
+
+impl Hash for Amount {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let amount = self.normalize();
+        amount.mantissa.hash(state);
+        amount.scale.hash(state);
+    }
+}
+
+impl From<i64> for Amount {
+    fn from(value: i64) -> Self {
+        Self::new(value.into(), 0)
+    }
+}
+
+impl TryFrom<f64> for Amount {
+    type Error = Error;
+
+    /// Convert the shortest decimal that reads back as `value`, so that `10.99` becomes
+    /// exactly `10.99`.
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if !value.is_finite() {
+            return Err(Error::InvalidAmount(value.to_string()));
+        }
+
+        value.to_string().parse()
+    }
+}
+
+impl FromStr for Amount {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidAmount(s.to_string());
+
+        let (negative, unsigned) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (whole, fraction) = match unsigned.split_once('.') {
+            Some((_, "")) => return Err(invalid()),
+            Some(parts) => parts,
+            None => (unsigned, ""),
+        };
+
+        let digits = whole.len() + fraction.len();
+        let all_digits = whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|c| c.is_ascii_digit());
+
+        if whole.is_empty() || !all_digits || digits > MAX_DIGITS {
+            return Err(invalid());
+        }
+
+        let mantissa = whole
+            .bytes()
+            .chain(fraction.bytes())
+            .try_fold(0i128, |acc, c| {
+                acc.checked_mul(10)?.checked_add(i128::from(c - b'0'))
+            })
+            .ok_or_else(invalid)?;
+        let mantissa = if negative { -mantissa } else { mantissa };
+
+        Ok(Self::new(mantissa, fraction.len() as u32))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        let sign = if self.is_negative() { "-" } else { "" };
+
+        if fraction.is_empty() {
+            write!(f, "{sign}{whole}")
+        } else {
+            write!(f, "{sign}{whole}.{fraction}")
+        }
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let amount = self.normalize();
+
+        if let Ok(value) = i64::try_from(amount.mantissa)
+            && amount.scale == 0
+        {
+            return serializer.serialize_i64(value);
+        }
+
+        // A float is written using the shortest digits that read back as it, which are this
+        // amount's own digits when it survives the trip through a float.
+        let float = self.to_f64();
+
+        if Amount::try_from(float).is_ok_and(|a| a == amount) {
+            return serializer.serialize_f64(float);
+        }
+
+        let digits = RawValue::from_string(amount.to_string()).map_err(ser::Error::custom)?;
+        digits.serialize(serializer)
+    }
+}
+
+struct AmountVisitor;
+
+impl Visitor<'_> for AmountVisitor {
+    type Value = Amount;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a decimal number")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(Amount::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(Amount::new(value.into(), 0))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        // The literal digits are gone by now, so this is only as precise as the float.
+        Amount::try_from(value).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(AmountVisitor)
+    }
+}
+
+/// An active ISO 4217 currency.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Currency {
+    code: &'static str,
+    minor_units: Option<u8>,
+}
+
+/// The active ISO 4217 currency codes and their minor units, sorted by code.
+///
+/// Codes without minor units, like those for precious metals, have `None`.
+const CURRENCIES: &[(&str, Option<u8>)] = &[
+    ("AED", Some(2)),
+    ("AFN", Some(2)),
+    ("ALL", Some(2)),
+    ("AMD", Some(2)),
+    ("ANG", Some(2)),
+    ("AOA", Some(2)),
+    ("ARS", Some(2)),
+    ("AUD", Some(2)),
+    ("AWG", Some(2)),
+    ("AZN", Some(2)),
+    ("BAM", Some(2)),
+    ("BBD", Some(2)),
+    ("BDT", Some(2)),
+    ("BGN", Some(2)),
+    ("BHD", Some(3)),
+    ("BIF", Some(0)),
+    ("BMD", Some(2)),
+    ("BND", Some(2)),
+    ("BOB", Some(2)),
+    ("BOV", Some(2)),
+    ("BRL", Some(2)),
+    ("BSD", Some(2)),
+    ("BTN", Some(2)),
+    ("BWP", Some(2)),
+    ("BYN", Some(2)),
+    ("BZD", Some(2)),
+    ("CAD", Some(2)),
+    ("CDF", Some(2)),
+    ("CHE", Some(2)),
+    ("CHF", Some(2)),
+    ("CHW", Some(2)),
+    ("CLF", Some(4)),
+    ("CLP", Some(0)),
+    ("CNY", Some(2)),
+    ("COP", Some(2)),
+    ("COU", Some(2)),
+    ("CRC", Some(2)),
+    ("CUC", Some(2)),
+    ("CUP", Some(2)),
+    ("CVE", Some(2)),
+    ("CZK", Some(2)),
+    ("DJF", Some(0)),
+    ("DKK", Some(2)),
+    ("DOP", Some(2)),
+    ("DZD", Some(2)),
+    ("EGP", Some(2)),
+    ("ERN", Some(2)),
+    ("ETB", Some(2)),
+    ("EUR", Some(2)),
+    ("FJD", Some(2)),
+    ("FKP", Some(2)),
+    ("GBP", Some(2)),
+    ("GEL", Some(2)),
+    ("GHS", Some(2)),
+    ("GIP", Some(2)),
+    ("GMD", Some(2)),
+    ("GNF", Some(0)),
+    ("GTQ", Some(2)),
+    ("GYD", Some(2)),
+    ("HKD", Some(2)),
+    ("HNL", Some(2)),
+    ("HTG", Some(2)),
+    ("HUF", Some(2)),
+    ("IDR", Some(2)),
+    ("ILS", Some(2)),
+    ("INR", Some(2)),
+    ("IQD", Some(3)),
+    ("IRR", Some(2)),
+    ("ISK", Some(0)),
+    ("JMD", Some(2)),
+    ("JOD", Some(3)),
+    ("JPY", Some(0)),
+    ("KES", Some(2)),
+    ("KGS", Some(2)),
+    ("KHR", Some(2)),
+    ("KMF", Some(0)),
+    ("KPW", Some(2)),
+    ("KRW", Some(0)),
+    ("KWD", Some(3)),
+    ("KYD", Some(2)),
+    ("KZT", Some(2)),
+    ("LAK", Some(2)),
+    ("LBP", Some(2)),
+    ("LKR", Some(2)),
+    ("LRD", Some(2)),
+    ("LSL", Some(2)),
+    ("LYD", Some(3)),
+    ("MAD", Some(2)),
+    ("MDL", Some(2)),
+    ("MGA", Some(2)),
+    ("MKD", Some(2)),
+    ("MMK", Some(2)),
+    ("MNT", Some(2)),
+    ("MOP", Some(2)),
+    ("MRU", Some(2)),
+    ("MUR", Some(2)),
+    ("MVR", Some(2)),
+    ("MWK", Some(2)),
+    ("MXN", Some(2)),
+    ("MXV", Some(2)),
+    ("MYR", Some(2)),
+    ("MZN", Some(2)),
+    ("NAD", Some(2)),
+    ("NGN", Some(2)),
+    ("NIO", Some(2)),
+    ("NOK", Some(2)),
+    ("NPR", Some(2)),
+    ("NZD", Some(2)),
+    ("OMR", Some(3)),
+    ("PAB", Some(2)),
+    ("PEN", Some(2)),
+    ("PGK", Some(2)),
+    ("PHP", Some(2)),
+    ("PKR", Some(2)),
+    ("PLN", Some(2)),
+    ("PYG", Some(0)),
+    ("QAR", Some(2)),
+    ("RON", Some(2)),
+    ("RSD", Some(2)),
+    ("RUB", Some(2)),
+    ("RWF", Some(0)),
+    ("SAR", Some(2)),
+    ("SBD", Some(2)),
+    ("SCR", Some(2)),
+    ("SDG", Some(2)),
+    ("SEK", Some(2)),
+    ("SGD", Some(2)),
+    ("SHP", Some(2)),
+    ("SLE", Some(2)),
+    ("SLL", Some(2)),
+    ("SOS", Some(2)),
+    ("SRD", Some(2)),
+    ("SSP", Some(2)),
+    ("STN", Some(2)),
+    ("SVC", Some(2)),
+    ("SYP", Some(2)),
+    ("SZL", Some(2)),
+    ("THB", Some(2)),
+    ("TJS", Some(2)),
+    ("TMT", Some(2)),
+    ("TND", Some(3)),
+    ("TOP", Some(2)),
+    ("TRY", Some(2)),
+    ("TTD", Some(2)),
+    ("TWD", Some(2)),
+    ("TZS", Some(2)),
+    ("UAH", Some(2)),
+    ("UGX", Some(0)),
+    ("USD", Some(2)),
+    ("USN", Some(2)),
+    ("UYI", Some(0)),
+    ("UYU", Some(2)),
+    ("UYW", Some(4)),
+    ("UZS", Some(2)),
+    ("VED", Some(2)),
+    ("VES", Some(2)),
+    ("VND", Some(0)),
+    ("VUV", Some(0)),
+    ("WST", Some(2)),
+    ("XAF", Some(0)),
+    ("XAG", None),
+    ("XAU", None),
+    ("XBA", None),
+    ("XBB", None),
+    ("XBC", None),
+    ("XBD", None),
+    ("XCD", Some(2)),
+    ("XCG", Some(2)),
+    ("XDR", None),
+    ("XOF", Some(0)),
+    ("XPD", None),
+    ("XPF", Some(0)),
+    ("XPT", None),
+    ("XSU", None),
+    ("XTS", None),
+    ("XUA", None),
+    ("XXX", None),
+    ("YER", Some(2)),
+    ("ZAR", Some(2)),
+    ("ZMW", Some(2)),
+    ("ZWG", Some(2)),
+    ("ZWL", Some(2)),
+];
+
+impl Currency {
+    pub const EUR: Self = Self::known("EUR", Some(2));
+    pub const GBP: Self = Self::known("GBP", Some(2));
+    pub const JPY: Self = Self::known("JPY", Some(0));
+    pub const USD: Self = Self::known("USD", Some(2));
+
+    const fn known(code: &'static str, minor_units: Option<u8>) -> Self {
+        Self { code, minor_units }
+    }
+
+    /// Look up an ISO 4217 code, ignoring case.
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.to_ascii_uppercase();
+        let i = CURRENCIES
+            .binary_search_by(|(c, _)| (*c).cmp(code.as_str()))
+            .ok()?;
+        let (code, minor_units) = CURRENCIES[i];
+
+        Some(Self::known(code, minor_units))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.code
+    }
+
+    /// The number of digits after the decimal point used by this currency, such as `2` for
+    /// the cents of the US dollar, or `None` for codes like `XAU` (gold) that have none.
+    pub fn minor_units(&self) -> Option<u8> {
+        self.minor_units
+    }
+
+    /// The symbol commonly used for this currency, if it has a widely recognized one.
+    fn symbol(&self) -> Option<&'static str> {
+        match self.code {
+            "EUR" => Some("€"),
+            "GBP" => Some("£"),
+            "JPY" => Some("¥"),
+            "USD" => Some("$"),
+            "INR" => Some("₹"),
+            "KRW" => Some("₩"),
+            "BRL" => Some("R$"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code)
+    }
+}
+
+impl FromStr for Currency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_code(s).ok_or_else(|| Error::UnknownCurrency(s.to_string()))
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.code)
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code = <Cow<'de, str>>::deserialize(deserializer)?;
+        code.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Where a currency goes relative to the number when formatting a price.
+#[derive(Clone, Copy)]
+enum Placement {
+    Before,
+    BeforeSpaced,
+    AfterSpaced,
+}
+
+/// The separators and currency placement used by a locale.
+struct Style {
+    decimal: char,
+    group: char,
+    placement: Placement,
+}
+
+const NBSP: char = '\u{a0}';
+
+impl Style {
+    /// Pick the conventions for a locale by its primary language, and for a few languages,
+    /// its region.
+    fn of(locale: &LangTag) -> Self {
+        let tag = locale.as_str().to_ascii_lowercase();
+        let mut subtags = tag.split('-');
+        let language = subtags.next().unwrap_or_default();
+        let region = subtags.find(|s| s.len() == 2);
+
+        let (decimal, group, placement) = match (language, region) {
+            ("de", Some("ch" | "li")) => ('.', '\'', Placement::BeforeSpaced),
+            ("pt", Some("br")) => (',', '.', Placement::BeforeSpaced),
+            ("nl" | "id", _) => (',', '.', Placement::BeforeSpaced),
+            ("da" | "de" | "el" | "es" | "hr" | "it" | "ro" | "sl" | "sr" | "tr", _) => {
+                (',', '.', Placement::AfterSpaced)
+            }
+            (
+                "bg" | "cs" | "et" | "fi" | "fr" | "hu" | "lt" | "lv" | "nb" | "nn" | "no" | "pl"
+                | "pt" | "ru" | "sk" | "sv" | "uk",
+                _,
+            ) => (',', NBSP, Placement::AfterSpaced),
+            _ => ('.', ',', Placement::Before),
+        };
+
+        Self {
+            decimal,
+            group,
+            placement,
+        }
+    }
+}
+
+impl Price {
+    pub fn new(value: impl Into<Amount>, currency: Currency) -> Self {
+        Self {
+            value: value.into(),
+            currency,
+        }
+    }
+
+    /// The price as a whole number of the currency's minor units, such as `1099` cents for
+    /// 10.99 USD.
+    ///
+    /// Returns `None` if the currency has no minor units, or the price is more precise than
+    /// them.
+    pub fn minor_amount(&self) -> Option<i128> {
+        let scale = u32::from(self.currency.minor_units?);
+        let rounded = self.value.round(scale)?;
+
+        (rounded == self.value).then_some(rounded.mantissa)
+    }
+
+    /// Format the price for display, following the usual conventions of `locale` for
+    /// separators and currency placement, like `$1,234.50` for `en-US` or `1.234,50 €` for
+    /// `de`.
+    ///
+    /// The amount is rounded to the currency's minor units. Only the conventions of common
+    /// locales are known; others are formatted like `en`.
+    pub fn format(&self, locale: &LangTag) -> String {
+        let style = Style::of(locale);
+        let amount = match self.currency.minor_units {
+            Some(scale) => self.value.round(scale.into()).unwrap_or(self.value),
+            None => self.value,
+        };
+
+        let digits = amount.to_string();
+        let digits = digits.trim_start_matches('-');
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let mut number = String::new();
+
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                number.push(style.group);
+            }
+
+            number.push(c);
+        }
+
+        if !fraction.is_empty() {
+            number.push(style.decimal);
+            number.push_str(fraction);
+        }
+
+        let sign = if amount.is_negative() { "-" } else { "" };
+        let code = self.currency.as_str();
+
+        match (style.placement, self.currency.symbol()) {
+            (Placement::Before, Some(symbol)) => format!("{sign}{symbol}{number}"),
+            (Placement::Before | Placement::BeforeSpaced, None) => {
+                format!("{sign}{code}{NBSP}{number}")
+            }
+            (Placement::BeforeSpaced, Some(symbol)) => format!("{sign}{symbol}{NBSP}{number}"),
+            (Placement::AfterSpaced, symbol) => {
+                format!("{sign}{number}{NBSP}{}", symbol.unwrap_or(code))
+            }
+        }
+    }
+}
+
+impl PartialOrd for Price {
+    /// Prices are only comparable when they're in the same currency.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self.currency == other.currency).then(|| self.value.cmp(&other.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(s: &str) -> Amount {
+        s.parse().expect("valid amount")
+    }
+
+    #[test]
+    fn test_amount() {
+        assert_eq!(amount("10.99").mantissa(), 1099);
+        assert_eq!(amount("10.99").scale(), 2);
+        assert_eq!(amount("-0.05").to_string(), "-0.05");
+        assert_eq!(amount("+7").to_string(), "7");
+        assert_eq!(amount("10.5"), amount("10.50"));
+        assert!(amount("9.999") < amount("10"));
+        assert!(amount("-1") < amount("0.001"));
+
+        assert_eq!(amount("2.345").round(2), Some(amount("2.35")));
+        assert_eq!(amount("-2.345").round(2), Some(amount("-2.35")));
+        assert_eq!(amount("2.344").round(2), Some(amount("2.34")));
+        assert_eq!(
+            amount("2").round(2).map(|a| a.to_string()).as_deref(),
+            Some("2.00")
+        );
+
+        for invalid in ["", "-", ".5", "5.", "1.2.3", "1e5", "ten", "1,5"] {
+            assert!(invalid.parse::<Amount>().is_err(), "{invalid:?}");
+        }
+
+        // Large values keep every digit.
+        let large = amount("12345678901234567.89");
+        assert_eq!(large.to_string(), "12345678901234567.89");
+        assert!(large > amount("12345678901234567.88"));
+    }
+
+    #[test]
+    fn test_amount_precision() {
+        // Amounts are written exactly as JSON text, even beyond the digits a float holds.
+        for digits in [
+            "12345678901234567.89",
+            "17014118346046923173168730371588410572",
+            "99999999999999999999",
+            "0.1",
+        ] {
+            assert_eq!(serde_json::to_string(&amount(digits)).unwrap(), digits);
+        }
+
+        // A Value can only hold as many digits as a float.
+        assert_eq!(
+            serde_json::to_value(amount("12345678901234567.89")).unwrap(),
+            serde_json::json!(12345678901234568.0)
+        );
+
+        let json = serde_json::to_string(&amount("-0.000001")).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Amount>(&json).unwrap(),
+            amount("-0.000001")
+        );
+
+        let price = Price::new(amount("12345678901234567.89"), Currency::USD);
+        assert_eq!(
+            serde_json::to_string(&price).unwrap(),
+            r#"{"value":12345678901234567.89,"currency":"USD"}"#
+        );
+
+        // JSON numbers are read through a float, so only its digits are kept.
+        let json = r#"{"value": 12345678901234567.89, "currency": "USD"}"#;
+        let price: Price = serde_json::from_str(json).unwrap();
+        assert_eq!(price.value, amount("12345678901234568"));
+        assert_eq!(
+            serde_json::to_string(&price).unwrap(),
+            r#"{"value":12345678901234568,"currency":"USD"}"#
+        );
+
+        // But strings are read exactly.
+        let json = r#"{"value": "12345678901234567.89", "currency": "USD"}"#;
+        let price: Price = serde_json::from_str(json).unwrap();
+        assert_eq!(price.value, amount("12345678901234567.89"));
+
+        // This holds within links, where the price is read along with any extensions.
+        let json = r#"{"price": {"value": "12345678901234567.89", "currency": "USD"}}"#;
+        let properties: LinkProperties<'_> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            properties.price.map(|p| p.value),
+            Some(amount("12345678901234567.89"))
+        );
+    }
+
+    #[test]
+    fn test_amount_serde() {
+        let price: Price = serde_json::from_str(r#"{"value": 10.99, "currency": "USD"}"#).unwrap();
+        assert_eq!(price.value, amount("10.99"));
+        assert_eq!(price.value.scale(), 2);
+        assert_eq!(price.currency, Currency::USD);
+        assert_eq!(
+            serde_json::to_string(&price).unwrap(),
+            r#"{"value":10.99,"currency":"USD"}"#
+        );
+
+        let price: Price = serde_json::from_str(r#"{"value": 500, "currency": "jpy"}"#).unwrap();
+        assert_eq!(price, Price::new(500, Currency::JPY));
+        assert_eq!(
+            serde_json::to_string(&price).unwrap(),
+            r#"{"value":500,"currency":"JPY"}"#
+        );
+
+        assert!(serde_json::from_str::<Price>(r#"{"value": 1, "currency": "ABC"}"#).is_err());
+        assert!(serde_json::from_str::<Price>(r#"{"value": 1, "currency": "dollars"}"#).is_err());
+    }
+
+    #[test]
+    fn test_currency() {
+        assert!(CURRENCIES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(Currency::from_code("usd"), Some(Currency::USD));
+        assert_eq!(Currency::from_code("KWD").unwrap().minor_units(), Some(3));
+        assert_eq!(Currency::from_code("XAU").unwrap().minor_units(), None);
+        assert_eq!(Currency::from_code("XYZ"), None);
+
+        assert_eq!(
+            Price::new(amount("10.99"), Currency::USD).minor_amount(),
+            Some(1099)
+        );
+        assert_eq!(
+            Price::new(amount("10.5"), Currency::USD).minor_amount(),
+            Some(1050)
+        );
+        assert_eq!(
+            Price::new(amount("10.999"), Currency::USD).minor_amount(),
+            None
+        );
+        assert_eq!(
+            Price::new(amount("10.5"), Currency::JPY).minor_amount(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_price_order() {
+        let cheap = Price::new(amount("4.99"), Currency::USD);
+        let dear = Price::new(amount("10"), Currency::USD);
+        let euros = Price::new(amount("1"), Currency::EUR);
+
+        assert!(cheap < dear);
+        assert_eq!(cheap.partial_cmp(&euros), None);
+        assert_eq!(
+            Price::new(amount("10.00"), Currency::USD).partial_cmp(&dear),
+            Some(Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn test_price_format() {
+        let price = Price::new(amount("1234.5"), Currency::EUR);
+
+        assert_eq!(price.format(langtag::langtag!("en-US")), "€1,234.50");
+        assert_eq!(price.format(langtag::langtag!("de")), "1.234,50\u{a0}€");
+        assert_eq!(
+            price.format(langtag::langtag!("fr-CA")),
+            "1\u{a0}234,50\u{a0}€"
+        );
+        assert_eq!(price.format(langtag::langtag!("nl")), "€\u{a0}1.234,50");
+
+        let price = Price::new(amount("-1234567.891"), Currency::from_code("CHF").unwrap());
+        assert_eq!(
+            price.format(langtag::langtag!("de-CH")),
+            "-CHF\u{a0}1'234'567.89"
+        );
+        assert_eq!(
+            price.format(langtag::langtag!("en")),
+            "-CHF\u{a0}1,234,567.89"
+        );
+
+        let price = Price::new(amount("1500"), Currency::JPY);
+        assert_eq!(price.format(langtag::langtag!("ja")), "¥1,500");
+    }
+}
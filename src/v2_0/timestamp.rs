@@ -0,0 +1,1041 @@
+//! Support for the timestamps used throughout feeds
+//!
+//! Fields like [FeedMetadata::modified] and [PublicationMetadata::published] hold [ISO 8601]
+//! dates, but catalogs write them with varying precision: just a year like `1917`, a year and
+//! month like `2023-04`, a full date, or a complete [RFC 3339] date and time. A [DateTime]
+//! accepts all of these, remembers which parts were given so that it is written back out the
+//! same way, and can be compared with other timestamps to sort by recency.
+//!
+//! Some catalogs also write dates that aren't ISO 8601 at all, like `March 2019`. Rather than
+//! rejecting the whole feed, a [Timestamp] keeps these as [Timestamp::Unparsed], so that they
+//! are still written back out as they were given.
+//!
+//! With the `chrono`, `jiff` or `time` features enabled, timestamps can be converted to and
+//! from the date and time types of those crates.
+//!
+//! [FeedMetadata::modified]: crate::v2_0::metadata::FeedMetadata::modified
+//! [PublicationMetadata::published]: crate::v2_0::metadata::PublicationMetadata::published
+//! [ISO 8601]: https://www.iso.org/iso-8601-date-and-time-format.html
+//! [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+/// An error encountered while parsing a [Timestamp].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The string is not written in any of the supported forms.
+    Syntax,
+
+    /// The named part of the timestamp is outside of its allowed range, such as a 13th month.
+    OutOfRange(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax => write!(f, "invalid timestamp"),
+            Self::OutOfRange(part) => write!(f, "invalid timestamp: {part} is out of range"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// How much of a [DateTime] was given.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Precision {
+    /// Only the year, like `2023`.
+    Year,
+    /// The year and month, like `2023-04`.
+    Month,
+    /// A full date, like `2023-04-01`.
+    Day,
+    /// A date and time without seconds, like `2023-04-01T10:30Z`.
+    Minute,
+    /// A date and time, like `2023-04-01T10:30:15Z`.
+    Second,
+    /// A date and time with fractional seconds, like `2023-04-01T10:30:15.250Z`.
+    Fraction,
+}
+
+/// The offset from UTC of a [DateTime]'s time.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Offset {
+    /// Written as `Z`.
+    Utc,
+    /// Written as a number of hours and minutes, like `+02:00`.
+    Minutes(i16),
+}
+
+impl Offset {
+    /// The number of minutes ahead of UTC.
+    pub fn minutes(&self) -> i16 {
+        match self {
+            Self::Utc => 0,
+            Self::Minutes(minutes) => *minutes,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Time {
+    hour: u8,
+    minute: u8,
+    second: Option<u8>,
+    /// The fractional seconds in nanoseconds, and the number of digits they were written with.
+    fraction: Option<(u32, u8)>,
+    offset: Option<Offset>,
+}
+
+/// A date, optionally with a time, written with any precision from a year down to a fraction
+/// of a second.
+///
+/// These are ordered by the moment they start at, so `2023` sorts before `2023-04`, and
+/// `2023-04-01T12:00:00+02:00` sorts before `2023-04-01T11:00:00Z`. Those without an offset
+/// from UTC are treated as being in UTC.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct DateTime {
+    year: u16,
+    month: Option<u8>,
+    day: Option<u8>,
+    time: Option<Time>,
+}
+
+/// A timestamp from a feed, which is either a [DateTime] or a value that couldn't be parsed as
+/// one.
+///
+/// Timestamps are ordered by their [DateTime], with any unparsed values sorting before all
+/// parsed ones, as if they were the oldest.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Timestamp {
+    Parsed(DateTime),
+
+    /// A value that isn't written in any of the supported forms, kept as it was given.
+    Unparsed(String),
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The number of days between 1970-01-01 and the given date.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let (month, day) = (i64::from(month), i64::from(day));
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+impl DateTime {
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> Option<u8> {
+        self.month
+    }
+
+    pub fn day(&self) -> Option<u8> {
+        self.day
+    }
+
+    pub fn hour(&self) -> Option<u8> {
+        self.time.map(|t| t.hour)
+    }
+
+    pub fn minute(&self) -> Option<u8> {
+        self.time.map(|t| t.minute)
+    }
+
+    pub fn second(&self) -> Option<u8> {
+        self.time.and_then(|t| t.second)
+    }
+
+    pub fn nanosecond(&self) -> Option<u32> {
+        self.time.and_then(|t| t.fraction).map(|(nanos, _)| nanos)
+    }
+
+    pub fn offset(&self) -> Option<Offset> {
+        self.time.and_then(|t| t.offset)
+    }
+
+    pub fn precision(&self) -> Precision {
+        match (self.month, self.day, self.time) {
+            (None, _, _) => Precision::Year,
+            (Some(_), None, _) => Precision::Month,
+            (Some(_), Some(_), None) => Precision::Day,
+            (Some(_), Some(_), Some(time)) => match (time.second, time.fraction) {
+                (None, _) => Precision::Minute,
+                (Some(_), None) => Precision::Second,
+                (Some(_), Some(_)) => Precision::Fraction,
+            },
+        }
+    }
+
+    /// The number of seconds between the Unix epoch and the start of this timestamp.
+    pub fn unix_timestamp(&self) -> i64 {
+        let days = days_from_civil(
+            i64::from(self.year),
+            self.month.unwrap_or(1),
+            self.day.unwrap_or(1),
+        );
+        let seconds = self.time.map_or(0, |time| {
+            let offset = time.offset.map_or(0, |offset| offset.minutes());
+
+            i64::from(time.hour) * 3600 + i64::from(time.minute) * 60 - i64::from(offset) * 60
+                + i64::from(time.second.unwrap_or(0))
+        });
+
+        days * 86_400 + seconds
+    }
+
+    /// A date with [Precision::Day], such as from another crate's date type.
+    #[cfg(any(feature = "chrono", feature = "jiff", feature = "time"))]
+    fn date(year: i64, month: u8, day: u8) -> Result<Self, Error> {
+        let year = u16::try_from(year)
+            .ok()
+            .filter(|year| *year <= 9999)
+            .ok_or(Error::OutOfRange("year"))?;
+
+        Ok(Self {
+            year,
+            month: Some(month),
+            day: Some(day),
+            time: None,
+        })
+    }
+
+    /// Add a time to a date, with as many fractional digits as `nanosecond` needs.
+    #[cfg(any(feature = "chrono", feature = "jiff", feature = "time"))]
+    fn with_time(
+        mut self,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        nanosecond: u32,
+        offset_seconds: i32,
+    ) -> Result<Self, Error> {
+        if offset_seconds % 60 != 0 {
+            return Err(Error::OutOfRange("offset"));
+        }
+
+        let fraction = (nanosecond != 0).then(|| {
+            let zeros = (0..9)
+                .take_while(|i| nanosecond % 10u32.pow(i + 1) == 0)
+                .count();
+            (nanosecond, 9 - zeros as u8)
+        });
+        let offset = match offset_seconds / 60 {
+            0 => Offset::Utc,
+            minutes => Offset::Minutes(minutes as i16),
+        };
+
+        self.time = Some(Time {
+            hour,
+            minute,
+            second: Some(second),
+            fraction,
+            offset: Some(offset),
+        });
+
+        Ok(self)
+    }
+
+    /// The offset from UTC in seconds, with no offset treated as UTC.
+    #[cfg(any(feature = "chrono", feature = "jiff", feature = "time"))]
+    fn offset_seconds(&self) -> i32 {
+        self.offset()
+            .map_or(0, |offset| i32::from(offset.minutes()) * 60)
+    }
+
+    fn key(&self) -> (i64, u32, Precision, Option<Offset>, u8) {
+        let digits = self.time.and_then(|t| t.fraction).map_or(0, |(_, n)| n);
+
+        (
+            self.unix_timestamp(),
+            self.nanosecond().unwrap_or(0),
+            self.precision(),
+            self.offset(),
+            digits,
+        )
+    }
+}
+
+impl PartialOrd for DateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+
+        if let Some(month) = self.month {
+            write!(f, "-{month:02}")?;
+        }
+
+        if let Some(day) = self.day {
+            write!(f, "-{day:02}")?;
+        }
+
+        let Some(time) = self.time else {
+            return Ok(());
+        };
+
+        write!(f, "T{:02}:{:02}", time.hour, time.minute)?;
+
+        if let Some(second) = time.second {
+            write!(f, ":{second:02}")?;
+        }
+
+        if let Some((nanos, digits)) = time.fraction {
+            let fraction = format!("{nanos:09}");
+            write!(f, ".{}", &fraction[..usize::from(digits)])?;
+        }
+
+        match time.offset {
+            None => Ok(()),
+            Some(Offset::Utc) => write!(f, "Z"),
+            Some(Offset::Minutes(minutes)) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+/// A cursor over the bytes of a timestamp being parsed.
+struct Parser<'a> {
+    s: &'a [u8],
+}
+
+impl Parser<'_> {
+    fn eat(&mut self, chars: &[u8]) -> Option<u8> {
+        let (&c, rest) = self.s.split_first()?;
+
+        if chars.contains(&c) {
+            self.s = rest;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    /// Parse exactly `n` digits.
+    fn digits(&mut self, n: usize) -> Result<u32, Error> {
+        if self.s.len() < n || !self.s[..n].iter().all(u8::is_ascii_digit) {
+            return Err(Error::Syntax);
+        }
+
+        let value = self.s[..n]
+            .iter()
+            .fold(0, |acc, c| acc * 10 + u32::from(c - b'0'));
+        self.s = &self.s[n..];
+
+        Ok(value)
+    }
+
+    /// Parse a two-digit number within `range`.
+    fn part(
+        &mut self,
+        range: std::ops::RangeInclusive<u8>,
+        name: &'static str,
+    ) -> Result<u8, Error> {
+        let value = self.digits(2)? as u8;
+
+        if range.contains(&value) {
+            Ok(value)
+        } else {
+            Err(Error::OutOfRange(name))
+        }
+    }
+
+    fn fraction(&mut self) -> Result<(u32, u8), Error> {
+        let digits = self.s.iter().take_while(|c| c.is_ascii_digit()).count();
+
+        if !(1..=9).contains(&digits) {
+            return Err(Error::Syntax);
+        }
+
+        let value = self.digits(digits)?;
+        let nanos = value * 10u32.pow(9 - digits as u32);
+
+        Ok((nanos, digits as u8))
+    }
+
+    fn offset(&mut self) -> Result<Option<Offset>, Error> {
+        if self.eat(b"Zz").is_some() {
+            return Ok(Some(Offset::Utc));
+        }
+
+        let Some(sign) = self.eat(b"+-") else {
+            return Ok(None);
+        };
+
+        let hours = self.part(0..=23, "offset")?;
+        let minutes = if self.s.is_empty() {
+            0
+        } else {
+            self.eat(b":");
+            self.part(0..=59, "offset")?
+        };
+
+        let minutes = i16::from(hours) * 60 + i16::from(minutes);
+
+        Ok(Some(Offset::Minutes(if sign == b'-' {
+            -minutes
+        } else {
+            minutes
+        })))
+    }
+
+    fn time(&mut self) -> Result<Time, Error> {
+        let hour = self.part(0..=23, "hour")?;
+        self.eat(b":").ok_or(Error::Syntax)?;
+        let minute = self.part(0..=59, "minute")?;
+
+        let second = match self.eat(b":") {
+            Some(_) => Some(self.part(0..=60, "second")?),
+            None => None,
+        };
+
+        let fraction = match (second, self.eat(b".,")) {
+            (Some(_), Some(_)) => Some(self.fraction()?),
+            (None, Some(_)) => return Err(Error::Syntax),
+            (_, None) => None,
+        };
+
+        let offset = self.offset()?;
+
+        Ok(Time {
+            hour,
+            minute,
+            second,
+            fraction,
+            offset,
+        })
+    }
+
+    fn timestamp(&mut self) -> Result<DateTime, Error> {
+        let year = self.digits(4)? as u16;
+        let mut timestamp = DateTime {
+            year,
+            month: None,
+            day: None,
+            time: None,
+        };
+
+        if self.eat(b"-").is_some() {
+            timestamp.month = Some(self.part(1..=12, "month")?);
+
+            if self.eat(b"-").is_some() {
+                let max = days_in_month(i64::from(year), timestamp.month.unwrap_or(1));
+                timestamp.day = Some(self.part(1..=max, "day")?);
+
+                if self.eat(b"Tt ").is_some() {
+                    timestamp.time = Some(self.time()?);
+                }
+            }
+        }
+
+        if self.s.is_empty() {
+            Ok(timestamp)
+        } else {
+            Err(Error::Syntax)
+        }
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Parser { s: s.as_bytes() }.timestamp()
+    }
+}
+
+impl Timestamp {
+    /// The date and time, if this timestamp could be parsed.
+    pub fn date_time(&self) -> Option<&DateTime> {
+        match self {
+            Self::Parsed(date_time) => Some(date_time),
+            Self::Unparsed(_) => None,
+        }
+    }
+}
+
+impl From<DateTime> for Timestamp {
+    fn from(date_time: DateTime) -> Self {
+        Self::Parsed(date_time)
+    }
+}
+
+impl From<String> for Timestamp {
+    /// Parse a timestamp, keeping it as [Timestamp::Unparsed] if it isn't written in one of
+    /// the supported forms.
+    fn from(s: String) -> Self {
+        s.parse().map_or(Self::Unparsed(s), Self::Parsed)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    /// Parse a timestamp, failing if it isn't written in one of the supported forms.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self::Parsed)
+    }
+}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Parsed(a), Self::Parsed(b)) => a.cmp(b),
+            (Self::Unparsed(a), Self::Unparsed(b)) => a.cmp(b),
+            (Self::Unparsed(_), Self::Parsed(_)) => Ordering::Less,
+            (Self::Parsed(_), Self::Unparsed(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parsed(date_time) => date_time.fmt(f),
+            Self::Unparsed(s) => f.write_str(s),
+        }
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = <Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(Self::from(s.into_owned()))
+    }
+}
+
+/// Implement conversions between [Timestamp] and another crate's type, by way of [DateTime].
+#[cfg(any(feature = "chrono", feature = "jiff", feature = "time"))]
+macro_rules! convert_timestamp {
+    ($($ty: ty),* $(,)?) => {
+        $(
+            impl TryFrom<$ty> for Timestamp {
+                type Error = Error;
+
+                fn try_from(value: $ty) -> Result<Self, Self::Error> {
+                    DateTime::try_from(value).map(Self::Parsed)
+                }
+            }
+
+            impl TryFrom<&Timestamp> for $ty {
+                type Error = Error;
+
+                /// Fails for [Timestamp::Unparsed].
+                fn try_from(timestamp: &Timestamp) -> Result<Self, Self::Error> {
+                    timestamp
+                        .date_time()
+                        .map(|date_time| <$ty>::from(*date_time))
+                        .ok_or(Error::Syntax)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::Date> for DateTime {
+    type Error = Error;
+
+    fn try_from(date: time::Date) -> Result<Self, Self::Error> {
+        Self::date(date.year().into(), date.month().into(), date.day())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for DateTime {
+    type Error = Error;
+
+    fn try_from(value: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        Self::try_from(value.date())?.with_time(
+            value.hour(),
+            value.minute(),
+            value.second(),
+            value.nanosecond(),
+            value.offset().whole_seconds(),
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<DateTime> for time::Date {
+    /// The day that the timestamp starts on, in its own offset.
+    fn from(value: DateTime) -> Self {
+        let month = time::Month::try_from(value.month.unwrap_or(1)).expect("valid month");
+
+        time::Date::from_calendar_date(value.year.into(), month, value.day.unwrap_or(1))
+            .expect("valid date")
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<DateTime> for time::OffsetDateTime {
+    /// The moment that the timestamp starts at.
+    fn from(value: DateTime) -> Self {
+        let offset =
+            time::UtcOffset::from_whole_seconds(value.offset_seconds()).expect("valid offset");
+
+        time::OffsetDateTime::from_unix_timestamp(value.unix_timestamp())
+            .and_then(|t| t.replace_nanosecond(value.nanosecond().unwrap_or(0)))
+            .expect("year within range")
+            .to_offset(offset)
+    }
+}
+
+#[cfg(feature = "time")]
+convert_timestamp!(time::Date, time::OffsetDateTime);
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDate> for DateTime {
+    type Error = Error;
+
+    fn try_from(date: chrono::NaiveDate) -> Result<Self, Self::Error> {
+        use chrono::Datelike;
+
+        Self::date(date.year().into(), date.month() as u8, date.day() as u8)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> TryFrom<chrono::DateTime<Tz>> for DateTime {
+    type Error = Error;
+
+    fn try_from(value: chrono::DateTime<Tz>) -> Result<Self, Self::Error> {
+        use chrono::{Offset as _, Timelike};
+
+        let value = value.fixed_offset();
+        // Leap seconds are written as a 60th second, but chrono adds them to the nanoseconds.
+        let (second, nanosecond) = match value.nanosecond() {
+            n if n >= 1_000_000_000 => (60, n - 1_000_000_000),
+            n => (value.second() as u8, n),
+        };
+
+        Self::try_from(value.date_naive())?.with_time(
+            value.hour() as u8,
+            value.minute() as u8,
+            second,
+            nanosecond,
+            value.offset().fix().local_minus_utc(),
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::NaiveDate {
+    /// The day that the timestamp starts on, in its own offset.
+    fn from(value: DateTime) -> Self {
+        chrono::NaiveDate::from_ymd_opt(
+            value.year.into(),
+            value.month.unwrap_or(1).into(),
+            value.day.unwrap_or(1).into(),
+        )
+        .expect("valid date")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::DateTime<chrono::FixedOffset> {
+    /// The moment that the timestamp starts at.
+    fn from(value: DateTime) -> Self {
+        let offset = chrono::FixedOffset::east_opt(value.offset_seconds()).expect("valid offset");
+
+        chrono::DateTime::from_timestamp(value.unix_timestamp(), value.nanosecond().unwrap_or(0))
+            .expect("year within range")
+            .with_timezone(&offset)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::DateTime<chrono::Utc> {
+    /// The moment that the timestamp starts at.
+    fn from(value: DateTime) -> Self {
+        chrono::DateTime::<chrono::FixedOffset>::from(value).to_utc()
+    }
+}
+
+#[cfg(feature = "chrono")]
+convert_timestamp!(
+    chrono::NaiveDate,
+    chrono::DateTime<chrono::FixedOffset>,
+    chrono::DateTime<chrono::Utc>,
+);
+
+#[cfg(feature = "jiff")]
+impl TryFrom<jiff::civil::Date> for DateTime {
+    type Error = Error;
+
+    fn try_from(date: jiff::civil::Date) -> Result<Self, Self::Error> {
+        Self::date(date.year().into(), date.month() as u8, date.day() as u8)
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl TryFrom<jiff::Zoned> for DateTime {
+    type Error = Error;
+
+    fn try_from(value: jiff::Zoned) -> Result<Self, Self::Error> {
+        Self::try_from(value.date())?.with_time(
+            value.hour() as u8,
+            value.minute() as u8,
+            value.second() as u8,
+            value.subsec_nanosecond() as u32,
+            value.offset().seconds(),
+        )
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl TryFrom<jiff::Timestamp> for DateTime {
+    type Error = Error;
+
+    /// Timestamps from jiff are always in UTC.
+    fn try_from(value: jiff::Timestamp) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_zoned(jiff::tz::TimeZone::UTC))
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl From<DateTime> for jiff::civil::Date {
+    /// The day that the timestamp starts on, in its own offset.
+    fn from(value: DateTime) -> Self {
+        jiff::civil::Date::new(
+            value.year as i16,
+            value.month.unwrap_or(1) as i8,
+            value.day.unwrap_or(1) as i8,
+        )
+        .expect("valid date")
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl From<DateTime> for jiff::Timestamp {
+    /// The moment that the timestamp starts at.
+    fn from(value: DateTime) -> Self {
+        let nanosecond = value.nanosecond().unwrap_or(0) as i32;
+
+        jiff::Timestamp::new(value.unix_timestamp(), nanosecond).expect("year within range")
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl From<DateTime> for jiff::Zoned {
+    /// The moment that the timestamp starts at, in its own offset.
+    fn from(value: DateTime) -> Self {
+        let offset = jiff::tz::Offset::from_seconds(value.offset_seconds()).expect("valid offset");
+
+        jiff::Timestamp::from(value).to_zoned(jiff::tz::TimeZone::fixed(offset))
+    }
+}
+
+#[cfg(feature = "jiff")]
+convert_timestamp!(jiff::civil::Date, jiff::Timestamp, jiff::Zoned);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2_0::Feed;
+
+    fn ts(s: &str) -> DateTime {
+        s.parse().expect("valid timestamp")
+    }
+
+    #[test]
+    fn test_timestamp_precision() {
+        let cases = [
+            ("1917", Precision::Year),
+            ("2023-04", Precision::Month),
+            ("2023-04-01", Precision::Day),
+            ("2023-04-01T10:30", Precision::Minute),
+            ("2023-04-01T10:30:15Z", Precision::Second),
+            ("2023-04-01T10:30:15.250+02:00", Precision::Fraction),
+            ("2023-04-01T10:30:15.123456789-05:30", Precision::Fraction),
+        ];
+
+        for (s, precision) in cases {
+            let timestamp = ts(s);
+            assert_eq!(timestamp.precision(), precision, "{s}");
+            assert_eq!(timestamp.to_string(), s);
+        }
+
+        // Alternate forms are written back out in their usual form.
+        assert_eq!(
+            ts("2023-04-01 10:30:15z").to_string(),
+            "2023-04-01T10:30:15Z"
+        );
+        assert_eq!(
+            ts("2023-04-01T10:30+0200").to_string(),
+            "2023-04-01T10:30+02:00"
+        );
+        assert_eq!(
+            ts("2023-04-01T10:30-03").to_string(),
+            "2023-04-01T10:30-03:00"
+        );
+        assert_eq!(ts("2023-04-01T10:30:15,5Z").nanosecond(), Some(500_000_000));
+    }
+
+    #[test]
+    fn test_timestamp_invalid() {
+        assert_eq!("".parse::<DateTime>(), Err(Error::Syntax));
+        assert_eq!("garbage".parse::<DateTime>(), Err(Error::Syntax));
+        assert_eq!("23".parse::<DateTime>(), Err(Error::Syntax));
+        assert_eq!("2023-4-1".parse::<DateTime>(), Err(Error::Syntax));
+        assert_eq!("2023-04-01T10".parse::<DateTime>(), Err(Error::Syntax));
+        assert_eq!(
+            "2023-04-01T10:30.5Z".parse::<DateTime>(),
+            Err(Error::Syntax)
+        );
+        assert_eq!("2023-04-01Z".parse::<DateTime>(), Err(Error::Syntax));
+        assert_eq!(
+            "2023-13".parse::<DateTime>(),
+            Err(Error::OutOfRange("month"))
+        );
+        assert_eq!(
+            "2023-02-29".parse::<DateTime>(),
+            Err(Error::OutOfRange("day"))
+        );
+        assert!("2024-02-29".parse::<DateTime>().is_ok());
+        assert_eq!(
+            "2023-04-01T24:00".parse::<DateTime>(),
+            Err(Error::OutOfRange("hour"))
+        );
+    }
+
+    #[test]
+    fn test_timestamp_order() {
+        assert_eq!(ts("1970-01-01T00:00:00Z").unix_timestamp(), 0);
+        assert_eq!(ts("2000-03-01").unix_timestamp(), 951_868_800);
+        assert_eq!(ts("1969-12-31T23:00:00-01:00").unix_timestamp(), 0);
+        assert_eq!(ts("1597").unix_timestamp(), -11_770_704_000);
+
+        let mut timestamps = [
+            ts("2023-04-01T11:00:00Z"),
+            ts("2023-04"),
+            ts("2023-04-01T12:00:00+02:00"),
+            ts("1851-01-01"),
+            ts("2023"),
+            ts("2023-04-01"),
+        ];
+        timestamps.sort();
+
+        let sorted: Vec<_> = timestamps.iter().map(DateTime::to_string).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "1851-01-01",
+                "2023",
+                "2023-04",
+                "2023-04-01",
+                "2023-04-01T12:00:00+02:00",
+                "2023-04-01T11:00:00Z",
+            ]
+        );
+
+        // Different ways of writing the same moment are still distinct.
+        assert_ne!(ts("2023-04-01T10:00:00Z"), ts("2023-04-01T10:00:00+00:00"));
+        assert_ne!(
+            ts("2023-04-01T10:00:00Z").cmp(&ts("2023-04-01T10:00:00+00:00")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_timestamp_serde() {
+        let timestamp: Timestamp = serde_json::from_str(r#""2023-04-01T10:30Z""#).unwrap();
+        assert_eq!(timestamp, Timestamp::Parsed(ts("2023-04-01T10:30Z")));
+        assert_eq!(
+            serde_json::to_string(&timestamp).unwrap(),
+            r#""2023-04-01T10:30Z""#
+        );
+
+        // Values in other forms are kept as they were written.
+        let timestamp: Timestamp = serde_json::from_str(r#""March 2019""#).unwrap();
+        assert_eq!(timestamp, Timestamp::Unparsed("March 2019".into()));
+        assert_eq!(timestamp.date_time(), None);
+        assert_eq!(
+            serde_json::to_string(&timestamp).unwrap(),
+            r#""March 2019""#
+        );
+        assert!("March 2019".parse::<Timestamp>().is_err());
+        assert!(timestamp < Timestamp::Parsed(ts("1597")));
+
+        assert!(serde_json::from_str::<Timestamp>("2023").is_err());
+    }
+
+    #[test]
+    fn test_unparsed_timestamp_in_feed() {
+        let json = r#"{
+            "metadata": {"title": "Example", "modified": "March 2019"},
+            "links": [],
+            "publications": [{
+                "metadata": {"title": "Book", "published": "1917"},
+                "links": [{
+                    "href": "https://example.com/book.epub",
+                    "rel": "http://opds-spec.org/acquisition/borrow",
+                    "properties": {"availability": {"state": "available", "until": "soon"}}
+                }]
+            }]
+        }"#;
+
+        let feed: Feed<'_> = serde_json::from_str(json).expect("can parse feed");
+        assert_eq!(
+            feed.metadata.modified,
+            Some(Timestamp::Unparsed("March 2019".into()))
+        );
+
+        let publication = &feed.publications[0];
+        assert_eq!(
+            publication.metadata.published,
+            Some(Timestamp::Parsed(ts("1917")))
+        );
+
+        let availability = publication.links[0].properties.availability.as_ref();
+        assert_eq!(
+            availability.and_then(|a| a.until.as_ref()),
+            Some(&Timestamp::Unparsed("soon".into()))
+        );
+
+        let output = serde_json::to_value(&feed).expect("can serialize feed");
+        assert_eq!(output["metadata"]["modified"], "March 2019");
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_conversions() {
+        use time::{Date, Month, OffsetDateTime, Time, UtcOffset};
+
+        let date = Date::from_calendar_date(2023, Month::April, 1).unwrap();
+        let time = Time::from_hms_milli(10, 30, 15, 250).unwrap();
+        let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+        let date_time = date.with_time(time).assume_offset(offset);
+
+        let timestamp = Timestamp::try_from(date_time).unwrap();
+        assert_eq!(timestamp.to_string(), "2023-04-01T10:30:15.25+02:00");
+        assert_eq!(OffsetDateTime::try_from(&timestamp), Ok(date_time));
+
+        let date_time = date.midnight().assume_utc();
+        let timestamp = Timestamp::Parsed(ts("2023-04"));
+        assert_eq!(Date::try_from(&timestamp), Ok(date));
+        assert_eq!(OffsetDateTime::try_from(&timestamp), Ok(date_time));
+        assert_eq!(
+            Timestamp::try_from(date_time).map(|t| t.to_string()),
+            Ok("2023-04-01T00:00:00Z".into())
+        );
+        assert_eq!(
+            Timestamp::try_from(date),
+            Ok(Timestamp::Parsed(ts("2023-04-01")))
+        );
+
+        let ides = Date::from_calendar_date(-44, Month::March, 15).unwrap();
+        assert_eq!(Timestamp::try_from(ides), Err(Error::OutOfRange("year")));
+        assert_eq!(
+            Date::try_from(&Timestamp::Unparsed("March 2019".into())),
+            Err(Error::Syntax)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_conversions() {
+        use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+        let offset = FixedOffset::east_opt(-5 * 3600).unwrap();
+        let date_time = offset.with_ymd_and_hms(2023, 4, 1, 10, 30, 15).unwrap();
+        let timestamp = Timestamp::try_from(date_time).unwrap();
+        assert_eq!(timestamp.to_string(), "2023-04-01T10:30:15-05:00");
+        assert_eq!(
+            chrono::DateTime::<FixedOffset>::try_from(&timestamp),
+            Ok(date_time)
+        );
+        assert_eq!(
+            chrono::DateTime::<Utc>::try_from(&timestamp),
+            Ok(Utc.with_ymd_and_hms(2023, 4, 1, 15, 30, 15).unwrap())
+        );
+
+        let timestamp = Timestamp::Parsed(ts("1917"));
+        assert_eq!(
+            NaiveDate::try_from(&timestamp),
+            Ok(NaiveDate::from_ymd_opt(1917, 1, 1).unwrap())
+        );
+        assert_eq!(
+            NaiveDate::try_from(&Timestamp::Unparsed("March 2019".into())),
+            Err(Error::Syntax)
+        );
+    }
+
+    #[cfg(feature = "jiff")]
+    #[test]
+    fn test_jiff_conversions() {
+        let instant: jiff::Timestamp = "2023-04-01T10:30:15.123Z".parse().unwrap();
+        let timestamp = Timestamp::try_from(instant).unwrap();
+        assert_eq!(timestamp.to_string(), "2023-04-01T10:30:15.123Z");
+        assert_eq!(jiff::Timestamp::try_from(&timestamp), Ok(instant));
+
+        let timestamp = Timestamp::Parsed(ts("2023-04-01T12:30+02:00"));
+        let zoned = jiff::Zoned::try_from(&timestamp).unwrap();
+        assert_eq!(zoned.timestamp(), "2023-04-01T10:30Z".parse().unwrap());
+        assert_eq!(zoned.offset().seconds(), 7200);
+        assert_eq!(
+            Timestamp::try_from(zoned).map(|t| t.to_string()),
+            Ok("2023-04-01T12:30:00+02:00".into())
+        );
+
+        assert_eq!(
+            jiff::civil::Date::try_from(&timestamp),
+            Ok(jiff::civil::date(2023, 4, 1))
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Checking feeds against the rules of the OPDS 2.0 specification
+//!
+//! Parsing a [Feed] only checks that the JSON has the expected shape. The specification also
+//! places requirements on how the parts of a feed relate to each other, such as every
+//! publication needing an acquisition link, which are checked by [Feed::validate].
+//!
+//! Each problem found is reported as an [Issue], which points at the offending part of the
+//! feed using a [JSON Pointer] like `/groups/0/publications/3/links/1`.
+//!
+//! Feeds that should only use the full form of every field, without any unknown fields, can
+//! be parsed with [Feed::from_json_strict]. See [strict] for more information.
+//!
+//! With the `json-schema` feature enabled, documents can also be checked against JSON Schemas
+//! based on the published OPDS 2.0 schemas using `validate_against_schema`, which reports its
+//! problems in the same way.
+//!
+//! [JSON Pointer]: https://www.rfc-editor.org/rfc/rfc6901
+use std::fmt;
+
+use super::*;
+use crate::mime::MediaType;
+
+pub mod strict;
+
+#[cfg(feature = "json-schema")]
+pub mod schema;
+
+#[cfg(feature = "json-schema")]
+pub use schema::validate_against_schema;
+
+/// The image formats that clients are expected to support.
+///
+/// See [Section 2.3: Images][opds-spec-images] for more information.
+///
+/// [opds-spec-images]: https://drafts.opds.io/opds-2.0#23-images
+const IMAGE_TYPES: [&str; 4] = ["image/jpeg", "image/avif", "image/png", "image/gif"];
+
+/// The prefix shared by the acquisition relations defined by OPDS, including the ones that
+/// do not have an [AcquisitionKind], such as `http://opds-spec.org/acquisition/borrow`.
+const ACQUISITION_REL: &str = "http://opds-spec.org/acquisition";
+
+fn is_acquisition(link: &Link<'_>) -> bool {
+    link.get_acquisition().is_some()
+        || link
+            .rel
+            .iter()
+            .any(|rel| rel.as_str().starts_with(ACQUISITION_REL))
+}
+
+/// Escape a field name for use within a JSON pointer.
+pub(crate) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// How serious an [Issue] is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    /// The feed is allowed by the specification, but may not work well with all clients.
+    Warning,
+
+    /// The feed breaks a requirement of the specification.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A requirement that a feed is checked against.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Rule {
+    /// The feed does not have a link with the [Relation::Myself] relation.
+    MissingSelfLink,
+
+    /// A publication does not have any acquisition links.
+    MissingAcquisition,
+
+    /// A link does not have an `href`, and is not marked as [AvailabilityState::Unavailable].
+    MissingHref,
+
+    /// An image link does not have a MIME type.
+    MissingImageType,
+
+    /// None of a publication's images use one of the image formats that clients are
+    /// expected to support.
+    ///
+    /// This is only checked when every image has a MIME type, since the others are reported
+    /// as [Rule::MissingImageType] instead.
+    UnsupportedImageTypes,
+
+    /// A facet does not have any links.
+    EmptyFacet,
+
+    /// A group contains both navigation links and publications.
+    MixedGroup,
+
+    /// An object has a field that is not modeled by this crate.
+    ///
+    /// This is only checked when parsing with [Feed::from_json_strict].
+    UnknownField,
+
+    /// A value is not written in its full form, such as a single object or string in place of
+    /// an array, or an array in place of a single value.
+    ///
+    /// This is only checked when parsing with [Feed::from_json_strict].
+    Shorthand,
+
+    /// A value does not match the keyword of the same name in the JSON Schema.
+    #[cfg(feature = "json-schema")]
+    Schema(schema::Keyword),
+}
+
+impl Rule {
+    /// The identifier for this rule.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingSelfLink => "missing-self-link",
+            Self::MissingAcquisition => "missing-acquisition",
+            Self::MissingHref => "missing-href",
+            Self::MissingImageType => "missing-image-type",
+            Self::UnsupportedImageTypes => "unsupported-image-types",
+            Self::EmptyFacet => "empty-facet",
+            Self::MixedGroup => "mixed-group",
+            Self::UnknownField => "unknown-field",
+            Self::Shorthand => "shorthand",
+            #[cfg(feature = "json-schema")]
+            Self::Schema(keyword) => keyword.rule_id(),
+        }
+    }
+
+    /// How serious it is to break this rule.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::MissingImageType => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            Self::MissingSelfLink => "the feed has no self link",
+            Self::MissingAcquisition => "the publication has no acquisition link",
+            Self::MissingHref => "the link has no href",
+            Self::MissingImageType => "the image has no media type",
+            Self::UnsupportedImageTypes => "none of the images use a widely supported format",
+            Self::EmptyFacet => "the facet has no links",
+            Self::MixedGroup => "the group contains both navigation and publications",
+            Self::UnknownField => "the field is not part of the specification",
+            Self::Shorthand => "the value is written in a shorthand form",
+            #[cfg(feature = "json-schema")]
+            Self::Schema(keyword) => keyword.describe(),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A problem found while validating a feed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Issue {
+    pub severity: Severity,
+
+    /// The rule that was broken.
+    pub rule: Rule,
+
+    /// A JSON pointer to the offending part of the feed, such as `/publications/3/links`.
+    pub pointer: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}] {}: {}",
+            self.severity,
+            self.rule,
+            self.pointer,
+            self.rule.describe()
+        )
+    }
+}
+
+/// Every problem found while validating a feed.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct Report {
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    /// Whether no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Whether any of the problems found break a requirement of the specification.
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    fn push(&mut self, rule: Rule, pointer: String) {
+        let severity = rule.severity();
+        self.issues.push(Issue {
+            severity,
+            rule,
+            pointer,
+        });
+    }
+
+    fn links(&mut self, path: &str, links: &[Link<'_>]) {
+        for (i, l) in links.iter().enumerate() {
+            self.link(&format!("{path}/{i}"), l);
+        }
+    }
+
+    fn link(&mut self, path: &str, link: &Link<'_>) {
+        let unavailable = link
+            .properties
+            .availability
+            .as_ref()
+            .is_some_and(|a| a.state == AvailabilityState::Unavailable);
+
+        if link.href.is_none() && !unavailable {
+            self.push(Rule::MissingHref, path.to_string());
+        }
+
+        self.links(&format!("{path}/alternate"), &link.alternate);
+        self.links(&format!("{path}/children"), &link.children);
+    }
+
+    fn facet(&mut self, path: &str, facet: &Facet<'_>) {
+        if facet.links.is_empty() {
+            self.push(Rule::EmptyFacet, format!("{path}/links"));
+        }
+
+        self.links(&format!("{path}/links"), &facet.links);
+    }
+
+    fn publications(&mut self, path: &str, publications: &[Publication<'_>]) {
+        for (i, p) in publications.iter().enumerate() {
+            self.publication(&format!("{path}/{i}"), p);
+        }
+    }
+
+    fn publication(&mut self, path: &str, publication: &Publication<'_>) {
+        if !publication.links.iter().any(is_acquisition) {
+            self.push(Rule::MissingAcquisition, format!("{path}/links"));
+        }
+
+        self.links(&format!("{path}/links"), &publication.links);
+        self.links(&format!("{path}/images"), &publication.images);
+
+        for (i, image) in publication.images.iter().enumerate() {
+            if image.mime.is_none() {
+                self.push(Rule::MissingImageType, format!("{path}/images/{i}"));
+            }
+        }
+
+        let typed = publication.images.iter().all(|image| image.mime.is_some());
+        let supported = publication.images.iter().any(|image| {
+            image.mime.as_deref().is_some_and(|mime| {
+                MediaType::parse(mime).is_ok_and(|mime| IMAGE_TYPES.iter().any(|ty| mime.is(ty)))
+            })
+        });
+
+        if !publication.images.is_empty() && typed && !supported {
+            self.push(Rule::UnsupportedImageTypes, format!("{path}/images"));
+        }
+    }
+
+    fn group(&mut self, path: &str, group: &FeedGroup<'_>) {
+        if !group.navigation.is_empty() && !group.publications.is_empty() {
+            self.push(Rule::MixedGroup, path.to_string());
+        }
+
+        self.links(&format!("{path}/links"), &group.links);
+        self.links(&format!("{path}/navigation"), &group.navigation);
+        self.publications(&format!("{path}/publications"), &group.publications);
+    }
+}
+
+impl Feed<'_> {
+    /// Check this feed against the rules of the OPDS 2.0 specification.
+    ///
+    /// See [crate::v2_0::validate] for more information.
+    pub fn validate(&self) -> Report {
+        let mut report = Report::default();
+
+        if !self.links.iter().any(|l| l.rel.contains(&Relation::Myself)) {
+            report.push(Rule::MissingSelfLink, "/links".into());
+        }
+
+        report.links("/links", &self.links);
+        report.links("/navigation", &self.navigation);
+
+        for (i, f) in self.facets.iter().enumerate() {
+            report.facet(&format!("/facets/{i}"), f);
+        }
+
+        report.publications("/publications", &self.publications);
+
+        for (i, g) in self.groups.iter().enumerate() {
+            report.group(&format!("/groups/{i}"), g);
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn pointers(report: &Report) -> Vec<(Rule, &str)> {
+        report
+            .issues
+            .iter()
+            .map(|i| (i.rule, i.pointer.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_feeds() {
+        for name in ["test-feed-opds-io", "test-feed-archive-org"] {
+            let json = std::fs::read_to_string(format!("{CRATE_DIR}/tests/{name}.in.json"))
+                .expect("valid file input");
+            let feed: Feed<'_> = serde_json::from_str(&json).expect("can parse feed");
+            let report = feed.validate();
+
+            assert!(
+                !report.has_errors(),
+                "{name} has errors: {:?}",
+                report.issues
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_issues() {
+        let json = r#"{
+            "metadata": {"title": "Example"},
+            "links": [{"rel": "next", "href": "https://example.com/2.json"}],
+            "facets": [{"metadata": {"title": "Genre"}, "links": []}],
+            "groups": [{
+                "metadata": {"title": "Mixed"},
+                "navigation": [{"href": "https://example.com/new.json", "title": "New"}],
+                "publications": [{
+                    "metadata": {"title": "Book"},
+                    "links": [
+                        {"rel": "http://opds-spec.org/acquisition/open-access"},
+                        {
+                            "rel": "http://opds-spec.org/acquisition/borrow",
+                            "properties": {"availability": {"state": "unavailable"}}
+                        }
+                    ],
+                    "images": [{"href": "https://example.com/cover.tiff", "type": "image/tiff"}]
+                }, {
+                    "metadata": {"title": "Other"},
+                    "links": [{"rel": "alternate", "href": "https://example.com/other"}],
+                    "images": [{"href": "https://example.com/cover"}]
+                }]
+            }]
+        }"#;
+
+        let feed: Feed<'_> = serde_json::from_str(json).expect("can parse feed");
+        let report = feed.validate();
+
+        assert!(report.has_errors());
+        assert_eq!(
+            pointers(&report),
+            vec![
+                (Rule::MissingSelfLink, "/links"),
+                (Rule::EmptyFacet, "/facets/0/links"),
+                (Rule::MixedGroup, "/groups/0"),
+                (Rule::MissingHref, "/groups/0/publications/0/links/0"),
+                (
+                    Rule::UnsupportedImageTypes,
+                    "/groups/0/publications/0/images"
+                ),
+                (Rule::MissingAcquisition, "/groups/0/publications/1/links"),
+                (Rule::MissingImageType, "/groups/0/publications/1/images/0"),
+            ]
+        );
+        assert_eq!(
+            report.issues[6].to_string(),
+            "warning[missing-image-type] /groups/0/publications/1/images/0: \
+             the image has no media type"
+        );
+    }
+
+    #[test]
+    fn test_validate_image_types() {
+        let feed = |images: &str| {
+            format!(
+                r#"{{
+                    "metadata": {{"title": "Example"}},
+                    "links": [{{"rel": "self", "href": "https://example.com/feed.json"}}],
+                    "publications": [{{
+                        "metadata": {{"title": "Book"}},
+                        "links": [{{
+                            "rel": "http://opds-spec.org/acquisition",
+                            "href": "https://example.com/book.epub"
+                        }}],
+                        "images": {images}
+                    }}]
+                }}"#
+            )
+        };
+
+        let json = feed(r#"[{"href": "https://example.com/a.png", "type": "IMAGE/PNG; q=1"}]"#);
+        let feed_in: Feed<'_> = serde_json::from_str(&json).expect("can parse feed");
+        assert!(feed_in.validate().is_empty());
+
+        let json = feed(
+            r#"[{"href": "https://example.com/a.webp", "type": "image/webp"},
+                {"href": "https://example.com/a.jxl", "type": "image/jxl"}]"#,
+        );
+        let feed_in: Feed<'_> = serde_json::from_str(&json).expect("can parse feed");
+        assert_eq!(
+            pointers(&feed_in.validate()),
+            vec![(Rule::UnsupportedImageTypes, "/publications/0/images")]
+        );
+    }
+}
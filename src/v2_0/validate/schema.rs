@@ -0,0 +1,786 @@
+//! Checking feeds against JSON Schemas for OPDS 2.0
+//!
+//! The crate includes the schemas for OPDS 2.0 and the Readium Web Publication Manifest, so
+//! that feeds can be checked without network access. See `schemas/README.md` in the crate's
+//! source for where they come from and how to update them. The validation keywords of
+//! [JSON Schema draft-07] are supported, while unknown formats are accepted.
+//!
+//! [JSON Schema draft-07]: https://json-schema.org/specification-links#draft-7
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+use serde_json::Value;
+use url::Url;
+
+use super::{Report, Rule, escape};
+use crate::template::UriTemplate;
+
+/// The schema that documents are checked against by [validate_against_schema].
+const FEED_SCHEMA: &str = "https://drafts.opds.io/schema/feed.schema.json";
+
+const SCHEMAS: [&str; 13] = [
+    include_str!("../../../schemas/opds/acquisition-object.schema.json"),
+    include_str!("../../../schemas/opds/feed-metadata.schema.json"),
+    include_str!("../../../schemas/opds/feed.schema.json"),
+    include_str!("../../../schemas/opds/properties.schema.json"),
+    include_str!("../../../schemas/opds/publication.schema.json"),
+    include_str!("../../../schemas/readium/a11y.schema.json"),
+    include_str!("../../../schemas/readium/contributor-object.schema.json"),
+    include_str!("../../../schemas/readium/contributor.schema.json"),
+    include_str!("../../../schemas/readium/language-map.schema.json"),
+    include_str!("../../../schemas/readium/link.schema.json"),
+    include_str!("../../../schemas/readium/metadata.schema.json"),
+    include_str!("../../../schemas/readium/subject-object.schema.json"),
+    include_str!("../../../schemas/readium/subject.schema.json"),
+];
+
+/// The included schemas, keyed by their `$id`.
+fn registry() -> &'static BTreeMap<String, Value> {
+    static REGISTRY: OnceLock<BTreeMap<String, Value>> = OnceLock::new();
+
+    REGISTRY.get_or_init(|| {
+        SCHEMAS
+            .iter()
+            .map(|s| {
+                let schema: Value = serde_json::from_str(s).expect("included schema is valid");
+                let id = schema["$id"].as_str().expect("included schema has an $id");
+                (id.to_string(), schema)
+            })
+            .collect()
+    })
+}
+
+/// The compiled form of a `pattern` or `patternProperties` regular expression.
+///
+/// The schemas only use a handful of expressions, so each is compiled once and kept for
+/// later checks. Expressions using features of ECMA-262 regular expressions that can't be
+/// compiled here, like lookahead, give [None].
+fn regex(pattern: &str) -> Option<Regex> {
+    static PATTERNS: OnceLock<Mutex<BTreeMap<String, Option<Regex>>>> = OnceLock::new();
+
+    let mut patterns = PATTERNS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+
+    patterns
+        .entry(pattern.to_string())
+        .or_insert_with(|| Regex::new(pattern).ok())
+        .clone()
+}
+
+/// Whether `s` matches `pattern`, or [None] if the pattern can't be compiled.
+fn is_match(pattern: &str, s: &str) -> Option<bool> {
+    regex(pattern).map(|re| re.is_match(s))
+}
+
+/// The schema keyword that a value failed to match.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Keyword {
+    Ref,
+    Type,
+    Enum,
+    Const,
+    Format,
+    MinLength,
+    MaxLength,
+    Pattern,
+    MultipleOf,
+    Minimum,
+    Maximum,
+    ExclusiveMinimum,
+    ExclusiveMaximum,
+    Required,
+    AdditionalProperties,
+    PropertyNames,
+    Dependencies,
+    MinProperties,
+    MaxProperties,
+    AdditionalItems,
+    MinItems,
+    MaxItems,
+    UniqueItems,
+    Contains,
+    AnyOf,
+    OneOf,
+    Not,
+}
+
+impl Keyword {
+    /// The name of this keyword within a schema.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ref => "$ref",
+            Self::Type => "type",
+            Self::Enum => "enum",
+            Self::Const => "const",
+            Self::Format => "format",
+            Self::MinLength => "minLength",
+            Self::MaxLength => "maxLength",
+            Self::Pattern => "pattern",
+            Self::MultipleOf => "multipleOf",
+            Self::Minimum => "minimum",
+            Self::Maximum => "maximum",
+            Self::ExclusiveMinimum => "exclusiveMinimum",
+            Self::ExclusiveMaximum => "exclusiveMaximum",
+            Self::Required => "required",
+            Self::AdditionalProperties => "additionalProperties",
+            Self::PropertyNames => "propertyNames",
+            Self::Dependencies => "dependencies",
+            Self::MinProperties => "minProperties",
+            Self::MaxProperties => "maxProperties",
+            Self::AdditionalItems => "additionalItems",
+            Self::MinItems => "minItems",
+            Self::MaxItems => "maxItems",
+            Self::UniqueItems => "uniqueItems",
+            Self::Contains => "contains",
+            Self::AnyOf => "anyOf",
+            Self::OneOf => "oneOf",
+            Self::Not => "not",
+        }
+    }
+
+    /// The identifier of the [Rule] for this keyword.
+    pub(super) fn rule_id(&self) -> &'static str {
+        match self {
+            Self::Ref => "schema-ref",
+            Self::Type => "schema-type",
+            Self::Enum => "schema-enum",
+            Self::Const => "schema-const",
+            Self::Format => "schema-format",
+            Self::MinLength => "schema-min-length",
+            Self::MaxLength => "schema-max-length",
+            Self::Pattern => "schema-pattern",
+            Self::MultipleOf => "schema-multiple-of",
+            Self::Minimum => "schema-minimum",
+            Self::Maximum => "schema-maximum",
+            Self::ExclusiveMinimum => "schema-exclusive-minimum",
+            Self::ExclusiveMaximum => "schema-exclusive-maximum",
+            Self::Required => "schema-required",
+            Self::AdditionalProperties => "schema-additional-properties",
+            Self::PropertyNames => "schema-property-names",
+            Self::Dependencies => "schema-dependencies",
+            Self::MinProperties => "schema-min-properties",
+            Self::MaxProperties => "schema-max-properties",
+            Self::AdditionalItems => "schema-additional-items",
+            Self::MinItems => "schema-min-items",
+            Self::MaxItems => "schema-max-items",
+            Self::UniqueItems => "schema-unique-items",
+            Self::Contains => "schema-contains",
+            Self::AnyOf => "schema-any-of",
+            Self::OneOf => "schema-one-of",
+            Self::Not => "schema-not",
+        }
+    }
+
+    pub(super) fn describe(&self) -> &'static str {
+        match self {
+            Self::Ref => "the schema refers to an unknown schema",
+            Self::Type => "the value has the wrong type",
+            Self::Enum => "the value is not one of the allowed values",
+            Self::Const => "the value is not the expected value",
+            Self::Format => "the value is not in the expected format",
+            Self::MinLength => "the string is too short",
+            Self::MaxLength => "the string is too long",
+            Self::Pattern => {
+                "the string does not match the expected pattern, or the pattern is invalid"
+            }
+            Self::MultipleOf => "the number is not a multiple of the expected value",
+            Self::Minimum | Self::ExclusiveMinimum => "the number is too small",
+            Self::Maximum | Self::ExclusiveMaximum => "the number is too large",
+            Self::Required => "a required property is missing",
+            Self::AdditionalProperties => "the property is not allowed",
+            Self::PropertyNames => "the property name is not allowed",
+            Self::Dependencies => "the object is missing a property that this one depends on",
+            Self::MinProperties => "the object has too few properties",
+            Self::MaxProperties => "the object has too many properties",
+            Self::AdditionalItems => "the array has more items than allowed",
+            Self::MinItems => "the array has too few items",
+            Self::MaxItems => "the array has too many items",
+            Self::UniqueItems => "the array contains duplicate items",
+            Self::Contains => "the array does not contain a matching item",
+            Self::AnyOf => "the value does not match any of the allowed schemas",
+            Self::OneOf => "the value does not match exactly one of the allowed schemas",
+            Self::Not => "the value matches a disallowed schema",
+        }
+    }
+}
+
+impl fmt::Display for Keyword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn is_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Check for a full date as described by [RFC 3339], such as `2024-01-31`.
+///
+/// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339#section-5.6
+fn is_date(s: &str) -> bool {
+    let mut parts = s.split('-');
+
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+
+    if !is_digits(year) || !is_digits(month) || !is_digits(day) {
+        return false;
+    }
+
+    matches!(month.parse::<u8>(), Ok(1..=12)) && matches!(day.parse::<u8>(), Ok(1..=31))
+}
+
+/// Check for a time of day as described by [RFC 3339], such as `12:30:00.5Z`.
+///
+/// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339#section-5.6
+fn is_time(s: &str) -> bool {
+    let (time, offset) = if let Some(time) = s.strip_suffix(['Z', 'z']) {
+        (time, None)
+    } else if let Some(i) = s.rfind(['+', '-']) {
+        (&s[..i], Some(&s[i + 1..]))
+    } else {
+        return false;
+    };
+
+    let is_hhmm = |s: &str| matches!(s.split_once(':'), Some((h, m)) if h.len() == 2 && m.len() == 2 && is_digits(h) && is_digits(m));
+
+    if offset.is_some_and(|offset| !is_hhmm(offset)) {
+        return false;
+    }
+
+    let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+
+    match time.rsplit_once(':') {
+        Some((hhmm, ss)) => is_hhmm(hhmm) && ss.len() == 2 && is_digits(ss) && is_digits(fraction),
+        None => false,
+    }
+}
+
+fn is_format(value: &str, format: &str) -> bool {
+    match format {
+        "uri" => Url::parse(value).is_ok(),
+        "uri-reference" => {
+            let base = Url::parse("https://example.invalid/").expect("valid base URL");
+            base.join(value).is_ok()
+        }
+        "uri-template" => UriTemplate::parse(value).is_ok(),
+        "date" => is_date(value),
+        "date-time" => match value.split_once(['T', 't']) {
+            Some((date, time)) => is_date(date) && is_time(time),
+            None => false,
+        },
+        _ => true,
+    }
+}
+
+/// Walks a value alongside the schema that it should match.
+struct Validator<'r> {
+    report: &'r mut Report,
+}
+
+impl Validator<'_> {
+    fn push(&mut self, keyword: Keyword, pointer: String) {
+        self.report.push(Rule::Schema(keyword), pointer);
+    }
+
+    /// Whether `value` matches `schema`, without reporting any issues.
+    fn matches(schema: &Value, base: &Url, value: &Value) -> bool {
+        let mut report = Report::default();
+        Validator {
+            report: &mut report,
+        }
+        .validate(schema, base, value, "");
+        report.is_empty()
+    }
+
+    fn validate(&mut self, schema: &Value, base: &Url, value: &Value, pointer: &str) {
+        let Some(schema) = schema.as_object() else {
+            return;
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let resolved = base.join(reference).ok().and_then(|mut url| {
+                let fragment = url.fragment().unwrap_or_default().to_string();
+                url.set_fragment(None);
+
+                let schema = registry().get(url.as_str())?.pointer(&fragment)?;
+                Some((url, schema))
+            });
+
+            match resolved {
+                Some((base, schema)) => self.validate(schema, &base, value, pointer),
+                None => self.push(Keyword::Ref, pointer.to_string()),
+            }
+
+            // Other keywords are ignored alongside "$ref" in draft-07.
+            return;
+        }
+
+        let number = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+        let count = |keyword: &str| schema.get(keyword).and_then(Value::as_u64);
+
+        match schema.get("type") {
+            Some(Value::String(ty)) if !is_type(value, ty) => {
+                self.push(Keyword::Type, pointer.to_string());
+                return;
+            }
+            Some(Value::Array(tys))
+                if !tys
+                    .iter()
+                    .flat_map(Value::as_str)
+                    .any(|ty| is_type(value, ty)) =>
+            {
+                self.push(Keyword::Type, pointer.to_string());
+                return;
+            }
+            _ => {}
+        }
+
+        if let Some(Value::Array(values)) = schema.get("enum")
+            && !values.contains(value)
+        {
+            self.push(Keyword::Enum, pointer.to_string());
+        }
+
+        if schema.get("const").is_some_and(|c| c != value) {
+            self.push(Keyword::Const, pointer.to_string());
+        }
+
+        match value {
+            Value::String(s) => {
+                let format = schema.get("format").and_then(Value::as_str);
+                if format.is_some_and(|format| !is_format(s, format)) {
+                    self.push(Keyword::Format, pointer.to_string());
+                }
+
+                let len = s.chars().count() as u64;
+                if count("minLength").is_some_and(|min| len < min) {
+                    self.push(Keyword::MinLength, pointer.to_string());
+                }
+                if count("maxLength").is_some_and(|max| len > max) {
+                    self.push(Keyword::MaxLength, pointer.to_string());
+                }
+
+                let pattern = schema.get("pattern").and_then(Value::as_str);
+                if pattern.is_some_and(|pattern| is_match(pattern, s) != Some(true)) {
+                    self.push(Keyword::Pattern, pointer.to_string());
+                }
+            }
+            Value::Number(n) => {
+                let n = n.as_f64().unwrap_or_default();
+
+                if number("minimum").is_some_and(|min| n < min) {
+                    self.push(Keyword::Minimum, pointer.to_string());
+                }
+                if number("maximum").is_some_and(|max| n > max) {
+                    self.push(Keyword::Maximum, pointer.to_string());
+                }
+                if number("exclusiveMinimum").is_some_and(|min| n <= min) {
+                    self.push(Keyword::ExclusiveMinimum, pointer.to_string());
+                }
+                if number("exclusiveMaximum").is_some_and(|max| n >= max) {
+                    self.push(Keyword::ExclusiveMaximum, pointer.to_string());
+                }
+                if number("multipleOf").is_some_and(|m| m > 0.0 && (n / m).fract() != 0.0) {
+                    self.push(Keyword::MultipleOf, pointer.to_string());
+                }
+            }
+            Value::Object(object) => {
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let patterns = schema.get("patternProperties").and_then(Value::as_object);
+
+                if patterns
+                    .into_iter()
+                    .flatten()
+                    .any(|(pattern, _)| regex(pattern).is_none())
+                {
+                    self.push(Keyword::Pattern, pointer.to_string());
+                }
+
+                for name in schema
+                    .get("required")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .flat_map(Value::as_str)
+                {
+                    if !object.contains_key(name) {
+                        self.push(Keyword::Required, format!("{pointer}/{}", escape(name)));
+                    }
+                }
+
+                for (key, v) in object {
+                    let path = format!("{pointer}/{}", escape(key));
+
+                    if let Some(names) = schema.get("propertyNames")
+                        && !Self::matches(names, base, &Value::String(key.clone()))
+                    {
+                        self.push(Keyword::PropertyNames, path.clone());
+                    }
+
+                    match schema.get("dependencies").and_then(|d| d.get(key)) {
+                        Some(Value::Array(names)) => {
+                            for name in names.iter().flat_map(Value::as_str) {
+                                if !object.contains_key(name) {
+                                    let path = format!("{pointer}/{}", escape(name));
+                                    self.push(Keyword::Dependencies, path);
+                                }
+                            }
+                        }
+                        Some(dependency) => self.validate(dependency, base, value, pointer),
+                        None => {}
+                    }
+                    let property = properties.and_then(|p| p.get(key));
+                    let matching: Vec<_> = patterns
+                        .into_iter()
+                        .flatten()
+                        .filter(|(pattern, _)| is_match(pattern, key) == Some(true))
+                        .map(|(_, schema)| schema)
+                        .collect();
+
+                    for schema in property.into_iter().chain(matching.iter().copied()) {
+                        self.validate(schema, base, v, &path);
+                    }
+
+                    if property.is_some() || !matching.is_empty() {
+                        continue;
+                    }
+
+                    match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => self.push(Keyword::AdditionalProperties, path),
+                        Some(additional) => self.validate(additional, base, v, &path),
+                        None => {}
+                    }
+                }
+
+                let len = object.len() as u64;
+                if count("minProperties").is_some_and(|min| len < min) {
+                    self.push(Keyword::MinProperties, pointer.to_string());
+                }
+                if count("maxProperties").is_some_and(|max| len > max) {
+                    self.push(Keyword::MaxProperties, pointer.to_string());
+                }
+            }
+            Value::Array(items) => {
+                match schema.get("items") {
+                    Some(Value::Array(schemas)) => {
+                        for (i, (item, schema)) in items.iter().zip(schemas).enumerate() {
+                            self.validate(schema, base, item, &format!("{pointer}/{i}"));
+                        }
+
+                        for (i, item) in items.iter().enumerate().skip(schemas.len()) {
+                            let path = format!("{pointer}/{i}");
+
+                            match schema.get("additionalItems") {
+                                Some(Value::Bool(false)) => {
+                                    self.push(Keyword::AdditionalItems, path)
+                                }
+                                Some(additional) => self.validate(additional, base, item, &path),
+                                None => {}
+                            }
+                        }
+                    }
+                    Some(schema) => {
+                        for (i, item) in items.iter().enumerate() {
+                            self.validate(schema, base, item, &format!("{pointer}/{i}"));
+                        }
+                    }
+                    None => {}
+                }
+
+                if let Some(contains) = schema.get("contains")
+                    && !items.iter().any(|item| Self::matches(contains, base, item))
+                {
+                    self.push(Keyword::Contains, pointer.to_string());
+                }
+
+                let len = items.len() as u64;
+                if count("minItems").is_some_and(|min| len < min) {
+                    self.push(Keyword::MinItems, pointer.to_string());
+                }
+                if count("maxItems").is_some_and(|max| len > max) {
+                    self.push(Keyword::MaxItems, pointer.to_string());
+                }
+
+                let unique = schema.get("uniqueItems") == Some(&Value::Bool(true));
+                if unique
+                    && items
+                        .iter()
+                        .enumerate()
+                        .any(|(i, item)| items[..i].contains(item))
+                {
+                    self.push(Keyword::UniqueItems, pointer.to_string());
+                }
+            }
+            _ => {}
+        }
+
+        for schema in schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            self.validate(schema, base, value, pointer);
+        }
+
+        if let Some(schemas) = schema.get("anyOf").and_then(Value::as_array)
+            && !schemas.iter().any(|s| Self::matches(s, base, value))
+        {
+            self.push(Keyword::AnyOf, pointer.to_string());
+        }
+
+        if let Some(schemas) = schema.get("oneOf").and_then(Value::as_array)
+            && schemas
+                .iter()
+                .filter(|s| Self::matches(s, base, value))
+                .count()
+                != 1
+        {
+            self.push(Keyword::OneOf, pointer.to_string());
+        }
+
+        if let Some(not) = schema.get("not")
+            && Self::matches(not, base, value)
+        {
+            self.push(Keyword::Not, pointer.to_string());
+        }
+
+        if let Some(condition) = schema.get("if") {
+            let branch = if Self::matches(condition, base, value) {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+
+            if let Some(branch) = branch {
+                self.validate(branch, base, value, pointer);
+            }
+        }
+    }
+}
+
+/// Check a JSON document against the OPDS 2.0 feed schema.
+///
+/// Unlike [Feed::validate][super::Feed::validate], this works on the raw JSON, and so can
+/// also find problems that would prevent the document from being parsed as a feed. Each
+/// issue reported is for a [Rule::Schema], and points at the value that did not match.
+pub fn validate_against_schema(value: &Value) -> Report {
+    let mut report = Report::default();
+    let base = Url::parse(FEED_SCHEMA).expect("valid schema URL");
+    let schema = &registry()[FEED_SCHEMA];
+
+    Validator {
+        report: &mut report,
+    }
+    .validate(schema, &base, value, "");
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn pointers(report: &Report) -> Vec<(Rule, &str)> {
+        report
+            .issues
+            .iter()
+            .map(|i| (i.rule, i.pointer.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_schema_feeds() {
+        let json = std::fs::read_to_string(format!("{CRATE_DIR}/tests/test-feed-opds-io.in.json"))
+            .expect("valid file input");
+        let value: Value = serde_json::from_str(&json).expect("can parse JSON");
+        let report = validate_against_schema(&value);
+
+        assert!(report.is_empty(), "unexpected issues: {:?}", report.issues);
+    }
+
+    #[test]
+    fn test_schema_issues() {
+        let json = r#"{
+            "metadata": {"title": "Example", "numberOfItems": -1, "modified": "yesterday"},
+            "links": [{"rel": "next", "href": "https://example.com/2.json"}],
+            "navigation": [{"href": "https://example.com/new.json"}],
+            "publications": [{
+                "metadata": {
+                    "title": "Book",
+                    "accessibility": {"hazard": ["explosions"]}
+                },
+                "links": [{
+                    "rel": "http://opds-spec.org/acquisition",
+                    "href": "https://example.com/book.epub",
+                    "properties": {"price": {"value": 1.99, "currency": "XYZ"}}
+                }, {
+                    "rel": "related",
+                    "href": "https://example.com/{id}",
+                    "templated": "yes"
+                }]
+            }]
+        }"#;
+
+        let value: Value = serde_json::from_str(json).expect("can parse JSON");
+        let report = validate_against_schema(&value);
+
+        assert!(report.has_errors());
+        assert_eq!(
+            pointers(&report),
+            vec![
+                (Rule::Schema(Keyword::Minimum), "/metadata/numberOfItems"),
+                (Rule::Schema(Keyword::Format), "/metadata/modified"),
+                (Rule::Schema(Keyword::Contains), "/links"),
+                (Rule::Schema(Keyword::Required), "/navigation/0/title"),
+                (
+                    Rule::Schema(Keyword::Enum),
+                    "/publications/0/metadata/accessibility/hazard/0"
+                ),
+                (
+                    Rule::Schema(Keyword::Enum),
+                    "/publications/0/links/0/properties/price/currency"
+                ),
+                (
+                    Rule::Schema(Keyword::Type),
+                    "/publications/0/links/1/templated"
+                ),
+            ]
+        );
+        assert_eq!(
+            report.issues[3].to_string(),
+            "error[schema-required] /navigation/0/title: a required property is missing"
+        );
+    }
+
+    #[test]
+    fn test_schema_patterns() {
+        let feed = |title: Value| {
+            serde_json::json!({
+                "metadata": {"title": title},
+                "links": [{"rel": "self", "href": "https://example.com/feed.json"}],
+                "navigation": [{"href": "https://example.com/new.json", "title": "New"}]
+            })
+        };
+
+        let report = validate_against_schema(&feed(serde_json::json!({"en": "A", "zh-Hant": "B"})));
+        assert!(report.is_empty(), "unexpected issues: {:?}", report.issues);
+
+        let report =
+            validate_against_schema(&feed(serde_json::json!({"en": "A", "not a tag!": "B"})));
+        assert_eq!(
+            pointers(&report),
+            vec![(Rule::Schema(Keyword::AnyOf), "/metadata/title")]
+        );
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"pattern": "^urn:"}},
+            "patternProperties": {"^x-": {"type": "number"}},
+            "additionalProperties": false
+        });
+        let value = serde_json::json!({"id": "isbn:1", "x-count": "two", "x-size": 2, "other": 1});
+        let base = Url::parse(FEED_SCHEMA).unwrap();
+        let mut report = Report::default();
+        Validator {
+            report: &mut report,
+        }
+        .validate(&schema, &base, &value, "");
+
+        assert_eq!(
+            pointers(&report),
+            vec![
+                (Rule::Schema(Keyword::Pattern), "/id"),
+                (Rule::Schema(Keyword::Type), "/x-count"),
+                (Rule::Schema(Keyword::AdditionalProperties), "/other"),
+            ]
+        );
+    }
+
+    fn check(schema: Value, value: Value) -> Report {
+        let base = Url::parse(FEED_SCHEMA).unwrap();
+        let mut report = Report::default();
+        Validator {
+            report: &mut report,
+        }
+        .validate(&schema, &base, &value, "");
+        report
+    }
+
+    #[test]
+    fn test_schema_invalid_patterns() {
+        let schema = serde_json::json!({"pattern": "^(?!draft)"});
+        let report = check(schema, serde_json::json!("final"));
+        assert!(report.has_errors());
+        assert_eq!(
+            pointers(&report),
+            vec![(Rule::Schema(Keyword::Pattern), "")]
+        );
+
+        let schema = serde_json::json!({"patternProperties": {"^(?!x-)": {"type": "string"}}});
+        let report = check(schema, serde_json::json!({"id": 1}));
+        assert_eq!(
+            pointers(&report),
+            vec![(Rule::Schema(Keyword::Pattern), "")]
+        );
+    }
+
+    #[test]
+    fn test_schema_keywords() {
+        let schema = serde_json::json!({
+            "properties": {
+                "size": {"multipleOf": 0.5},
+                "pair": {"items": [{"type": "string"}, {"type": "number"}], "additionalItems": false}
+            },
+            "propertyNames": {"maxLength": 6},
+            "dependencies": {"height": ["width"], "size": {"required": ["unit"]}}
+        });
+        let value = serde_json::json!({
+            "size": 1.25,
+            "pair": ["a", 1, true],
+            "height": 2,
+            "toolong": 0
+        });
+
+        assert_eq!(
+            pointers(&check(schema, value)),
+            vec![
+                (Rule::Schema(Keyword::Required), "/unit"),
+                (Rule::Schema(Keyword::MultipleOf), "/size"),
+                (Rule::Schema(Keyword::AdditionalItems), "/pair/2"),
+                (Rule::Schema(Keyword::Dependencies), "/width"),
+                (Rule::Schema(Keyword::PropertyNames), "/toolong"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,550 @@
+//! Strict parsing of feeds
+//!
+//! By default, parsing a [Feed] is forgiving: fields that are not modeled by this crate are
+//! kept in [extensions], and many fields accept shorthand forms, such
+//! as a single string for a contributor's name in place of an array of contributor objects.
+//!
+//! [Feed::from_json_strict] instead rejects any document that does not use the full form of
+//! every field. A document passes when every part of it is written the same way that this
+//! crate would write it back out, except that fields like `rel` and `@context`, which this
+//! crate writes as a single string when there is only one value, must always be arrays.
+use std::fmt;
+
+use serde::ser::{self, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use super::*;
+use crate::helpers::SINGLETON_KEY;
+use crate::v2_0::extensions::EXTENSION_KEY;
+
+/// An error encountered while strictly parsing a feed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The document could not be parsed as a feed at all.
+    Json(serde_json::Error),
+
+    /// The document is a feed, but uses unknown fields or shorthand forms.
+    Invalid(Report),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "invalid feed: {e}"),
+            Self::Invalid(report) => {
+                write!(f, "feed is not strictly valid")?;
+
+                for issue in report.issues.iter() {
+                    write!(f, "\n{issue}")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(e) => Some(e),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// Whether a value is one that this crate leaves out when writing a document.
+fn is_omitted(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::Array(values) => values.is_empty(),
+        Value::Object(values) => values.is_empty(),
+        _ => false,
+    }
+}
+
+/// Serializes values into [Value]s in the same way as [serde_json::to_value], except that
+/// the entries of every [Extensions] map are left out, and single values written in place of
+/// an array are kept in the array.
+///
+/// [Extensions]: crate::v2_0::extensions::Extensions
+struct WithoutExtensions;
+
+macro_rules! forward {
+    ($($method: ident($ty: ty)),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Value, serde_json::Error> {
+                serde_json::value::Serializer.$method(v)
+            }
+        )*
+    };
+}
+
+impl Serializer for WithoutExtensions {
+    type Ok = Value;
+    type Error = serde_json::Error;
+    type SerializeSeq = SeqWithoutExtensions;
+    type SerializeTuple = SeqWithoutExtensions;
+    type SerializeTupleStruct = SeqWithoutExtensions;
+    type SerializeTupleVariant = Variant<SeqWithoutExtensions>;
+    type SerializeMap = MapWithoutExtensions;
+    type SerializeStruct = MapWithoutExtensions;
+    type SerializeStructVariant = Variant<MapWithoutExtensions>;
+
+    forward!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    );
+
+    fn serialize_none(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, Self::Error> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        if name == EXTENSION_KEY {
+            // Only map keys are wrapped like this, and no other key is written as null, so
+            // this marks the entry to be left out.
+            return Ok(Value::Null);
+        }
+
+        if name == SINGLETON_KEY {
+            return Ok(Value::Array(vec![value.serialize(self)?]));
+        }
+
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Self::Error> {
+        let mut map = Map::new();
+        map.insert(variant.to_string(), value.serialize(self)?);
+        Ok(Value::Object(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SeqWithoutExtensions(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let inner = self.serialize_seq(Some(len))?;
+        Ok(Variant { variant, inner })
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MapWithoutExtensions::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(MapWithoutExtensions::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let inner = MapWithoutExtensions::default();
+        Ok(Variant { variant, inner })
+    }
+}
+
+struct SeqWithoutExtensions(Vec<Value>);
+
+impl ser::SerializeSeq for SeqWithoutExtensions {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.push(value.serialize(WithoutExtensions)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Array(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SeqWithoutExtensions {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqWithoutExtensions {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+#[derive(Default)]
+struct MapWithoutExtensions {
+    map: Map<String, Value>,
+
+    /// The key of the entry being written, or `None` when it is an extension.
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for MapWithoutExtensions {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.key = match key.serialize(WithoutExtensions)? {
+            Value::Null => None,
+            Value::String(key) => Some(key),
+            key @ (Value::Bool(_) | Value::Number(_)) => Some(key.to_string()),
+            _ => return Err(ser::Error::custom("key must be a string")),
+        };
+
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if let Some(key) = self.key.take() {
+            self.map.insert(key, value.serialize(WithoutExtensions)?);
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Object(self.map))
+    }
+}
+
+impl ser::SerializeStruct for MapWithoutExtensions {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.map
+            .insert(key.to_string(), value.serialize(WithoutExtensions)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Value::Object(self.map))
+    }
+}
+
+/// An enum variant holding a tuple or struct, written as an object with a single field.
+struct Variant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> Variant<S> {
+    fn wrap(variant: &'static str, value: Value) -> Value {
+        let mut map = Map::new();
+        map.insert(variant.to_string(), value);
+        Value::Object(map)
+    }
+}
+
+impl ser::SerializeTupleVariant for Variant<SeqWithoutExtensions> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Self::wrap(
+            self.variant,
+            ser::SerializeSeq::end(self.inner)?,
+        ))
+    }
+}
+
+impl ser::SerializeStructVariant for Variant<MapWithoutExtensions> {
+    type Ok = Value;
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Value, Self::Error> {
+        Ok(Self::wrap(
+            self.variant,
+            ser::SerializeStruct::end(self.inner)?,
+        ))
+    }
+}
+
+impl Report {
+    /// Compare a document against the way this crate writes it back out, both with (`full`)
+    /// and without (`modeled`) any extensions.
+    ///
+    /// Fields that are missing from both outputs are known fields with an empty or default
+    /// value, which this crate leaves out when writing a document, while those only in `full`
+    /// are unknown fields. Arrays holding a single value are only kept in `modeled`, so `full`
+    /// may have the value on its own in their place.
+    fn strict(&mut self, path: &str, input: &Value, modeled: &Value, full: &Value) {
+        match (input, modeled, full) {
+            (Value::Object(input), Value::Object(modeled), Value::Object(full)) => {
+                for (key, value) in input {
+                    let path = format!("{path}/{}", escape(key));
+
+                    match (modeled.get(key), full.get(key)) {
+                        (Some(m), Some(f)) => self.strict(&path, value, m, f),
+                        (None, None) if is_omitted(value) => {}
+                        _ => self.push(Rule::UnknownField, path),
+                    }
+                }
+            }
+            (Value::Array(input), Value::Array(modeled), full) => {
+                let full = match full {
+                    Value::Array(full) => full.as_slice(),
+                    single => std::slice::from_ref(single),
+                };
+
+                for (i, ((value, m), f)) in input.iter().zip(modeled).zip(full).enumerate() {
+                    self.strict(&format!("{path}/{i}"), value, m, f);
+                }
+            }
+            (Value::Array(_), _, _) | (_, Value::Array(_) | Value::Object(_), _) => {
+                self.push(Rule::Shorthand, path.to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Feed<'a> {
+    /// Parse a feed, rejecting any unknown fields or shorthand forms.
+    ///
+    /// See [crate::v2_0::validate::strict] for more information.
+    pub fn from_json_strict(json: &'a str) -> Result<Self, Error> {
+        let input: Value = serde_json::from_str(json)?;
+        let feed: Feed<'a> = serde_json::from_str(json)?;
+        let modeled = feed.serialize(WithoutExtensions)?;
+        let full = serde_json::to_value(&feed)?;
+
+        let mut report = Report::default();
+        report.strict("", &input, &modeled, &full);
+
+        if report.is_empty() {
+            Ok(feed)
+        } else {
+            Err(Error::Invalid(report))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRATE_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+    fn pointers(report: &Report) -> Vec<(Rule, &str)> {
+        report
+            .issues
+            .iter()
+            .map(|i| (i.rule, i.pointer.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_strict_canonical() {
+        // Anything written by this crate can be read back in strictly, apart from the
+        // relations that it writes without an array when there is only one.
+        let json = std::fs::read_to_string(format!("{CRATE_DIR}/tests/test-feed-opds-io.in.json"))
+            .expect("valid file input");
+        let feed: Feed<'_> = serde_json::from_str(&json).expect("can parse feed");
+        let json = serde_json::to_string(&feed).expect("can serialize feed");
+
+        let Err(Error::Invalid(report)) = Feed::from_json_strict(&json) else {
+            panic!("feed with single relations should not be strictly valid");
+        };
+
+        assert!(
+            report
+                .issues
+                .iter()
+                .all(|i| i.rule == Rule::Shorthand && i.pointer.ends_with("/rel")),
+            "unexpected issues: {:?}",
+            report.issues
+        );
+    }
+
+    #[test]
+    fn test_strict_violations() {
+        let json = r#"{
+            "metadata": {"title": "Example", "vendor": true},
+            "links": [
+                {"rel": ["self"], "href": "https://example.com/feed.json", "templated": false},
+                {"rel": "search", "href": "https://example.com/search{?query}", "templated": true}
+            ],
+            "publications": [{
+                "metadata": {
+                    "title": "Book",
+                    "author": "Someone",
+                    "belongsTo": {"series": {"name": "Series", "position": 1}, "collection": []}
+                },
+                "links": [{
+                    "rel": ["http://opds-spec.org/acquisition"],
+                    "href": "https://example.com/book.epub",
+                    "properties": {"lcp_hashed_passphrase": "aGFzaA=="}
+                }]
+            }],
+            "groups": [{"metadata": {"title": "Group"}, "unknown": 1, "navigation": []}]
+        }"#;
+
+        let Err(Error::Invalid(report)) = Feed::from_json_strict(json) else {
+            panic!("feed should not be strictly valid");
+        };
+
+        assert_eq!(
+            pointers(&report),
+            vec![
+                (Rule::UnknownField, "/metadata/vendor"),
+                (Rule::Shorthand, "/links/1/rel"),
+                (Rule::Shorthand, "/publications/0/metadata/author"),
+                (Rule::Shorthand, "/publications/0/metadata/belongsTo/series"),
+                (
+                    Rule::UnknownField,
+                    "/publications/0/links/0/properties/lcp_hashed_passphrase"
+                ),
+                (Rule::UnknownField, "/groups/0/unknown"),
+            ]
+        );
+
+        // The same document is accepted when not parsing strictly.
+        let feed: Feed<'_> = serde_json::from_str(json).expect("can parse feed");
+        assert_eq!(
+            feed.metadata.extensions.get("vendor"),
+            Some(&Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn test_strict_empty_unknown_fields() {
+        // Unknown fields are reported even when their values look like defaults.
+        let json = r#"{
+            "metadata": {"title": "x", "bogus": false, "other": null, "empty": {}},
+            "links": [{"rel": ["self"], "href": "a", "unknownList": []}]
+        }"#;
+
+        let Err(Error::Invalid(report)) = Feed::from_json_strict(json) else {
+            panic!("feed should not be strictly valid");
+        };
+
+        assert_eq!(
+            pointers(&report),
+            vec![
+                (Rule::UnknownField, "/metadata/bogus"),
+                (Rule::UnknownField, "/metadata/other"),
+                (Rule::UnknownField, "/metadata/empty"),
+                (Rule::UnknownField, "/links/0/unknownList"),
+            ]
+        );
+
+        // Known fields left at their defaults are still accepted.
+        let json = r#"{
+            "metadata": {"title": "x", "subtitle": [], "modified": null},
+            "links": [{"rel": ["self"], "href": "a", "templated": false, "properties": {}}]
+        }"#;
+
+        Feed::from_json_strict(json).expect("can parse feed strictly");
+    }
+}
@@ -0,0 +1,465 @@
+//! Shared support for the XML-based formats in this crate
+//!
+//! OPDS 1.x catalogs are [Atom] documents that mix several XML namespaces. The constants in
+//! this module are the namespaces this crate understands, and [Error] is returned when reading
+//! an XML document fails.
+//!
+//! [Atom]: https://www.rfc-editor.org/rfc/rfc4287
+use std::borrow::Cow;
+use std::fmt;
+
+use quick_xml::NsReader;
+use quick_xml::XmlVersion;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::name::ResolveResult;
+
+/// The [Atom] namespace.
+///
+/// [Atom]: https://www.rfc-editor.org/rfc/rfc4287
+pub const ATOM: &str = "http://www.w3.org/2005/Atom";
+
+/// The OPDS catalog namespace, used for elements like `opds:price`.
+pub const OPDS: &str = "http://opds-spec.org/2010/catalog";
+
+/// The [Dublin Core] terms namespace.
+///
+/// [Dublin Core]: https://www.dublincore.org/specifications/dublin-core/dcmi-terms/
+pub const DC_TERMS: &str = "http://purl.org/dc/terms/";
+
+/// The legacy [Dublin Core] elements namespace, which some catalogs use instead of
+/// [DC_TERMS].
+///
+/// [Dublin Core]: https://www.dublincore.org/specifications/dublin-core/dces/
+pub const DC_ELEMENTS: &str = "http://purl.org/dc/elements/1.1/";
+
+/// The [OpenSearch 1.1] namespace.
+///
+/// [OpenSearch 1.1]: https://github.com/dewitt/opensearch/blob/master/opensearch-1-1-draft-6.md
+pub const OPENSEARCH: &str = "http://a9.com/-/spec/opensearch/1.1/";
+
+/// The namespace of the OpenSearch [Parameter extension], used for `parameters:Parameter`.
+///
+/// [Parameter extension]: https://github.com/dewitt/opensearch/blob/master/mediawiki/Specifications/OpenSearch/Extensions/Parameter/1.0/Draft%202.wiki
+pub const OPENSEARCH_PARAMETERS: &str =
+    "http://a9.com/-/spec/opensearch/extensions/parameters/1.0/";
+
+/// The [Atom Threading Extensions] namespace, used for `thr:count`.
+///
+/// [Atom Threading Extensions]: https://www.rfc-editor.org/rfc/rfc4685
+pub const THREADING: &str = "http://purl.org/syndication/thread/1.0";
+
+/// The namespace used by [Calibre] for metadata that has no standard equivalent, such as
+/// `calibre:series`.
+///
+/// [Calibre]: https://calibre-ebook.com/
+pub const CALIBRE: &str = "http://calibre.kovidgoyal.net/2009/metadata";
+
+/// The XHTML namespace, used for `type="xhtml"` text constructs.
+pub const XHTML: &str = "http://www.w3.org/1999/xhtml";
+
+/// The namespace bound to the reserved `xml` prefix.
+const XML: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// An error encountered while reading or writing an XML document.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The document is not well-formed XML.
+    Xml(quick_xml::Error),
+
+    /// The document does not contain the expected root element.
+    UnexpectedRoot {
+        expected: &'static str,
+        found: String,
+    },
+
+    /// A required element or attribute is missing.
+    Missing {
+        parent: &'static str,
+        name: &'static str,
+    },
+
+    /// An element or attribute contains a value that could not be parsed.
+    InvalidValue { name: &'static str, value: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(e) => write!(f, "malformed XML: {e}"),
+            Self::UnexpectedRoot { expected, found } if found.is_empty() => {
+                write!(
+                    f,
+                    "expected a <{expected}> element, but the document is empty"
+                )
+            }
+            Self::UnexpectedRoot { expected, found } => {
+                write!(f, "expected a <{expected}> element, found <{found}>")
+            }
+            Self::Missing { parent, name } => write!(f, "<{parent}> is missing {name}"),
+            Self::InvalidValue { name, value } => write!(f, "invalid value for {name}: {value:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Xml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(e: quick_xml::Error) -> Self {
+        Self::Xml(e)
+    }
+}
+
+/// A namespace-qualified attribute.
+#[derive(Clone, Debug)]
+pub(crate) struct Attribute {
+    pub ns: Option<String>,
+    pub name: String,
+    pub value: String,
+}
+
+/// A node within an element's content.
+#[derive(Clone, Debug)]
+pub(crate) enum Node {
+    Element(Element),
+    Text(String),
+}
+
+/// A namespace-qualified element, along with all of its content.
+#[derive(Clone, Debug)]
+pub(crate) struct Element {
+    pub ns: Option<String>,
+    pub name: String,
+    pub attrs: Vec<Attribute>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    pub fn new(ns: &str, name: &str) -> Self {
+        Self {
+            ns: Some(ns.to_string()),
+            name: name.to_string(),
+            attrs: vec![],
+            children: vec![],
+        }
+    }
+
+    /// Create an element containing only text.
+    pub fn text_element(ns: &str, name: &str, text: &str) -> Self {
+        Self::new(ns, name).with_text(text)
+    }
+
+    pub fn with_attr(self, name: &str, value: &str) -> Self {
+        self.with_ns_attr(None, name, value)
+    }
+
+    pub fn with_ns_attr(mut self, ns: Option<&str>, name: &str, value: &str) -> Self {
+        self.attrs.push(Attribute {
+            ns: ns.map(str::to_string),
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn with_child(mut self, child: Element) -> Self {
+        self.children.push(Node::Element(child));
+        self
+    }
+
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.children.push(Node::Text(text.to_string()));
+        self
+    }
+
+    pub fn is(&self, ns: &str, name: &str) -> bool {
+        self.ns.as_deref() == Some(ns) && self.name == name
+    }
+
+    /// Get the value of an attribute without a namespace.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|a| a.ns.is_none() && a.name == name)
+            .map(|a| a.value.as_str())
+    }
+
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    pub fn child(&self, ns: &str, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.is(ns, name))
+    }
+
+    /// The text directly within this element, with surrounding whitespace removed.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+
+        for node in self.children.iter() {
+            if let Node::Text(s) = node {
+                text.push_str(s);
+            }
+        }
+
+        text.trim().to_string()
+    }
+}
+
+fn resolve(res: ResolveResult<'_>) -> Option<String> {
+    match res {
+        ResolveResult::Bound(ns) => Some(ns.as_ref().to_string()),
+        ResolveResult::Unbound | ResolveResult::Unknown(_) => None,
+    }
+}
+
+fn start_element(reader: &NsReader<&[u8]>, start: &BytesStart<'_>) -> Result<Element, Error> {
+    let (ns, local) = reader.resolver().resolve_element(start.name());
+    let mut attrs = vec![];
+
+    for attr in start.attributes() {
+        let attr = attr.map_err(quick_xml::Error::from)?;
+
+        if attr.key.as_namespace_binding().is_some() {
+            continue;
+        }
+
+        let (ns, local) = reader.resolver().resolve_attribute(attr.key);
+        let value = attr.normalized_value(XmlVersion::Implicit1_0)?;
+
+        attrs.push(Attribute {
+            ns: resolve(ns),
+            name: local.into_inner().to_string(),
+            value: value.into_owned(),
+        });
+    }
+
+    Ok(Element {
+        ns: resolve(ns),
+        name: local.into_inner().to_string(),
+        attrs,
+        children: vec![],
+    })
+}
+
+fn push_text(stack: &mut [Element], text: Cow<'_, str>) {
+    let Some(parent) = stack.last_mut() else {
+        return;
+    };
+
+    if let Some(Node::Text(prev)) = parent.children.last_mut() {
+        prev.push_str(&text);
+    } else {
+        parent.children.push(Node::Text(text.into_owned()));
+    }
+}
+
+/// Parse a document into its root element.
+///
+/// Returns `None` if the document contains no elements.
+pub(crate) fn parse(xml: &str) -> Result<Option<Element>, Error> {
+    let mut reader = NsReader::from_str(xml);
+    let mut stack: Vec<Element> = vec![];
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => {
+                let element = start_element(&reader, &start)?;
+                stack.push(element);
+            }
+            Event::Empty(start) => {
+                let element = start_element(&reader, &start)?;
+
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(Node::Element(element)),
+                    None => return Ok(Some(element)),
+                }
+            }
+            Event::End(_) => {
+                let Some(element) = stack.pop() else {
+                    continue;
+                };
+
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(Node::Element(element)),
+                    None => return Ok(Some(element)),
+                }
+            }
+            Event::Text(text) => {
+                push_text(&mut stack, text.xml10_content());
+            }
+            Event::CData(text) => {
+                push_text(&mut stack, text.xml10_content());
+            }
+            Event::GeneralRef(entity) => {
+                let text = if let Some(c) = entity.resolve_char_ref()? {
+                    Cow::Owned(c.to_string())
+                } else if let Some(s) = resolve_predefined_entity(&entity) {
+                    Cow::Borrowed(s)
+                } else {
+                    Cow::Owned(format!("&{};", entity.xml10_content()))
+                };
+
+                push_text(&mut stack, text);
+            }
+            Event::Eof => return Ok(None),
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+        }
+    }
+}
+
+/// Parse a fragment of markup, such as one produced by [to_fragment], into a list of nodes.
+///
+/// Returns `None` if the fragment is not well-formed.
+pub(crate) fn parse_fragment(fragment: &str) -> Option<Vec<Node>> {
+    let wrapped = format!("<fragment>{fragment}</fragment>");
+
+    parse(&wrapped).ok().flatten().map(|root| root.children)
+}
+
+/// Write elements to a [quick_xml::Writer], using `prefixes` for any namespaces that have
+/// already been declared, and declaring any others as the default namespace where needed.
+pub(crate) struct ElementWriter<'p> {
+    prefixes: &'p [(&'p str, &'p str)],
+}
+
+impl<'p> ElementWriter<'p> {
+    pub fn new(prefixes: &'p [(&'p str, &'p str)]) -> Self {
+        Self { prefixes }
+    }
+
+    fn prefix(&self, ns: &str) -> Option<&'p str> {
+        if ns == XML {
+            return Some("xml");
+        }
+
+        self.prefixes
+            .iter()
+            .find(|(n, _)| *n == ns)
+            .map(|(_, p)| *p)
+    }
+
+    /// Write the root element of a document, declaring its namespace as the default and
+    /// declaring all of the prefixes.
+    pub fn write_root<W: std::io::Write>(
+        &self,
+        writer: &mut quick_xml::Writer<W>,
+        root: &Element,
+    ) -> std::io::Result<()> {
+        let mut decls: Vec<(String, &str)> = vec![];
+
+        if let Some(ns) = root.ns.as_deref() {
+            decls.push(("xmlns".into(), ns));
+        }
+
+        for (ns, prefix) in self.prefixes.iter() {
+            decls.push((format!("xmlns:{prefix}"), ns));
+        }
+
+        self.write_element(writer, root, root.ns.as_deref(), decls)
+    }
+
+    pub fn write<W: std::io::Write>(
+        &self,
+        writer: &mut quick_xml::Writer<W>,
+        element: &Element,
+        default_ns: Option<&str>,
+    ) -> std::io::Result<()> {
+        self.write_element(writer, element, default_ns, vec![])
+    }
+
+    fn write_element<'e, W: std::io::Write>(
+        &self,
+        writer: &mut quick_xml::Writer<W>,
+        element: &'e Element,
+        default_ns: Option<&'e str>,
+        mut decls: Vec<(String, &'e str)>,
+    ) -> std::io::Result<()> {
+        let mut child_ns = default_ns;
+
+        let name = match element.ns.as_deref() {
+            Some(ns) if Some(ns) == default_ns => element.name.clone(),
+            Some(ns) => match self.prefix(ns) {
+                Some(prefix) => format!("{prefix}:{}", element.name),
+                None => {
+                    decls.push(("xmlns".into(), ns));
+                    child_ns = Some(ns);
+                    element.name.clone()
+                }
+            },
+            None if default_ns.is_some() => {
+                decls.push(("xmlns".into(), ""));
+                child_ns = None;
+                element.name.clone()
+            }
+            None => element.name.clone(),
+        };
+
+        let mut start = BytesStart::new(name.as_str());
+
+        for (key, value) in decls.iter() {
+            start.push_attribute((key.as_str(), *value));
+        }
+
+        for attr in element.attrs.iter() {
+            let key = match attr.ns.as_deref() {
+                None => attr.name.clone(),
+                Some(ns) => match self.prefix(ns) {
+                    Some(prefix) => format!("{prefix}:{}", attr.name),
+                    None => {
+                        let prefix = format!("ns{}", decls.len());
+                        start.push_attribute((format!("xmlns:{prefix}").as_str(), ns));
+                        decls.push((prefix.clone(), ns));
+                        format!("{prefix}:{}", attr.name)
+                    }
+                },
+            };
+
+            start.push_attribute((key.as_str(), attr.value.as_str()));
+        }
+
+        if element.children.is_empty() {
+            return writer.write_event(Event::Empty(start));
+        }
+
+        writer.write_event(Event::Start(start))?;
+
+        for child in element.children.iter() {
+            match child {
+                Node::Element(e) => self.write(writer, e, child_ns)?,
+                Node::Text(s) => writer.write_event(Event::Text(BytesText::new(s)))?,
+            }
+        }
+
+        writer.write_event(Event::End(BytesEnd::new(name.as_str())))
+    }
+}
+
+/// Serialize a list of nodes as a standalone fragment of markup.
+pub(crate) fn to_fragment(nodes: &[Node]) -> String {
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    let ew = ElementWriter::new(&[]);
+
+    for node in nodes.iter() {
+        let res = match node {
+            Node::Element(e) => ew.write(&mut writer, e, None),
+            Node::Text(s) => writer.write_event(Event::Text(BytesText::new(s))),
+        };
+
+        // Writing into a Vec<u8> cannot fail.
+        res.expect("can write to buffer");
+    }
+
+    String::from_utf8(writer.into_inner()).expect("writer produces UTF-8")
+}